@@ -0,0 +1,277 @@
+// A reader for the call graphs that tools emit in Graphviz DOT format. Only the subset of the
+// language used by the evaluated tools is supported: node statements with attribute lists and edge
+// statements, one per line or separated by semicolons.
+//
+//     Node0x55a0f5c865b0 [shape=record,label="{_ZN8generics5bench3run17h89d9cc231ea7a78eE}"];
+//     Node0x55a0f5c1c8a0 -> Node0x55a0f5c570b0;
+//
+//     1 [ label = "\"generics::bench::run\"" ]
+//     1 -> 2 [ ]
+
+use std::collections::BTreeMap;
+
+use crate::error::Error;
+use crate::error::Result;
+
+#[derive(Default)]
+pub struct Dot {
+    // Node identifiers mapped to their labels. Nodes without a label are labelled by their
+    // identifier.
+    pub nodes: BTreeMap<String, String>,
+    pub edges: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Arrow,
+    Punct(char),
+}
+
+pub fn parse(source: &str) -> Result<Dot> {
+    let tokens = tokenize(source)?;
+    let mut dot = Dot::default();
+    let mut pos = 0;
+
+    // Skip the graph header up to and including the opening brace.
+    while pos < tokens.len() && tokens[pos] != Token::Punct('{') {
+        pos += 1;
+    }
+    pos += 1;
+
+    while pos < tokens.len() {
+        let id = match &tokens[pos] {
+            Token::Id(id) => id.clone(),
+            _ => {
+                pos += 1;
+                continue;
+            }
+        };
+        pos = skip_port(&tokens, pos + 1);
+
+        let mut chain = vec![id];
+        while tokens.get(pos) == Some(&Token::Arrow) {
+            match tokens.get(pos + 1) {
+                Some(Token::Id(id)) => chain.push(id.clone()),
+                _ => return Err(Error::new("malformed edge statement")),
+            }
+            pos = skip_port(&tokens, pos + 2);
+        }
+
+        let mut attributes = BTreeMap::new();
+        if tokens.get(pos) == Some(&Token::Punct('[')) {
+            pos += 1;
+            while pos < tokens.len() && tokens[pos] != Token::Punct(']') {
+                if let (Some(Token::Id(key)), Some(Token::Punct('=')), Some(Token::Id(value))) =
+                    (tokens.get(pos), tokens.get(pos + 1), tokens.get(pos + 2))
+                {
+                    attributes.insert(key.clone(), value.clone());
+                    pos += 3;
+                } else {
+                    pos += 1;
+                }
+            }
+            pos += 1;
+        }
+
+        // Graph attributes (label="Call graph") and default attribute statements are skipped.
+        if tokens.get(pos) == Some(&Token::Punct('=')) {
+            pos += 2;
+            continue;
+        }
+        if chain.len() == 1 && ["graph", "node", "edge"].contains(&chain[0].as_str()) {
+            continue;
+        }
+
+        if chain.len() == 1 {
+            let label = attributes.get("label").map(|label| record_label(label));
+            let id = chain.pop().unwrap();
+            let label = label.unwrap_or_else(|| id.clone());
+            dot.nodes.insert(id, label);
+        } else {
+            for pair in chain.windows(2) {
+                for id in pair {
+                    if !dot.nodes.contains_key(id) {
+                        dot.nodes.insert(id.clone(), id.clone());
+                    }
+                }
+                dot.edges.push((pair[0].clone(), pair[1].clone()));
+            }
+        }
+    }
+    Ok(dot)
+}
+
+// Writes a graph in the subset of the language that 'parse' reads, e.g., for graphs that the harness
+// builds itself.
+pub fn write(dot: &Dot, name: &str) -> String {
+    let mut output = format!("digraph {} {{\n", quote(name));
+    for (id, label) in &dot.nodes {
+        output.push_str(&format!("    {} [label={}];\n", quote(id), quote(label)));
+    }
+    for (caller, callee) in &dot.edges {
+        output.push_str(&format!("    {} -> {};\n", quote(caller), quote(callee)));
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn quote(id: &str) -> String {
+    format!("\"{}\"", id.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn skip_port(tokens: &[Token], pos: usize) -> usize {
+    match (tokens.get(pos), tokens.get(pos + 1)) {
+        (Some(Token::Punct(':')), Some(Token::Id(_))) => pos + 2,
+        _ => pos,
+    }
+}
+
+// LLVM opt emits record shaped nodes whose label is enclosed in braces, MIRAI quotes its labels.
+fn record_label(label: &str) -> String {
+    let label = label.trim();
+    let label = match label.strip_prefix('{').and_then(|l| l.strip_suffix('}')) {
+        Some(fields) => fields.split('|').next().unwrap_or(""),
+        None => label,
+    };
+    label.trim().trim_matches('"').to_string()
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '/' if chars.peek() == Some(&'/') => {
+                while chars.peek().is_some_and(|&c| c != '\n') {
+                    chars.next();
+                }
+            }
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some('"') => value.push('"'),
+                            Some('\\') => value.push('\\'),
+                            Some('\n') => {}
+                            Some(c) => {
+                                value.push('\\');
+                                value.push(c);
+                            }
+                            None => break,
+                        },
+                        Some('"') => break,
+                        Some(c) => value.push(c),
+                        None => return Err(Error::new("unterminated string in DOT file")),
+                    }
+                }
+                tokens.push(Token::Id(value));
+            }
+            '-' if chars.peek() == Some(&'>') || chars.peek() == Some(&'-') => {
+                chars.next();
+                tokens.push(Token::Arrow);
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let mut value = c.to_string();
+                while let Some(&c) = chars.peek() {
+                    if c.is_alphanumeric() || c == '_' || c == '.' {
+                        value.push(c);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Id(value));
+            }
+            c => tokens.push(Token::Punct(c)),
+        }
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edges(dot: &Dot) -> Vec<(&str, &str)> {
+        dot.edges
+            .iter()
+            .map(|(caller, callee)| (dot.nodes[caller].as_str(), dot.nodes[callee].as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_llvm_record_nodes() {
+        let dot = parse(
+            r#"digraph "Call graph: main.ll" {
+                label="Call graph: main.ll";
+
+                Node0x1 [shape=record,label="{external node}"];
+                Node0x1 -> Node0x2;
+                Node0x2 [shape=record,label="{_ZN8generics5bench3run17h89d9cc231ea7a78eE|{<s0>}}"];
+                Node0x2:s0 -> Node0x3;
+                Node0x3 [shape=record,label="{_ZN4core3ptr13drop_in_place17h0123456789abcdefE}"];
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(dot.nodes.len(), 3);
+        assert_eq!(
+            edges(&dot),
+            [
+                (
+                    "external node",
+                    "_ZN8generics5bench3run17h89d9cc231ea7a78eE"
+                ),
+                (
+                    "_ZN8generics5bench3run17h89d9cc231ea7a78eE",
+                    "_ZN4core3ptr13drop_in_place17h0123456789abcdefE"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_quoted_labels_and_edge_chains() {
+        let dot = parse(
+            r#"digraph {
+                node [shape=box];
+                1 [ label = "\"generics::bench::run\"" ]
+                2 [ label = "\"generics::lib::monomorphized\"" ]
+                1 -> 2 -> 3 [ ]
+                // A comment.
+                "a b" -> 1; 3 -- 1
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(dot.nodes["1"], "generics::bench::run");
+        assert_eq!(dot.nodes["3"], "3");
+        assert_eq!(
+            edges(&dot),
+            [
+                ("generics::bench::run", "generics::lib::monomorphized"),
+                ("generics::lib::monomorphized", "3"),
+                ("a b", "generics::bench::run"),
+                ("3", "generics::bench::run"),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_what_it_writes() {
+        let mut dot = Dot::default();
+        dot.nodes
+            .insert("a".to_string(), "<\"quoted\" as Trait>::m".to_string());
+        dot.nodes.insert("b".to_string(), "back\\slash".to_string());
+        dot.edges.push(("a".to_string(), "b".to_string()));
+        let read = parse(&write(&dot, "graph")).unwrap();
+        assert_eq!(read.nodes, dot.nodes);
+        assert_eq!(read.edges, dot.edges);
+    }
+
+    #[test]
+    fn rejects_unterminated_strings() {
+        assert!(parse("digraph { \"a -> b }").is_err());
+    }
+}
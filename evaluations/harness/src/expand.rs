@@ -0,0 +1,181 @@
+// Expansion of the benchmark's macro_rules! macros, as far as it is needed to find what an
+// invocation adds to the body of the invoking function, e.g., closures, which rustc numbers in the
+// order they appear in the expanded body.
+//
+// Only the matchers the benchmark uses are supported: a comma separated list of metavariables
+// ($a:expr, $b:ty) or a repetition of a single one ($( $x:expr ),*). Nested macro_rules!
+// definitions and other fragments of the macro-by-example syntax are not.
+
+use std::collections::HashMap;
+
+use proc_macro2::Delimiter;
+use proc_macro2::Group;
+use proc_macro2::TokenStream;
+use proc_macro2::TokenTree;
+
+// The tokens bound to each metavariable, one per repetition for repeated metavariables.
+type Bindings = HashMap<String, Vec<TokenStream>>;
+
+// Expands an invocation with the first rule of a macro_rules! definition that matches it. 'rules'
+// are the tokens between the braces of the definition and 'input' those of the invocation.
+pub fn expand(rules: &TokenStream, input: &TokenStream) -> Option<TokenStream> {
+    let args = arguments(input.clone());
+    rules
+        .clone()
+        .into_iter()
+        .collect::<Vec<_>>()
+        .split(|token| is_punct(token, ';'))
+        .filter_map(rule)
+        .find_map(|(matcher, transcriber)| {
+            let bindings = bind(matcher, &args)?;
+            Some(transcribe(transcriber, &bindings, None))
+        })
+}
+
+// The comma separated arguments of an invocation.
+fn arguments(input: TokenStream) -> Vec<TokenStream> {
+    let tokens: Vec<TokenTree> = input.into_iter().collect();
+    tokens
+        .split(|token| is_punct(token, ','))
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| arg.iter().cloned().collect())
+        .collect()
+}
+
+// Splits a rule, (matcher) => { transcriber }, into its matcher and transcriber.
+fn rule(tokens: &[TokenTree]) -> Option<(TokenStream, TokenStream)> {
+    match tokens {
+        [TokenTree::Group(matcher), eq, gt, TokenTree::Group(transcriber)]
+            if is_punct(eq, '=') && is_punct(gt, '>') =>
+        {
+            Some((matcher.stream(), transcriber.stream()))
+        }
+        _ => None,
+    }
+}
+
+fn bind(matcher: TokenStream, args: &[TokenStream]) -> Option<Bindings> {
+    let tokens: Vec<TokenTree> = matcher.into_iter().collect();
+    let mut bindings = Bindings::new();
+
+    // $( $x:expr ),* binds all arguments to $x.
+    if let [dollar, TokenTree::Group(group), ..] = tokens.as_slice() {
+        if is_punct(dollar, '$') && group.delimiter() == Delimiter::Parenthesis {
+            let name = metavariables(&group.stream()).into_iter().next()?;
+            let repetition = tokens.last().map(|token| token.to_string());
+            if repetition.as_deref() == Some("+") && args.is_empty() {
+                return None;
+            }
+            bindings.insert(name, args.to_vec());
+            return Some(bindings);
+        }
+    }
+
+    let names = metavariables(&tokens.into_iter().collect());
+    if names.len() != args.len() {
+        return None;
+    }
+    for (name, arg) in names.into_iter().zip(args) {
+        bindings.insert(name, vec![arg.clone()]);
+    }
+    Some(bindings)
+}
+
+// The names of the metavariables ($name:fragment) of a matcher, in order.
+fn metavariables(matcher: &TokenStream) -> Vec<String> {
+    let tokens: Vec<TokenTree> = matcher.clone().into_iter().collect();
+    tokens
+        .windows(3)
+        .filter_map(|window| match window {
+            [dollar, TokenTree::Ident(name), colon]
+                if is_punct(dollar, '$') && is_punct(colon, ':') =>
+            {
+                Some(name.to_string())
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+// Substitutes the metavariables of a transcriber. 'repetition' is the index of the repetition
+// being transcribed, if any.
+fn transcribe(
+    transcriber: TokenStream,
+    bindings: &Bindings,
+    repetition: Option<usize>,
+) -> TokenStream {
+    let mut output = TokenStream::new();
+    let mut tokens = transcriber.into_iter();
+    while let Some(token) = tokens.next() {
+        match token {
+            TokenTree::Punct(ref dollar) if dollar.as_char() == '$' => match tokens.next() {
+                Some(TokenTree::Ident(name)) => {
+                    let bound = bindings
+                        .get(&name.to_string())
+                        .and_then(|bound| bound.get(repetition.unwrap_or(0)));
+                    // Fragments are substituted as a whole, as if parenthesized.
+                    if let Some(bound) = bound {
+                        output
+                            .extend([TokenTree::Group(Group::new(Delimiter::None, bound.clone()))]);
+                    }
+                }
+                Some(TokenTree::Group(group)) => {
+                    // $( ... ) followed by an optional separator and *, + or ?.
+                    let mut separator = None;
+                    if let Some(TokenTree::Punct(punct)) = tokens.next() {
+                        if !['*', '+', '?'].contains(&punct.as_char()) {
+                            separator = Some(punct);
+                            tokens.next();
+                        }
+                    }
+                    let count = metavariables_used(&group.stream())
+                        .iter()
+                        .filter_map(|name| bindings.get(name))
+                        .map(Vec::len)
+                        .max()
+                        .unwrap_or(0);
+                    for i in 0..count {
+                        if i > 0 {
+                            if let Some(separator) = &separator {
+                                output.extend([TokenTree::Punct(separator.clone())]);
+                            }
+                        }
+                        output.extend(transcribe(group.stream(), bindings, Some(i)));
+                    }
+                }
+                Some(token) => output.extend([TokenTree::Punct(dollar.clone()), token]),
+                None => output.extend([TokenTree::Punct(dollar.clone())]),
+            },
+            TokenTree::Group(group) => {
+                let mut transcribed = Group::new(
+                    group.delimiter(),
+                    transcribe(group.stream(), bindings, repetition),
+                );
+                transcribed.set_span(group.span());
+                output.extend([TokenTree::Group(transcribed)]);
+            }
+            token => output.extend([token]),
+        }
+    }
+    output
+}
+
+// The names of the metavariables ($name) used by a transcriber.
+fn metavariables_used(transcriber: &TokenStream) -> Vec<String> {
+    let mut names = Vec::new();
+    let tokens: Vec<TokenTree> = transcriber.clone().into_iter().collect();
+    for (pos, token) in tokens.iter().enumerate() {
+        match (token, tokens.get(pos + 1)) {
+            (dollar, Some(TokenTree::Ident(name))) if is_punct(dollar, '$') => {
+                names.push(name.to_string())
+            }
+            (TokenTree::Group(group), _) => names.extend(metavariables_used(&group.stream())),
+            _ => {}
+        }
+    }
+    names
+}
+
+fn is_punct(token: &TokenTree, c: char) -> bool {
+    matches!(token, TokenTree::Punct(punct) if punct.as_char() == c)
+}
@@ -0,0 +1,728 @@
+// A static HTML explorer of the benchmark's call sites and of the results of the evaluated tools.
+//
+// The explorer is a directory of self-contained pages, without scripts or external assets, that
+// can be opened from the file system:
+//
+//     index.html                  the packages, as sections of src/README.md, with the share of
+//                                 the calls of each package that each stored record resolved
+//     <package>.html              the package's section of src/README.md, its call sites and
+//                                 their results, and its source files
+//     src-<...>.html              a source file, e.g., src-structs-src-lib.rs.html, with its call
+//                                 sites highlighted
+//
+// Hovering over a call site shows its annotation, kind, tags, the expected targets at each tier of
+// precision and what each record's graph has for it. The tiers are those that scores match at:
+//
+//     instance          the edges between instances of generic callers and callees, if annotated
+//     definition        the annotated targets
+//     implementation    for calls of a trait's method, the methods of the trait's implementations,
+//                       which the expected graph accepts as well
+//
+// A record resolves a call if its graph, with instances collapsed into their definitions, has an
+// edge from the caller to a function that matches a target (see score.rs).
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::index::FunctionKind;
+use crate::index::Index;
+use crate::packages;
+use crate::score;
+use crate::score::Layer;
+use crate::score::MatchMode;
+use crate::store::Record;
+use crate::truth::CallSite;
+use crate::truth::Truth;
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+a { color: #0550ae; }
+table { border-collapse: collapse; margin: 1em 0; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+code, pre { font-family: monospace; }
+.source { font-family: monospace; white-space: pre; }
+.line { position: relative; }
+.line .number { display: inline-block; width: 4em; color: #888; text-align: right;
+    margin-right: 1em; user-select: none; }
+.site { background: #fff5c0; cursor: help; }
+.site.resolved { background: #d8f5d0; }
+.site.partial { background: #ffe2b8; }
+.site.missed { background: #ffd0d0; }
+.popup { display: none; position: absolute; left: 6em; top: 1.4em; z-index: 1;
+    background: #fff; border: 1px solid #888; padding: 0.5em 1em; white-space: normal;
+    width: 60em; font-family: sans-serif; box-shadow: 2px 2px 6px #aaa; }
+.site:hover .popup, .site:focus .popup { display: block; }
+.resolved-text { color: #1a7f37; }
+.missed-text { color: #cf222e; }
+";
+
+// A stored record, named after its key.
+pub struct Tool {
+    pub name: String,
+    record: Record,
+    // The graph of each package, with instances collapsed into their definitions.
+    graphs: BTreeMap<String, CallGraph>,
+}
+
+impl Tool {
+    pub fn new(record: Record) -> Tool {
+        let key = &record.key;
+        let name = format!(
+            "{} {} ({}, {}, {})",
+            key.tool,
+            key.tool_version,
+            key.cfg,
+            key.mode.name(),
+            key.commit.chars().take(12).collect::<String>()
+        );
+        let graphs = record
+            .packages
+            .iter()
+            .map(|(package, result)| {
+                let graph = MatchMode::Collapsed.graph(result.graph.clone());
+                (package.clone(), graph)
+            })
+            .collect();
+        Tool {
+            name,
+            record,
+            graphs,
+        }
+    }
+}
+
+// What a tool's graph has for a call site.
+enum Outcome {
+    // The site names no target in the benchmark crates.
+    NotScored,
+    // The tool produced no graph for the package.
+    NoGraph(String),
+    // The callees that match a target.
+    Resolved(Vec<String>),
+    Missed,
+}
+
+fn outcome(tool: &Tool, site: &CallSite, index: &Index) -> Outcome {
+    if !site.is_scored(Layer::Benchmark) {
+        return Outcome::NotScored;
+    }
+    let graph = match tool.graphs.get(&site.package) {
+        Some(graph) => graph,
+        None => {
+            let reason = match tool.record.runs.get(&site.package) {
+                Some(run) => format!("the run {}", run.status.name()),
+                None => "not evaluated".to_string(),
+            };
+            return Outcome::NoGraph(reason);
+        }
+    };
+    let callees: Vec<String> = graph
+        .callees(&site.caller)
+        .filter(|callee| {
+            site.layer_targets(Layer::Benchmark)
+                .iter()
+                .any(|target| score::matches(index, target, callee))
+        })
+        .map(str::to_string)
+        .collect();
+    if callees.is_empty() {
+        Outcome::Missed
+    } else {
+        Outcome::Resolved(callees)
+    }
+}
+
+// Writes the explorer to 'output'.
+pub fn generate(
+    root: &Path,
+    index: &Index,
+    truth: &Truth,
+    tools: &[Tool],
+    output: &Path,
+) -> Result<()> {
+    fs::create_dir_all(output)
+        .map_err(|e| Error::new(format!("cannot create {}: {}", output.display(), e)))?;
+    let readme_path = root.join("src").join("README.md");
+    let readme = fs::read_to_string(&readme_path)
+        .map_err(|e| Error::new(format!("cannot read {}: {}", readme_path.display(), e)))?;
+    let readme = Readme::parse(root, &readme);
+
+    write_page(
+        output,
+        "index.html",
+        &index_page(&readme, truth, tools, index),
+    )?;
+    for package in packages::all() {
+        let page = package_page(&package.name, &readme, truth, tools, index);
+        write_page(output, &format!("{}.html", package.name), &page)?;
+    }
+    for (package, file) in &index.files {
+        let source = fs::read_to_string(root.join(file))
+            .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+        let page = source_page(package, file, &source, truth, tools, index);
+        write_page(output, &file_page(file), &page)?;
+    }
+    Ok(())
+}
+
+fn write_page(output: &Path, name: &str, body: &str) -> Result<()> {
+    let path = output.join(name);
+    fs::write(&path, body)
+        .map_err(|e| Error::new(format!("cannot write {}: {}", path.display(), e)))
+}
+
+fn page(title: &str, body: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n{}</body>\n</html>\n",
+        escape(title),
+        STYLE,
+        body
+    )
+}
+
+// The page of a source file, e.g., src-structs-src-lib.rs.html for src/structs/src/lib.rs.
+fn file_page(file: &Path) -> String {
+    let segments: Vec<_> = file
+        .iter()
+        .map(|segment| segment.to_string_lossy())
+        .collect();
+    format!("{}.html", segments.join("-"))
+}
+
+fn index_page(readme: &Readme, truth: &Truth, tools: &[Tool], index: &Index) -> String {
+    let mut body = String::new();
+    body.push_str("<h1>Benchmark call sites</h1>\n");
+    body.push_str(&readme.render(&readme.preamble));
+    if tools.is_empty() {
+        body.push_str("<p>The results store holds no records.</p>\n");
+    }
+
+    body.push_str(
+        "<table>\n<tr><th>Package</th><th>Category</th><th>Description</th><th>Calls</th>",
+    );
+    for tool in tools {
+        let _ = write!(body, "<th>{}</th>", escape(&tool.name));
+    }
+    body.push_str("</tr>\n");
+    for package in packages::all() {
+        let sites: Vec<&CallSite> = truth.package_sites(&package.name).collect();
+        let scored = sites
+            .iter()
+            .filter(|site| site.is_scored(Layer::Benchmark))
+            .count();
+        let description = readme
+            .section(&package.name)
+            .and_then(|section| section.entry("Description"))
+            .map(|lines| readme.inline(&lines.join(" ")))
+            .unwrap_or_default();
+        let _ = write!(
+            body,
+            "<tr><td><a href=\"{0}.html\">{0}</a></td><td>{1}</td><td>{2}</td><td>{3}</td>",
+            package.name, package.category, description, scored
+        );
+        for tool in tools {
+            let resolved = sites
+                .iter()
+                .filter(|site| matches!(outcome(tool, site, index), Outcome::Resolved(_)))
+                .count();
+            let _ = write!(body, "<td>{}</td>", share(resolved, scored));
+        }
+        body.push_str("</tr>\n");
+    }
+    body.push_str("</table>\n");
+    page("Benchmark call sites", &body)
+}
+
+fn package_page(
+    package: &str,
+    readme: &Readme,
+    truth: &Truth,
+    tools: &[Tool],
+    index: &Index,
+) -> String {
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p><a href=\"index.html\">Packages</a></p>\n<h1>{}</h1>",
+        escape(package)
+    );
+    if let Some(section) = readme.section(package) {
+        for (name, lines) in &section.entries {
+            let _ = writeln!(body, "<h3>{}</h3>", escape(name));
+            body.push_str(&readme.render(lines));
+        }
+    }
+
+    body.push_str("<h2>Call sites</h2>\n");
+    let sites: Vec<&CallSite> = truth.package_sites(package).collect();
+    if sites.is_empty() {
+        body.push_str("<p>The package has no annotated calls.</p>\n");
+    } else {
+        body.push_str("<table>\n<tr><th>Call</th><th>Caller</th><th>Kind</th><th>Target</th>");
+        for tool in tools {
+            let _ = write!(body, "<th>{}</th>", escape(&tool.name));
+        }
+        body.push_str("</tr>\n");
+        for site in &sites {
+            let _ = write!(
+                body,
+                "<tr><td><a href=\"{}#L{}\">{}:{}</a></td><td><code>{}</code></td>\
+                 <td>{}</td><td><code>{}</code></td>",
+                file_page(&site.file),
+                site.line,
+                escape(&site.file.display().to_string()),
+                site.line,
+                escape(&site.caller),
+                site.kind.annotation(),
+                escape(&site.annotation)
+            );
+            for tool in tools {
+                let _ = write!(
+                    body,
+                    "<td>{}</td>",
+                    outcome_text(&outcome(tool, site, index))
+                );
+            }
+            body.push_str("</tr>\n");
+        }
+        body.push_str("</table>\n");
+    }
+
+    body.push_str("<h2>Source files</h2>\n<ul>\n");
+    for (_, file) in index.files.iter().filter(|(name, _)| name == package) {
+        let _ = writeln!(
+            body,
+            "<li><a href=\"{}\">{}</a></li>",
+            file_page(file),
+            escape(&file.display().to_string())
+        );
+    }
+    body.push_str("</ul>\n");
+    page(package, &body)
+}
+
+fn source_page(
+    package: &str,
+    file: &Path,
+    source: &str,
+    truth: &Truth,
+    tools: &[Tool],
+    index: &Index,
+) -> String {
+    // The sites at each line: calls, and the invocations of macros that contain calls.
+    let mut lines: BTreeMap<usize, Vec<&CallSite>> = BTreeMap::new();
+    for site in &truth.sites {
+        if site.file == file {
+            lines.entry(site.line).or_default().push(site);
+        }
+        let invoked_here = index
+            .enclosing_function(&site.caller)
+            .is_some_and(|function| index.functions[function].file == file);
+        if let (Some(invocation), true) = (site.invocation, invoked_here) {
+            lines.entry(invocation).or_default().push(site);
+        }
+    }
+
+    let mut body = String::new();
+    let _ = writeln!(
+        body,
+        "<p><a href=\"index.html\">Packages</a> / <a href=\"{0}.html\">{0}</a></p>\n<h1>{1}</h1>",
+        escape(package),
+        escape(&file.display().to_string())
+    );
+    body.push_str("<div class=\"source\">");
+    for (number, line) in source.lines().enumerate() {
+        let number = number + 1;
+        let sites = match lines.get(&number) {
+            Some(sites) => sites,
+            None => {
+                let _ = writeln!(
+                    body,
+                    "<div class=\"line\" id=\"L{0}\"><span class=\"number\">{0}</span>{1}</div>",
+                    number,
+                    escape(line)
+                );
+                continue;
+            }
+        };
+        let _ = write!(
+            body,
+            "<div class=\"line site {0}\" id=\"L{1}\" tabindex=\"0\">\
+             <span class=\"number\">{1}</span>{2}<div class=\"popup\">",
+            status(sites, tools, index),
+            number,
+            escape(line)
+        );
+        for site in sites {
+            body.push_str(&popup(site, tools, index));
+        }
+        body.push_str("</div></div>\n");
+    }
+    body.push_str("</div>\n");
+    page(&file.display().to_string(), &body)
+}
+
+// The class of a highlighted line: whether all, some or none of the tools resolved its calls.
+fn status(sites: &[&CallSite], tools: &[Tool], index: &Index) -> &'static str {
+    let mut resolved = 0;
+    let mut judged = 0;
+    for site in sites {
+        for tool in tools {
+            match outcome(tool, site, index) {
+                Outcome::Resolved(_) => {
+                    resolved += 1;
+                    judged += 1;
+                }
+                Outcome::Missed => judged += 1,
+                Outcome::NotScored | Outcome::NoGraph(_) => {}
+            }
+        }
+    }
+    match (resolved, judged) {
+        (_, 0) => "",
+        (0, _) => "missed",
+        (resolved, judged) if resolved == judged => "resolved",
+        _ => "partial",
+    }
+}
+
+fn popup(site: &CallSite, tools: &[Tool], index: &Index) -> String {
+    let mut popup = String::new();
+    let _ = write!(
+        popup,
+        "<p><b>{}</b> in <code>{}</code></p><p><code>{}</code></p>",
+        site.kind.annotation(),
+        escape(&site.caller),
+        escape(&site.annotation)
+    );
+    if !site.description.is_empty() {
+        let _ = write!(popup, "<p>{}</p>", escape(&site.description));
+    }
+    if let Some(tags) = &site.tags {
+        let _ = write!(
+            popup,
+            "<p>Tags: <code>{}</code></p>",
+            escape(&tags.to_string())
+        );
+    }
+
+    popup.push_str("<table><tr><th>Tier</th><th>Expected targets</th></tr>");
+    let instances: Vec<String> = site
+        .instances
+        .iter()
+        .map(|edge| format!("{} → {}", edge.caller, edge.callee))
+        .collect();
+    let implementations: Vec<String> = site
+        .targets
+        .iter()
+        .flat_map(|target| {
+            index.functions.iter().filter(move |function| {
+                function.kind == FunctionKind::TraitImpl
+                    && score::matches(index, target, &function.name)
+            })
+        })
+        .map(|function| function.name.clone())
+        .collect();
+    for (tier, targets) in [
+        ("instance", &instances),
+        ("definition", &site.targets),
+        ("implementation", &implementations),
+    ] {
+        if targets.is_empty() {
+            continue;
+        }
+        let targets: Vec<String> = targets
+            .iter()
+            .map(|target| format!("<code>{}</code>", escape(target)))
+            .collect();
+        let _ = write!(
+            popup,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            tier,
+            targets.join("<br>")
+        );
+    }
+    popup.push_str("</table>");
+
+    if !tools.is_empty() {
+        popup.push_str("<table><tr><th>Record</th><th>Result</th></tr>");
+        for tool in tools {
+            let _ = write!(
+                popup,
+                "<tr><td>{}</td><td>{}</td></tr>",
+                escape(&tool.name),
+                outcome_text(&outcome(tool, site, index))
+            );
+        }
+        popup.push_str("</table>");
+    }
+    popup
+}
+
+fn outcome_text(outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::NotScored => "not scored".to_string(),
+        Outcome::NoGraph(reason) => escape(reason),
+        Outcome::Resolved(callees) => {
+            let callees: Vec<String> = callees
+                .iter()
+                .map(|callee| format!("<code>{}</code>", escape(callee)))
+                .collect();
+            format!(
+                "<span class=\"resolved-text\">resolved</span>: {}",
+                callees.join(", ")
+            )
+        }
+        Outcome::Missed => "<span class=\"missed-text\">missed</span>".to_string(),
+    }
+}
+
+fn share(resolved: usize, sites: usize) -> String {
+    if sites == 0 {
+        "-".to_string()
+    } else {
+        format!(
+            "{}/{} ({:.0}%)",
+            resolved,
+            sites,
+            100.0 * resolved as f64 / sites as f64
+        )
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// The sections of src/README.md, as far as the explorer shows them: a section per package whose
+// entries are '- Name:' items with indented lines, bullets ('* ...') among them.
+struct Readme {
+    root: PathBuf,
+    preamble: Vec<String>,
+    sections: Vec<Section>,
+    // The destinations of the reference links, e.g., '3' -> 'static_dispatch/src/lib.rs#L8'.
+    links: BTreeMap<String, String>,
+}
+
+struct Section {
+    name: String,
+    entries: Vec<(String, Vec<String>)>,
+}
+
+impl Section {
+    fn entry(&self, name: &str) -> Option<&Vec<String>> {
+        self.entries
+            .iter()
+            .find(|(entry, _)| entry == name)
+            .map(|(_, lines)| lines)
+    }
+}
+
+impl Readme {
+    fn parse(root: &Path, source: &str) -> Readme {
+        let mut readme = Readme {
+            root: root.to_path_buf(),
+            preamble: Vec::new(),
+            sections: Vec::new(),
+            links: BTreeMap::new(),
+        };
+        let mut in_code = false;
+        for line in source.lines() {
+            if line.starts_with("```") {
+                in_code = !in_code;
+            }
+            if let Some(name) = line.strip_prefix("## ").filter(|_| !in_code) {
+                readme.sections.push(Section {
+                    name: name.replace("\\_", "_"),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+            if let Some((label, destination)) = reference(line) {
+                readme
+                    .links
+                    .insert(label.to_string(), destination.to_string());
+                continue;
+            }
+            let section = match readme.sections.last_mut() {
+                Some(section) => section,
+                None => {
+                    if !line.starts_with("# ") {
+                        readme.preamble.push(line.to_string());
+                    }
+                    continue;
+                }
+            };
+            let entry = line
+                .strip_prefix("- ")
+                .and_then(|entry| entry.strip_suffix(':'));
+            match (entry, section.entries.last_mut()) {
+                (Some(entry), _) => section.entries.push((entry.to_string(), Vec::new())),
+                (None, Some((_, lines))) if !line.trim().is_empty() => {
+                    lines.push(line.trim().to_string())
+                }
+                _ => {}
+            }
+        }
+        readme
+    }
+
+    fn section(&self, package: &str) -> Option<&Section> {
+        self.sections.iter().find(|section| section.name == package)
+    }
+
+    // Renders lines of markdown: paragraphs, bullets and fenced code.
+    fn render(&self, lines: &[String]) -> String {
+        let mut html = String::new();
+        let mut paragraph: Vec<&str> = Vec::new();
+        let mut bullets: Vec<&str> = Vec::new();
+        let mut code: Option<Vec<&str>> = None;
+        let flush = |html: &mut String, paragraph: &mut Vec<&str>, bullets: &mut Vec<&str>| {
+            if !paragraph.is_empty() {
+                let _ = writeln!(html, "<p>{}</p>", self.inline(&paragraph.join(" ")));
+                paragraph.clear();
+            }
+            if !bullets.is_empty() {
+                html.push_str("<ul>\n");
+                for bullet in bullets.iter() {
+                    let _ = writeln!(html, "<li>{}</li>", self.inline(bullet));
+                }
+                html.push_str("</ul>\n");
+                bullets.clear();
+            }
+        };
+        for line in lines {
+            if line.starts_with("```") {
+                match code.take() {
+                    Some(code) => {
+                        let _ = writeln!(html, "<pre>{}</pre>", escape(&code.join("\n")));
+                    }
+                    None => {
+                        flush(&mut html, &mut paragraph, &mut bullets);
+                        code = Some(Vec::new());
+                    }
+                }
+                continue;
+            }
+            if let Some(code) = &mut code {
+                code.push(line.as_str());
+                continue;
+            }
+            let line = line.trim();
+            if let Some(bullet) = line.strip_prefix("* ") {
+                if !paragraph.is_empty() {
+                    flush(&mut html, &mut paragraph, &mut Vec::new());
+                }
+                bullets.push(bullet);
+            } else if line.is_empty() {
+                flush(&mut html, &mut paragraph, &mut bullets);
+            } else {
+                if !bullets.is_empty() {
+                    flush(&mut html, &mut Vec::new(), &mut bullets);
+                }
+                paragraph.push(line);
+            }
+        }
+        flush(&mut html, &mut paragraph, &mut bullets);
+        html
+    }
+
+    // Renders the inline markup of the readme: code spans, bold text, escapes and links, whose
+    // destinations in the benchmark's sources point to the explorer's pages.
+    fn inline(&self, text: &str) -> String {
+        let mut html = String::new();
+        let mut rest = text;
+        while let Some(c) = rest.chars().next() {
+            if let Some(code) = rest.strip_prefix('`') {
+                if let Some(end) = code.find('`') {
+                    let _ = write!(html, "<code>{}</code>", escape(&code[..end]));
+                    rest = &code[end + 1..];
+                    continue;
+                }
+            }
+            if let Some(bold) = rest.strip_prefix("**") {
+                if let Some(end) = bold.find("**") {
+                    let _ = write!(html, "<b>{}</b>", self.inline(&bold[..end]));
+                    rest = &bold[end + 2..];
+                    continue;
+                }
+            }
+            if let Some(escaped) = rest.strip_prefix('\\') {
+                if let Some(c) = escaped.chars().next() {
+                    html.push_str(&escape(&c.to_string()));
+                    rest = &escaped[c.len_utf8()..];
+                    continue;
+                }
+            }
+            if let Some((link, length)) = self.link(rest) {
+                html.push_str(&link);
+                rest = &rest[length..];
+                continue;
+            }
+            html.push_str(&escape(&c.to_string()));
+            rest = &rest[c.len_utf8()..];
+        }
+        html
+    }
+
+    // A link at the start of 'text', '[text][label]' or '[text](destination)', and its length.
+    fn link(&self, text: &str) -> Option<(String, usize)> {
+        let inner = text.strip_prefix('[')?;
+        let end = inner.find(']')?;
+        let label = &inner[..end];
+        let rest = &inner[end + 1..];
+        let (destination, length) = if let Some(reference) = rest.strip_prefix('[') {
+            let close = reference.find(']')?;
+            let destination = self.links.get(&reference[..close])?;
+            (destination.clone(), end + close + 4)
+        } else if let Some(destination) = rest.strip_prefix('(') {
+            let close = destination.find(')')?;
+            (destination[..close].to_string(), end + close + 4)
+        } else {
+            return None;
+        };
+        let link = format!(
+            "<a href=\"{}\">{}</a>",
+            escape(&self.destination(&destination)),
+            self.inline(label)
+        );
+        Some((link, length))
+    }
+
+    // Destinations relative to src/README.md that are source files of the benchmark point to the
+    // files' pages, other files of the repository are linked to where they are.
+    fn destination(&self, destination: &str) -> String {
+        if destination.contains("://") || destination.starts_with('#') {
+            return destination.to_string();
+        }
+        let (path, anchor) = match destination.split_once('#') {
+            Some((path, anchor)) => (path, format!("#{}", anchor)),
+            None => (destination, String::new()),
+        };
+        let file = PathBuf::from("src").join(path);
+        if file.extension().is_some_and(|extension| extension == "rs") {
+            format!("{}{}", file_page(&file), anchor)
+        } else {
+            let path = fs::canonicalize(self.root.join(&file)).unwrap_or(self.root.join(file));
+            format!("file://{}{}", path.display(), anchor)
+        }
+    }
+}
+
+// The label and destination of a reference definition, '[3]: <destination> "<title>"'.
+fn reference(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (label, rest) = rest.split_once("]: ")?;
+    let destination = rest.split(' ').next()?;
+    Some((label, destination))
+}
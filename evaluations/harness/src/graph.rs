@@ -0,0 +1,342 @@
+// The canonical call-graph model. Ground truth and tool outputs are brought to this model, where
+// nodes are named by their canonical names (see index.rs) and only functions defined in the
+// benchmark crates are kept.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dot::Dot;
+use crate::index::strip_generics;
+use crate::index::Index;
+use crate::names::canonical_path;
+use crate::names::split_path;
+use crate::names::split_top_level;
+use crate::names::Normalizer;
+
+#[derive(
+    Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(deny_unknown_fields)]
+pub struct Edge {
+    pub caller: String,
+    pub callee: String,
+}
+
+impl Edge {
+    pub fn new(caller: impl Into<String>, callee: impl Into<String>) -> Self {
+        Edge {
+            caller: caller.into(),
+            callee: callee.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CallGraph {
+    pub nodes: BTreeSet<String>,
+    pub edges: BTreeSet<Edge>,
+}
+
+impl CallGraph {
+    pub fn add_edge(&mut self, caller: &str, callee: &str) {
+        self.nodes.insert(caller.to_string());
+        self.nodes.insert(callee.to_string());
+        self.edges.insert(Edge::new(caller, callee));
+    }
+
+    pub fn callees<'a>(&'a self, caller: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.caller == caller)
+            .map(|edge| edge.callee.as_str())
+    }
+
+    // Builds the canonical graph of a tool's DOT output. Calls that go through functions outside
+    // the benchmark crates are folded, i.e., if 'a' calls std function 's' which calls 'b', the
+    // graph contains an edge from 'a' to 'b'. This is similar to MIRAI's 'Fold' reduction.
+    pub fn from_dot(dot: &Dot, normalizer: &Normalizer) -> CallGraph {
+        let names: BTreeMap<&str, Option<String>> = dot
+            .nodes
+            .iter()
+            .map(|(id, label)| (id.as_str(), normalizer.canonical(label)))
+            .collect();
+        let mut successors: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (caller, callee) in &dot.edges {
+            successors.entry(caller).or_default().push(callee);
+        }
+
+        // LLVM opt's 'external node' calls every externally visible function. Folding through it
+        // would connect everything to everything.
+        let barrier = |id: &str| dot.nodes.get(id).map(String::as_str) == Some("external node");
+
+        let mut graph = CallGraph::default();
+        for (id, name) in &names {
+            let caller = match name {
+                Some(name) => name,
+                None => continue,
+            };
+            graph.nodes.insert(caller.clone());
+
+            let mut stack: Vec<&str> = successors.get(id).cloned().unwrap_or_default();
+            let mut visited = HashSet::new();
+            while let Some(next) = stack.pop() {
+                if !visited.insert(next) || barrier(next) {
+                    continue;
+                }
+                match names.get(next).cloned().flatten() {
+                    Some(callee) => graph.add_edge(caller, &callee),
+                    None => stack.extend(successors.get(next).cloned().unwrap_or_default()),
+                }
+            }
+        }
+        graph
+    }
+
+    // The graph with the instances of generic functions merged into their definitions, e.g.,
+    // generics::lib::monomorphized::<structs::lib::One> into generics::lib::monomorphized.
+    pub fn collapse(&self) -> CallGraph {
+        let mut graph = CallGraph::default();
+        graph
+            .nodes
+            .extend(self.nodes.iter().map(|node| canonical_path(node)));
+        for edge in &self.edges {
+            graph.add_edge(&canonical_path(&edge.caller), &canonical_path(&edge.callee));
+        }
+        graph
+    }
+}
+
+// Queries over the graph. Functions are given by their canonical names or a suffix of them; see
+// find.
+impl CallGraph {
+    pub fn callers<'a>(&'a self, callee: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.callee == callee)
+            .map(|edge| edge.caller.as_str())
+    }
+
+    // The nodes that match a name given on the command line. A name matches a node if it is the
+    // node's canonical name or a suffix of it that starts at a path segment, and paths inside
+    // qualified names may be abbreviated to their last segment, e.g., both
+    // 'thin::Thin::new' and '<Thin as DefaultTrait>::default_method' are accepted. The method of
+    // a trait implementation that does not override the trait's provided method is the provided
+    // method, e.g., '<Thin as DefaultTrait>::default_method' is
+    // traits::lib::DefaultTrait::default_method.
+    pub fn find(&self, index: &Index, name: &str) -> Vec<&str> {
+        if self.nodes.contains(name) {
+            return vec![self.nodes.get(name).unwrap().as_str()];
+        }
+        let nodes: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter(|node| names(node, name))
+            .map(String::as_str)
+            .collect();
+        if !nodes.is_empty() {
+            return nodes;
+        }
+        match provided_method(name) {
+            Some(provided) if !index.functions.iter().any(|f| names(&f.name, name)) => {
+                self.find(index, &provided)
+            }
+            _ => nodes,
+        }
+    }
+
+    // The nodes transitively called by 'caller', excluding 'caller' unless it is recursive.
+    pub fn reachable<'a>(&'a self, caller: &'a str) -> BTreeSet<&'a str> {
+        let mut reachable = BTreeSet::new();
+        let mut stack: Vec<&str> = self.callees(caller).collect();
+        while let Some(node) = stack.pop() {
+            if reachable.insert(node) {
+                stack.extend(self.callees(node));
+            }
+        }
+        reachable
+    }
+
+    // A shortest call chain from 'from' to 'to', both included.
+    pub fn shortest_path<'a>(&'a self, from: &'a str, to: &'a str) -> Option<Vec<&'a str>> {
+        let mut predecessors: BTreeMap<&str, &str> = BTreeMap::new();
+        let mut queue = VecDeque::from(vec![from]);
+        while let Some(node) = queue.pop_front() {
+            if node == to {
+                let mut path = vec![to];
+                let mut node = to;
+                while node != from {
+                    node = predecessors[node];
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for callee in self.callees(node) {
+                if callee != from && !predecessors.contains_key(callee) {
+                    predecessors.insert(callee, node);
+                    queue.push_back(callee);
+                }
+            }
+        }
+        None
+    }
+
+    // The strongly connected components of the graph that contain a cycle, i.e., groups of
+    // mutually recursive functions and directly recursive functions (Tarjan's algorithm).
+    pub fn recursive_components(&self) -> Vec<Vec<&str>> {
+        struct Tarjan<'a> {
+            graph: &'a CallGraph,
+            counter: usize,
+            indices: BTreeMap<&'a str, usize>,
+            lowlinks: BTreeMap<&'a str, usize>,
+            stack: Vec<&'a str>,
+            components: Vec<Vec<&'a str>>,
+        }
+
+        impl<'a> Tarjan<'a> {
+            fn visit(&mut self, node: &'a str) {
+                self.indices.insert(node, self.counter);
+                self.lowlinks.insert(node, self.counter);
+                self.counter += 1;
+                self.stack.push(node);
+
+                for callee in self.graph.callees(node) {
+                    if !self.indices.contains_key(callee) {
+                        self.visit(callee);
+                        let lowlink = self.lowlinks[node].min(self.lowlinks[callee]);
+                        self.lowlinks.insert(node, lowlink);
+                    } else if self.stack.contains(&callee) {
+                        let lowlink = self.lowlinks[node].min(self.indices[callee]);
+                        self.lowlinks.insert(node, lowlink);
+                    }
+                }
+
+                if self.lowlinks[node] == self.indices[node] {
+                    let pos = self.stack.iter().rposition(|&n| n == node).unwrap();
+                    let mut component = self.stack.split_off(pos);
+                    component.sort_unstable();
+                    let recursive =
+                        component.len() > 1 || self.graph.callees(node).any(|c| c == node);
+                    if recursive {
+                        self.components.push(component);
+                    }
+                }
+            }
+        }
+
+        let mut tarjan = Tarjan {
+            graph: self,
+            counter: 0,
+            indices: BTreeMap::new(),
+            lowlinks: BTreeMap::new(),
+            stack: Vec::new(),
+            components: Vec::new(),
+        };
+        for node in &self.nodes {
+            if !tarjan.indices.contains_key(node.as_str()) {
+                tarjan.visit(node);
+            }
+        }
+        tarjan.components.sort();
+        tarjan.components
+    }
+}
+
+// Whether a name given on the command line names the function of a canonical name; see find.
+fn names(function: &str, name: &str) -> bool {
+    let suffix = format!("::{}", name);
+    let abbreviated = abbreviate(function);
+    function.ends_with(&suffix) || abbreviated == name || abbreviated.ends_with(&suffix)
+}
+
+// The provided method of the trait of a qualified name, e.g., DefaultTrait::default_method for
+// <Thin as DefaultTrait>::default_method.
+fn provided_method(name: &str) -> Option<String> {
+    match split_path(name).as_slice() {
+        [qualified, method] if qualified.starts_with('<') => {
+            let inner = &qualified[1..qualified.len() - 1];
+            let (_, trait_) = split_top_level(inner, " as ")?;
+            Some(format!("{}::{}", strip_generics(trait_), method))
+        }
+        _ => None,
+    }
+}
+
+// Abbreviates the paths inside the angle brackets of a qualified name to their last segment, e.g.,
+// <structs::lib::thin::Thin as traits::lib::DefaultTrait>::default_method is abbreviated to
+// <Thin as DefaultTrait>::default_method.
+fn abbreviate(name: &str) -> String {
+    let mut abbreviated = String::new();
+    let mut depth = 0;
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' => depth += 1,
+            '>' if !abbreviated.ends_with('-') => depth -= 1,
+            ':' if depth > 0 && chars.peek() == Some(&':') => {
+                chars.next();
+                while abbreviated.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+                    abbreviated.pop();
+                }
+                continue;
+            }
+            _ => {}
+        }
+        abbreviated.push(c);
+    }
+    abbreviated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages;
+    use crate::score::Layer;
+    use crate::truth::Truth;
+
+    // The expected call graph of the benchmark, with its index.
+    fn expected() -> (Index, CallGraph) {
+        let root = packages::default_root();
+        packages::load(&root).unwrap();
+        let index = Index::build(&root).unwrap();
+        let graph = Truth::extract(&root, &index)
+            .unwrap()
+            .graph(&index, Layer::Benchmark);
+        (index, graph)
+    }
+
+    #[test]
+    fn finds_functions_by_suffixes_and_abbreviations() {
+        let (index, graph) = expected();
+        assert_eq!(
+            graph.find(&index, "dynamic_dispatch::lib::dynamic_default"),
+            ["dynamic_dispatch::lib::dynamic_default"]
+        );
+        assert_eq!(
+            graph.find(&index, "lib::dynamic_default"),
+            ["dynamic_dispatch::lib::dynamic_default"]
+        );
+        assert_eq!(
+            graph.find(&index, "<Fat as DefaultTrait>::default_method"),
+            ["<structs::lib::fat::Fat as traits::lib::DefaultTrait>::default_method"]
+        );
+        assert!(graph.find(&index, "namic_default").is_empty());
+    }
+
+    #[test]
+    fn finds_the_provided_method_of_an_implementation_that_does_not_override_it() {
+        let (index, graph) = expected();
+        let provided = graph.find(&index, "<Thin as DefaultTrait>::default_method");
+        assert_eq!(provided, ["traits::lib::DefaultTrait::default_method"]);
+        assert_eq!(
+            graph.callers(provided[0]).collect::<Vec<_>>(),
+            ["dynamic_dispatch::lib::dynamic_default"]
+        );
+    }
+}
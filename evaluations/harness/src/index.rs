@@ -0,0 +1,1201 @@
+// Index of the functions defined in the benchmark packages.
+//
+// Call-graph generators name functions in different ways. MIRAI uses rustc's DefPath
+// (structs::lib::fat::{impl#1}::method), LLVM based tools use (demangled) symbol names
+// (<structs::lib::fat::Fat as traits::lib::FooTrait>::method) and the benchmark annotations use
+// paths like structs::lib::fat::{impl FooTrait for Fat}::method. The index is built by parsing the
+// benchmark sources and provides what is needed to translate all of them to a single canonical
+// name per function:
+//
+// - free functions are named by their path, e.g., generics::lib::monomorphized
+// - inherent methods are named by the path of their self type, e.g., structs::lib::Two::add_one
+// - trait methods are named by the path of their trait, e.g., traits::lib::FooTrait::method
+// - methods of trait implementations are named by their qualified path, e.g.,
+//   <structs::lib::thin::Thin as traits::lib::GenericFooTrait<u32>>::method
+// - closures are named by their enclosing function or closure and their ordinal among the closures
+//   of it, as rustc numbers them, e.g., closures::lib::apply::{closure#0}::{closure#1}; the
+//   coroutines of async blocks are numbered among them, and the coroutine of an async function is
+//   its closure #0, e.g., async_await::lib::foo_of::{closure#0}
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use proc_macro2::LineColumn;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::Parser;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::expand::expand;
+use crate::packages;
+
+// Traits implemented by the derive macros of the benchmark. The methods of a derived implementation
+// are not part of the sources, so they are added to the index based on the trait's definition.
+const DERIVES: &[(&str, &str)] = &[("Macros", "traits::lib::MacroTrait")];
+
+// Traits of the standard library prelude that the benchmark implements without importing them.
+// Names that are not declared or imported in scope resolve to them, as they do in rustc.
+const PRELUDE: &[(&str, &str)] = &[
+    ("Drop", "std::ops::Drop"),
+    ("PartialEq", "std::cmp::PartialEq"),
+    ("PartialOrd", "std::cmp::PartialOrd"),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FunctionKind {
+    Free,
+    Inherent,
+    TraitImpl,
+    TraitMethod,
+}
+
+pub struct Function {
+    pub name: String,
+    pub def_path: String,
+    pub package: String,
+    pub file: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    // The position of the function's name, where editors look the function up. Functions that are
+    // not part of the sources are positioned at their derive attribute.
+    pub ident: LineColumn,
+    pub kind: FunctionKind,
+    // Trait methods without a default implementation and methods generated by derive macros have
+    // no body in the sources.
+    pub has_body: bool,
+    // Whether the function has a 'self' parameter, i.e., is a method.
+    pub receiver: bool,
+    // Whether the function has type parameters, or is declared in a generic implementation or
+    // trait.
+    pub generic: bool,
+    // Whether the function, or an item enclosing it, is conditionally compiled (#[cfg(...)]).
+    pub cfg_gated: bool,
+    scope: usize,
+    // The signature in the sources. Functions generated by derive macros have none.
+    sig: Option<syn::Signature>,
+}
+
+pub struct Macro {
+    pub name: String,
+    pub path: String,
+    pub file: PathBuf,
+    pub start: usize,
+    pub end: usize,
+    // The rules of the macro, i.e., the tokens between the braces of its definition.
+    pub tokens: TokenStream,
+}
+
+pub struct Closure {
+    pub name: String,
+    // The function whose body defines the closure.
+    pub function: usize,
+    // The file and span of the closure's definition. Closures expanded from a macro are defined in
+    // the macro's file.
+    pub file: PathBuf,
+    pub start: usize,
+    // 1-based, as printed by rustc.
+    pub column: usize,
+    pub end: usize,
+    // For closures expanded from a macro, the line of the invocation in the function's file.
+    pub invocation: Option<usize>,
+}
+
+pub struct Invocation {
+    pub path: Vec<String>,
+    pub caller: usize,
+    pub line: usize,
+    pub tokens: TokenStream,
+}
+
+enum Name {
+    Item(String),
+    Use(Vec<String>),
+}
+
+struct Scope {
+    package: String,
+    module: String,
+    parent: Option<usize>,
+    self_ty: Option<String>,
+    names: HashMap<String, Name>,
+}
+
+#[derive(Default)]
+pub struct Index {
+    // The source files of each package, relative to the repository root.
+    pub files: Vec<(String, PathBuf)>,
+    pub functions: Vec<Function>,
+    pub macros: Vec<Macro>,
+    pub invocations: Vec<Invocation>,
+    pub closures: Vec<Closure>,
+    traits: HashMap<String, Vec<String>>,
+    scopes: Vec<Scope>,
+    modules: HashMap<String, usize>,
+}
+
+impl Index {
+    pub fn build(root: &Path) -> Result<Index> {
+        let mut builder = Builder {
+            root: root.to_path_buf(),
+            index: Index::default(),
+            impls: HashMap::new(),
+            derives: Vec::new(),
+            closures: Vec::new(),
+        };
+
+        for package in packages::all() {
+            let src = Path::new(&package.dir).join("src");
+            let file = if package.library {
+                src.join("lib.rs")
+            } else {
+                src.join("main.rs")
+            };
+            builder.file_module(&package.name, &package.name, &file, &src, None, false)?;
+        }
+
+        builder.finish();
+        Ok(builder.index)
+    }
+
+    pub fn function(&self, name: &str) -> Option<&Function> {
+        self.functions.iter().find(|function| function.name == name)
+    }
+
+    pub fn function_by_def_path(&self, def_path: &str) -> Option<&Function> {
+        self.functions
+            .iter()
+            .find(|function| function.def_path == def_path)
+    }
+
+    // Returns the innermost function whose definition spans the given line.
+    pub fn function_at(&self, file: &Path, line: usize) -> Option<usize> {
+        self.functions
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.has_body && f.file == file && f.start <= line && line <= f.end)
+            .min_by_key(|(_, f)| f.end - f.start)
+            .map(|(id, _)| id)
+    }
+
+    pub fn closure(&self, name: &str) -> Option<&Closure> {
+        self.closures.iter().find(|closure| closure.name == name)
+    }
+
+    // The closures defined directly in the body of a function or closure.
+    pub fn closures_of<'a>(&'a self, parent: &'a str) -> impl Iterator<Item = &'a Closure> + 'a {
+        self.closures.iter().filter(move |closure| {
+            closure
+                .name
+                .rsplit_once("::")
+                .is_some_and(|(prefix, _)| prefix == parent)
+        })
+    }
+
+    // The name of the caller of a call at the given line of a function's body: the innermost
+    // closure around the line, or the function itself. Calls inside macro definitions are given
+    // by the macro's file and line and the line of the invocation. If the invocation expands the
+    // call several times, e.g., once per argument, inside different closures, the caller is the
+    // function.
+    pub fn caller_at(
+        &self,
+        function: usize,
+        file: &Path,
+        line: usize,
+        invocation: Option<usize>,
+    ) -> &str {
+        let closures: Vec<&Closure> = self
+            .closures
+            .iter()
+            .filter(|closure| {
+                closure.function == function
+                    && closure.file == file
+                    && closure.invocation == invocation
+                    && closure.start <= line
+                    && line <= closure.end
+            })
+            .collect();
+        let innermost = closures
+            .iter()
+            .map(|closure| closure.end - closure.start)
+            .min();
+        let mut innermost = closures
+            .into_iter()
+            .filter(|closure| Some(closure.end - closure.start) == innermost);
+        match (innermost.next(), innermost.next()) {
+            (Some(closure), None) => &closure.name,
+            _ => &self.functions[function].name,
+        }
+    }
+
+    // The function named 'name' or, for a closure, the function that defines it.
+    pub fn enclosing_function(&self, name: &str) -> Option<usize> {
+        self.functions
+            .iter()
+            .position(|function| function.name == name)
+            .or_else(|| self.closure(name).map(|closure| closure.function))
+    }
+
+    pub fn macro_at(&self, file: &Path, line: usize) -> Option<&Macro> {
+        self.macros
+            .iter()
+            .find(|m| m.file == file && m.start <= line && line <= m.end)
+    }
+
+    pub fn is_trait(&self, path: &str) -> bool {
+        self.traits.contains_key(path)
+    }
+
+    pub fn trait_methods(&self, path: &str) -> &[String] {
+        self.traits.get(path).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn module_scope(&self, module: &str) -> Option<usize> {
+        self.modules.get(module).copied()
+    }
+
+    // The scope of a function's body, which includes the function's local 'use' declarations.
+    pub fn function_scope(&self, function: usize) -> usize {
+        self.functions[function].scope
+    }
+
+    // Resolves a path as it would be resolved by the compiler inside the given scope, e.g.,
+    // 'FooTrait' inside 'structs::lib::fat' resolves to 'traits::lib::FooTrait'.
+    pub fn resolve(&self, scope: usize, segments: &[String]) -> String {
+        if segments.is_empty() {
+            return String::new();
+        }
+
+        let rest = &segments[1..];
+        let base = match segments[0].as_str() {
+            "crate" => self.scopes[scope].package.clone(),
+            "self" => self.scopes[scope].module.clone(),
+            "super" => {
+                let mut module = parent_module(&self.scopes[scope].module);
+                let mut rest = rest;
+                while rest.first().map(String::as_str) == Some("super") {
+                    module = parent_module(&module);
+                    rest = &rest[1..];
+                }
+                return join(module, rest);
+            }
+            "Self" => match self.self_ty(scope) {
+                Some(self_ty) => self_ty.to_string(),
+                None => "Self".to_string(),
+            },
+            name => match self.lookup(scope, name) {
+                Some(path) => path,
+                None => match PRELUDE.iter().find(|(prelude, _)| *prelude == name) {
+                    Some((_, path)) => path.to_string(),
+                    None => name.to_string(),
+                },
+            },
+        };
+        join(base, rest)
+    }
+
+    fn self_ty(&self, scope: usize) -> Option<&str> {
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            if let Some(self_ty) = &self.scopes[id].self_ty {
+                return Some(self_ty);
+            }
+            current = self.scopes[id].parent;
+        }
+        None
+    }
+
+    fn lookup(&self, scope: usize, name: &str) -> Option<String> {
+        let mut current = Some(scope);
+        while let Some(id) = current {
+            match self.scopes[id].names.get(name) {
+                Some(Name::Item(path)) => return Some(path.clone()),
+                Some(Name::Use(segments)) => return Some(self.resolve_use(id, segments)),
+                None => current = self.scopes[id].parent,
+            }
+        }
+        None
+    }
+
+    // Paths of 'use' declarations start with 'crate', 'self', 'super', the name of an item
+    // declared in scope or the name of an external crate. Only the former refer to other 'use'
+    // declarations in the benchmark, e.g., 'use super::FooTrait' to the 'use' of FooTrait in the
+    // parent module.
+    fn resolve_use(&self, scope: usize, segments: &[String]) -> String {
+        match segments[0].as_str() {
+            "crate" | "self" | "super" => {
+                let path = self.resolve(scope, segments);
+                let used = path
+                    .rsplit_once("::")
+                    .and_then(|(module, name)| Some((self.module_scope(module)?, name)));
+                match used {
+                    Some((module, name)) => match self.scopes[module].names.get(name) {
+                        Some(Name::Use(segments)) => self.resolve_use(module, segments),
+                        _ => path,
+                    },
+                    None => path,
+                }
+            }
+            name => {
+                let mut current = Some(scope);
+                while let Some(id) = current {
+                    if let Some(Name::Item(path)) = self.scopes[id].names.get(name) {
+                        return join(path.clone(), &segments[1..]);
+                    }
+                    current = self.scopes[id].parent;
+                }
+                segments.join("::")
+            }
+        }
+    }
+
+    // Renders a type as rustc does in symbol names, with all paths resolved and lifetimes omitted.
+    pub fn type_in_scope(&self, scope: usize, ty: &syn::Type) -> String {
+        match ty {
+            syn::Type::Path(ty) if ty.qself.is_none() => self.path_in_scope(scope, &ty.path),
+            syn::Type::Reference(ty) => {
+                let mutability = if ty.mutability.is_some() { "mut " } else { "" };
+                format!("&{}{}", mutability, self.type_in_scope(scope, &ty.elem))
+            }
+            syn::Type::Ptr(ty) => {
+                let mutability = if ty.mutability.is_some() {
+                    "mut"
+                } else {
+                    "const"
+                };
+                format!("*{} {}", mutability, self.type_in_scope(scope, &ty.elem))
+            }
+            syn::Type::Slice(ty) => format!("[{}]", self.type_in_scope(scope, &ty.elem)),
+            syn::Type::Paren(ty) => self.type_in_scope(scope, &ty.elem),
+            syn::Type::Group(ty) => self.type_in_scope(scope, &ty.elem),
+            syn::Type::Tuple(ty) => {
+                let elems: Vec<_> = ty
+                    .elems
+                    .iter()
+                    .map(|t| self.type_in_scope(scope, t))
+                    .collect();
+                format!("({})", elems.join(", "))
+            }
+            syn::Type::TraitObject(ty) => {
+                let bounds: Vec<_> = ty
+                    .bounds
+                    .iter()
+                    .filter_map(|bound| match bound {
+                        syn::TypeParamBound::Trait(bound) => {
+                            Some(self.path_in_scope(scope, &bound.path))
+                        }
+                        _ => None,
+                    })
+                    .collect();
+                format!("dyn {}", bounds.join(" + "))
+            }
+            // Function pointer types, whose higher-ranked lifetimes are dropped like all others.
+            syn::Type::BareFn(ty) => {
+                let inputs = ty
+                    .inputs
+                    .iter()
+                    .map(|input| self.type_in_scope(scope, &input.ty))
+                    .collect();
+                self.fn_type(scope, inputs, &ty.output)
+            }
+            ty => tokens_to_string(ty.to_token_stream()),
+        }
+    }
+
+    // The type of the function pointers that a function coerces to, as type_in_scope names
+    // function pointer types, e.g., fn(&structs::lib::fat::Fat) -> u32 for
+    // <structs::lib::fat::Fat as traits::lib::FooTrait>::method. None for generic functions,
+    // whose instances coerce to different types, and for functions without a signature in the
+    // sources.
+    pub fn pointer_type(&self, function: &Function) -> Option<String> {
+        let sig = function.sig.as_ref()?;
+        if function.generic || sig.asyncness.is_some() || sig.variadic.is_some() {
+            return None;
+        }
+        let inputs = sig
+            .inputs
+            .iter()
+            .map(|input| match input {
+                syn::FnArg::Receiver(receiver) => self.type_in_scope(function.scope, &receiver.ty),
+                syn::FnArg::Typed(arg) => self.type_in_scope(function.scope, &arg.ty),
+            })
+            .collect();
+        Some(self.fn_type(function.scope, inputs, &sig.output))
+    }
+
+    fn fn_type(&self, scope: usize, inputs: Vec<String>, output: &syn::ReturnType) -> String {
+        let output = match output {
+            syn::ReturnType::Type(_, ty) => self.type_in_scope(scope, ty),
+            syn::ReturnType::Default => String::new(),
+        };
+        match output.as_str() {
+            "" | "()" => format!("fn({})", inputs.join(", ")),
+            output => format!("fn({}) -> {}", inputs.join(", "), output),
+        }
+    }
+
+    pub fn path_in_scope(&self, scope: usize, path: &syn::Path) -> String {
+        let segments: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+        let mut rendered = self.resolve(scope, &segments);
+        if let Some(syn::PathArguments::AngleBracketed(args)) =
+            path.segments.last().map(|s| &s.arguments)
+        {
+            let args: Vec<_> = args
+                .args
+                .iter()
+                .filter_map(|arg| match arg {
+                    syn::GenericArgument::Type(ty) => Some(self.type_in_scope(scope, ty)),
+                    syn::GenericArgument::Lifetime(_) => None,
+                    arg => Some(tokens_to_string(arg.to_token_stream())),
+                })
+                .collect();
+            if !args.is_empty() {
+                rendered = format!("{}<{}>", rendered, args.join(", "));
+            }
+        }
+        rendered
+    }
+}
+
+struct Builder {
+    root: PathBuf,
+    index: Index,
+    // rustc disambiguates the implementations declared inside the same parent item by numbering
+    // them in order of declaration ({impl#0}, {impl#1}, ...).
+    impls: HashMap<String, usize>,
+    derives: Vec<Derive>,
+    // The closures and macro invocations of each function body, numbered once all macros are known.
+    closures: Vec<(usize, Vec<ClosureNode>)>,
+}
+
+struct Derive {
+    self_ty: String,
+    trait_: String,
+    def_path: String,
+    package: String,
+    file: PathBuf,
+    line: usize,
+    scope: usize,
+}
+
+struct Module<'a> {
+    package: &'a str,
+    path: String,
+    file: PathBuf,
+    // Directory holding the files of the module's submodules.
+    dir: PathBuf,
+    scope: usize,
+    cfg_gated: bool,
+}
+
+impl Builder {
+    fn file_module(
+        &mut self,
+        package: &str,
+        path: &str,
+        file: &Path,
+        dir: &Path,
+        parent: Option<usize>,
+        cfg_gated: bool,
+    ) -> Result<()> {
+        let source = fs::read_to_string(self.root.join(file))
+            .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+        let ast = syn::parse_file(&source)
+            .map_err(|e| Error::new(format!("cannot parse {}: {}", file.display(), e)))?;
+        self.index
+            .files
+            .push((package.to_string(), file.to_path_buf()));
+        let scope = self.scope(package, path, parent, None);
+        let module = Module {
+            package,
+            path: path.to_string(),
+            file: file.to_path_buf(),
+            dir: dir.to_path_buf(),
+            scope,
+            cfg_gated,
+        };
+        self.items(&module, &ast.items)
+    }
+
+    fn scope(
+        &mut self,
+        package: &str,
+        module: &str,
+        parent: Option<usize>,
+        self_ty: Option<String>,
+    ) -> usize {
+        self.index.scopes.push(Scope {
+            package: package.to_string(),
+            module: module.to_string(),
+            parent,
+            self_ty,
+            names: HashMap::new(),
+        });
+        self.index.scopes.len() - 1
+    }
+
+    fn items(&mut self, module: &Module, items: &[syn::Item]) -> Result<()> {
+        self.index.modules.insert(module.path.clone(), module.scope);
+
+        // Names are collected first, as items can be used before their declaration.
+        for item in items {
+            let ident = match item {
+                syn::Item::Const(item) => Some(&item.ident),
+                syn::Item::Enum(item) => Some(&item.ident),
+                syn::Item::Fn(item) => Some(&item.sig.ident),
+                syn::Item::Mod(item) => Some(&item.ident),
+                syn::Item::Static(item) => Some(&item.ident),
+                syn::Item::Struct(item) => Some(&item.ident),
+                syn::Item::Trait(item) => Some(&item.ident),
+                syn::Item::Type(item) => Some(&item.ident),
+                syn::Item::Union(item) => Some(&item.ident),
+                syn::Item::Use(item) => {
+                    self.uses(module.scope, &item.tree, Vec::new());
+                    None
+                }
+                _ => None,
+            };
+            if let Some(ident) = ident {
+                let path = format!("{}::{}", module.path, ident);
+                self.index.scopes[module.scope]
+                    .names
+                    .insert(ident.to_string(), Name::Item(path));
+            }
+        }
+
+        for item in items {
+            match item {
+                syn::Item::Fn(item) => {
+                    let path = format!("{}::{}", module.path, item.sig.ident);
+                    let scope = self.scope(module.package, &module.path, Some(module.scope), None);
+                    self.function(
+                        module,
+                        path.clone(),
+                        path,
+                        FunctionKind::Free,
+                        item.span(),
+                        scope,
+                    );
+                    let cfg_gated = module.cfg_gated || has_cfg(&item.attrs);
+                    self.signature(&item.sig, false, cfg_gated);
+                    self.body(module, &item.block, self.index.functions.len() - 1);
+                }
+                syn::Item::Impl(item) => self.impl_block(module, item),
+                syn::Item::Trait(item) => self.trait_block(module, item),
+                syn::Item::Mod(item) => self.module(module, item)?,
+                syn::Item::Macro(item) => self.macro_rules(module, item),
+                syn::Item::Struct(item) => self.derives(module, &item.ident, &item.attrs),
+                syn::Item::Enum(item) => self.derives(module, &item.ident, &item.attrs),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn uses(&mut self, scope: usize, tree: &syn::UseTree, mut prefix: Vec<String>) {
+        match tree {
+            syn::UseTree::Path(path) => {
+                prefix.push(path.ident.to_string());
+                self.uses(scope, &path.tree, prefix);
+            }
+            syn::UseTree::Name(name) => {
+                let ident = name.ident.to_string();
+                if ident == "self" {
+                    if let Some(last) = prefix.last().cloned() {
+                        self.index.scopes[scope]
+                            .names
+                            .insert(last, Name::Use(prefix));
+                    }
+                } else {
+                    prefix.push(ident.clone());
+                    self.index.scopes[scope]
+                        .names
+                        .insert(ident, Name::Use(prefix));
+                }
+            }
+            syn::UseTree::Rename(rename) => {
+                prefix.push(rename.ident.to_string());
+                let alias = rename.rename.to_string();
+                self.index.scopes[scope]
+                    .names
+                    .insert(alias, Name::Use(prefix));
+            }
+            syn::UseTree::Group(group) => {
+                for tree in &group.items {
+                    self.uses(scope, tree, prefix.clone());
+                }
+            }
+            syn::UseTree::Glob(_) => {}
+        }
+    }
+
+    fn module(&mut self, parent: &Module, item: &syn::ItemMod) -> Result<()> {
+        let path = format!("{}::{}", parent.path, item.ident);
+        let dir = parent.dir.join(item.ident.to_string());
+        let cfg_gated = parent.cfg_gated || has_cfg(&item.attrs);
+        match &item.content {
+            Some((_, items)) => {
+                let scope = self.scope(parent.package, &path, Some(parent.scope), None);
+                let module = Module {
+                    package: parent.package,
+                    path,
+                    file: parent.file.clone(),
+                    dir,
+                    scope,
+                    cfg_gated,
+                };
+                self.items(&module, items)
+            }
+            None => {
+                let flat = parent.dir.join(format!("{}.rs", item.ident));
+                let file = if self.root.join(&flat).exists() {
+                    flat
+                } else {
+                    dir.join("mod.rs")
+                };
+                self.file_module(
+                    parent.package,
+                    &path,
+                    &file,
+                    &dir,
+                    Some(parent.scope),
+                    cfg_gated,
+                )
+            }
+        }
+    }
+
+    fn impl_block(&mut self, module: &Module, item: &syn::ItemImpl) {
+        let impl_path = self.next_impl(&module.path);
+        let self_ty = self.index.type_in_scope(module.scope, &item.self_ty);
+        let trait_ = item
+            .trait_
+            .as_ref()
+            .map(|(_, path, _)| self.index.path_in_scope(module.scope, path));
+        let scope = self.scope(
+            module.package,
+            &module.path,
+            Some(module.scope),
+            Some(self_ty.clone()),
+        );
+
+        for impl_item in &item.items {
+            if let syn::ImplItem::Fn(method) = impl_item {
+                let ident = method.sig.ident.to_string();
+                let (name, kind) = match &trait_ {
+                    Some(trait_) => (
+                        format!("<{} as {}>::{}", self_ty, trait_, ident),
+                        FunctionKind::TraitImpl,
+                    ),
+                    None => (
+                        format!("{}::{}", strip_generics(&self_ty), ident),
+                        FunctionKind::Inherent,
+                    ),
+                };
+                let def_path = format!("{}::{}", impl_path, ident);
+                let fn_scope = self.scope(module.package, &module.path, Some(scope), None);
+                self.function(module, name, def_path, kind, method.span(), fn_scope);
+                // Lifetime parameters are erased, so they do not make instances.
+                let generic = item
+                    .generics
+                    .params
+                    .iter()
+                    .any(|param| !matches!(param, syn::GenericParam::Lifetime(_)));
+                let cfg_gated = module.cfg_gated || has_cfg(&item.attrs) || has_cfg(&method.attrs);
+                self.signature(&method.sig, generic, cfg_gated);
+                self.body(module, &method.block, self.index.functions.len() - 1);
+            }
+        }
+    }
+
+    fn trait_block(&mut self, module: &Module, item: &syn::ItemTrait) {
+        let trait_path = format!("{}::{}", module.path, item.ident);
+        let mut methods = Vec::new();
+        for trait_item in &item.items {
+            if let syn::TraitItem::Fn(method) = trait_item {
+                let ident = method.sig.ident.to_string();
+                let path = format!("{}::{}", trait_path, ident);
+                let scope = self.scope(module.package, &module.path, Some(module.scope), None);
+                self.function(
+                    module,
+                    path.clone(),
+                    path,
+                    FunctionKind::TraitMethod,
+                    method.span(),
+                    scope,
+                );
+                let generic = !item.generics.params.is_empty();
+                let cfg_gated = module.cfg_gated || has_cfg(&item.attrs) || has_cfg(&method.attrs);
+                self.signature(&method.sig, generic, cfg_gated);
+                let id = self.index.functions.len() - 1;
+                match &method.default {
+                    Some(block) => self.body(module, block, id),
+                    None => self.index.functions[id].has_body = false,
+                }
+                methods.push(ident);
+            }
+        }
+        self.index.traits.insert(trait_path, methods);
+    }
+
+    fn macro_rules(&mut self, module: &Module, item: &syn::ItemMacro) {
+        let ident = match &item.ident {
+            Some(ident) => ident.to_string(),
+            None => return,
+        };
+        // Exported macros live at the root of their crate.
+        let exported = item
+            .attrs
+            .iter()
+            .any(|attr| attr.path().is_ident("macro_export"));
+        let path = if exported {
+            format!("{}::{}", module.package, ident)
+        } else {
+            format!("{}::{}", module.path, ident)
+        };
+        let span = item.span();
+        self.index.macros.push(Macro {
+            name: ident,
+            path,
+            file: module.file.clone(),
+            start: span.start().line,
+            end: span.end().line,
+            tokens: item.mac.tokens.clone(),
+        });
+    }
+
+    fn derives(&mut self, module: &Module, ident: &syn::Ident, attrs: &[syn::Attribute]) {
+        for attr in attrs.iter().filter(|attr| attr.path().is_ident("derive")) {
+            let derived = attr.parse_args_with(
+                syn::punctuated::Punctuated::<syn::Path, syn::Token![,]>::parse_terminated,
+            );
+            let derived = match derived {
+                Ok(derived) => derived,
+                Err(_) => continue,
+            };
+            for path in derived {
+                let name = path
+                    .segments
+                    .last()
+                    .map(|s| s.ident.to_string())
+                    .unwrap_or_default();
+                // Deriving PartialEq also generates an implementation of StructuralPartialEq.
+                if name == "PartialEq" {
+                    self.next_impl(&module.path);
+                }
+                let impl_path = self.next_impl(&module.path);
+                if let Some((_, trait_)) = DERIVES.iter().find(|(derive, _)| *derive == name) {
+                    self.derives.push(Derive {
+                        self_ty: format!("{}::{}", module.path, ident),
+                        trait_: trait_.to_string(),
+                        def_path: impl_path,
+                        package: module.package.to_string(),
+                        file: module.file.clone(),
+                        line: attr.span().start().line,
+                        scope: module.scope,
+                    });
+                }
+            }
+        }
+    }
+
+    fn next_impl(&mut self, parent: &str) -> String {
+        let counter = self.impls.entry(parent.to_string()).or_insert(0);
+        let path = format!("{}::{{impl#{}}}", parent, counter);
+        *counter += 1;
+        path
+    }
+
+    fn function(
+        &mut self,
+        module: &Module,
+        name: String,
+        def_path: String,
+        kind: FunctionKind,
+        span: proc_macro2::Span,
+        scope: usize,
+    ) {
+        self.index.functions.push(Function {
+            name,
+            def_path,
+            package: module.package.to_string(),
+            file: module.file.clone(),
+            start: span.start().line,
+            end: span.end().line,
+            ident: span.start(),
+            kind,
+            has_body: true,
+            receiver: false,
+            generic: false,
+            cfg_gated: false,
+            scope,
+            sig: None,
+        });
+    }
+
+    // Records the properties of the last added function that are read off its signature.
+    fn signature(&mut self, sig: &syn::Signature, generic: bool, cfg_gated: bool) {
+        let impl_trait = sig.inputs.iter().any(|input| match input {
+            syn::FnArg::Typed(arg) => matches!(*arg.ty, syn::Type::ImplTrait(_)),
+            syn::FnArg::Receiver(_) => false,
+        });
+        let type_params = sig
+            .generics
+            .params
+            .iter()
+            .any(|param| !matches!(param, syn::GenericParam::Lifetime(_)));
+        let function = self.index.functions.last_mut().unwrap();
+        function.ident = sig.ident.span().start();
+        function.receiver = sig.receiver().is_some();
+        function.generic = generic || type_params || impl_trait;
+        function.cfg_gated = cfg_gated;
+        function.sig = Some(sig.clone());
+    }
+
+    fn body(&mut self, module: &Module, block: &syn::Block, function: usize) {
+        let mut visitor = BodyVisitor::new(function);
+        visitor.visit_block(block);
+
+        let BodyVisitor {
+            nested,
+            uses,
+            invocations,
+            mut closures,
+            ..
+        } = visitor;
+        let scope = self.index.functions[function].scope;
+        for tree in &uses {
+            self.uses(scope, tree, Vec::new());
+        }
+        self.index.invocations.extend(invocations);
+        let mut nodes = closures.pop().unwrap_or_default();
+        // The body of an async function is the coroutine of its future, the function's only
+        // closure, and the closures of the body are nested in it.
+        let sig = self.index.functions[function].sig.as_ref();
+        if sig.is_some_and(|sig| sig.asyncness.is_some()) {
+            let span = block.span();
+            nodes = vec![ClosureNode::Closure {
+                start: span.start(),
+                end: span.end().line,
+                children: nodes,
+            }];
+        }
+        self.closures.push((function, nodes));
+
+        // Nested functions are named after the function they are declared in.
+        for item in nested {
+            let parent = &self.index.functions[function];
+            let name = format!("{}::{}", parent.def_path, item.sig.ident);
+            let def_path = name.clone();
+            self.index.scopes[scope]
+                .names
+                .insert(item.sig.ident.to_string(), Name::Item(name.clone()));
+            let nested_scope = self.scope(module.package, &module.path, Some(scope), None);
+            let cfg_gated = self.index.functions[function].cfg_gated || has_cfg(&item.attrs);
+            self.function(
+                module,
+                name,
+                def_path,
+                FunctionKind::Free,
+                item.span(),
+                nested_scope,
+            );
+            self.signature(&item.sig, false, cfg_gated);
+            self.body(module, &item.block, self.index.functions.len() - 1);
+        }
+    }
+
+    fn finish(&mut self) {
+        for (function, nodes) in std::mem::take(&mut self.closures) {
+            let file = self.index.functions[function].file.clone();
+            self.number_closures(function, None, nodes, &file, None);
+        }
+
+        for derive in std::mem::take(&mut self.derives) {
+            for method in self.index.trait_methods(&derive.trait_).to_vec() {
+                self.index.functions.push(Function {
+                    name: format!("<{} as {}>::{}", derive.self_ty, derive.trait_, method),
+                    def_path: format!("{}::{}", derive.def_path, method),
+                    package: derive.package.clone(),
+                    file: derive.file.clone(),
+                    start: derive.line,
+                    end: derive.line,
+                    ident: LineColumn {
+                        line: derive.line,
+                        column: 0,
+                    },
+                    kind: FunctionKind::TraitImpl,
+                    has_body: false,
+                    receiver: true,
+                    generic: false,
+                    cfg_gated: false,
+                    scope: derive.scope,
+                    sig: None,
+                });
+            }
+        }
+    }
+}
+
+// rustc numbers the closures of each function or closure in the order they appear in its body,
+// after macros are expanded. Bodies are thus first collected as trees of closures and macro
+// invocations, and the invocations are expanded once all macros are known.
+enum ClosureNode {
+    Closure {
+        start: LineColumn,
+        end: usize,
+        children: Vec<ClosureNode>,
+    },
+    Invocation {
+        path: Vec<String>,
+        line: usize,
+        tokens: TokenStream,
+    },
+}
+
+// Bounds the expansion of recursive macros.
+const MAX_EXPANSION_DEPTH: usize = 8;
+
+impl Builder {
+    fn number_closures(
+        &mut self,
+        function: usize,
+        parent: Option<usize>,
+        nodes: Vec<ClosureNode>,
+        file: &Path,
+        invocation: Option<usize>,
+    ) {
+        let mut closures = Vec::new();
+        self.expand_closures(function, nodes, file, invocation, 0, &mut closures);
+        for (ordinal, (file, invocation, node)) in closures.into_iter().enumerate() {
+            let (start, end, children) = match node {
+                ClosureNode::Closure {
+                    start,
+                    end,
+                    children,
+                } => (start, end, children),
+                ClosureNode::Invocation { .. } => continue,
+            };
+            let parent_name = match parent {
+                Some(parent) => &self.index.closures[parent].name,
+                None => &self.index.functions[function].name,
+            };
+            self.index.closures.push(Closure {
+                name: format!("{}::{{closure#{}}}", parent_name, ordinal),
+                function,
+                file: file.clone(),
+                start: start.line,
+                column: start.column + 1,
+                end,
+                invocation,
+            });
+            let id = self.index.closures.len() - 1;
+            self.number_closures(function, Some(id), children, &file, invocation);
+        }
+    }
+
+    // Replaces the macro invocations among 'nodes' by the closures of their expansions. Each
+    // closure comes with the file it is defined in and the line of the outermost invocation it
+    // was expanded from.
+    fn expand_closures(
+        &self,
+        function: usize,
+        nodes: Vec<ClosureNode>,
+        file: &Path,
+        invocation: Option<usize>,
+        depth: usize,
+        closures: &mut Vec<(PathBuf, Option<usize>, ClosureNode)>,
+    ) {
+        for node in nodes {
+            let (path, line, tokens) = match node {
+                ClosureNode::Closure { .. } => {
+                    closures.push((file.to_path_buf(), invocation, node));
+                    continue;
+                }
+                ClosureNode::Invocation { path, line, tokens } => (path, line, tokens),
+            };
+            if depth == MAX_EXPANSION_DEPTH {
+                continue;
+            }
+            let scope = self.index.functions[function].scope;
+            let invoked = self.index.resolve(scope, &path);
+            let macro_def = self
+                .index
+                .macros
+                .iter()
+                .find(|m| invoked == m.path || path == [m.name.clone()]);
+            // The closures passed to other macros, e.g., println!, are among their arguments.
+            let (expansion, file) = match macro_def {
+                Some(macro_def) => (expand(&macro_def.tokens, &tokens), macro_def.file.as_path()),
+                None => (Some(tokens), file),
+            };
+            let nodes = expansion.map(closure_nodes).unwrap_or_default();
+            let invocation = invocation.or(Some(line));
+            self.expand_closures(function, nodes, file, invocation, depth + 1, closures);
+        }
+    }
+}
+
+// The closures and macro invocations of the expansion of a macro, which is either a block of
+// statements or a list of expressions.
+fn closure_nodes(tokens: TokenStream) -> Vec<ClosureNode> {
+    let mut visitor = BodyVisitor::new(0);
+    let block = syn::parse2::<syn::Block>(quote::quote!({ #tokens }));
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    match block {
+        Ok(block) => visitor.visit_block(&block),
+        Err(_) => {
+            if let Ok(args) = parser.parse2(tokens) {
+                args.iter().for_each(|arg| visitor.visit_expr(arg));
+            }
+        }
+    }
+    visitor.closures.pop().unwrap_or_default()
+}
+
+struct BodyVisitor {
+    function: usize,
+    nested: Vec<syn::ItemFn>,
+    uses: Vec<syn::UseTree>,
+    invocations: Vec<Invocation>,
+    // The closures and invocations of the body and of each closure being visited.
+    closures: Vec<Vec<ClosureNode>>,
+}
+
+impl BodyVisitor {
+    fn new(function: usize) -> Self {
+        BodyVisitor {
+            function,
+            nested: Vec::new(),
+            uses: Vec::new(),
+            invocations: Vec::new(),
+            closures: vec![Vec::new()],
+        }
+    }
+}
+
+impl BodyVisitor {
+    // Ends the closure being visited, which spans 'span'.
+    fn closure(&mut self, span: proc_macro2::Span) {
+        let children = self.closures.pop().unwrap_or_default();
+        self.closures
+            .last_mut()
+            .unwrap()
+            .push(ClosureNode::Closure {
+                start: span.start(),
+                end: span.end().line,
+                children,
+            });
+    }
+}
+
+impl<'ast> Visit<'ast> for BodyVisitor {
+    fn visit_expr_closure(&mut self, closure: &'ast syn::ExprClosure) {
+        self.closures.push(Vec::new());
+        syn::visit::visit_expr_closure(self, closure);
+        self.closure(closure.span());
+    }
+
+    // rustc numbers the coroutines of async blocks among the closures.
+    fn visit_expr_async(&mut self, block: &'ast syn::ExprAsync) {
+        self.closures.push(Vec::new());
+        syn::visit::visit_expr_async(self, block);
+        self.closure(block.span());
+    }
+
+    fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+        self.nested.push(item.clone());
+    }
+
+    fn visit_item_use(&mut self, item: &'ast syn::ItemUse) {
+        self.uses.push(item.tree.clone());
+    }
+
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        let path: Vec<String> = mac
+            .path
+            .segments
+            .iter()
+            .map(|s| s.ident.to_string())
+            .collect();
+        let line = mac.span().start().line;
+        self.closures
+            .last_mut()
+            .unwrap()
+            .push(ClosureNode::Invocation {
+                path: path.clone(),
+                line,
+                tokens: mac.tokens.clone(),
+            });
+        self.invocations.push(Invocation {
+            path,
+            caller: self.function,
+            line,
+            tokens: mac.tokens.clone(),
+        });
+    }
+}
+
+fn has_cfg(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("cfg"))
+}
+
+pub fn join(base: String, rest: &[String]) -> String {
+    let mut path = base;
+    for segment in rest {
+        path.push_str("::");
+        path.push_str(segment);
+    }
+    path
+}
+
+fn parent_module(module: &str) -> String {
+    match module.rfind("::") {
+        Some(pos) => module[..pos].to_string(),
+        None => module.to_string(),
+    }
+}
+
+// Drops the generic arguments of a type path, e.g., generics::base::Wrapper<T> becomes
+// generics::base::Wrapper.
+pub fn strip_generics(path: &str) -> String {
+    let mut stripped = String::new();
+    let mut depth = 0;
+    for c in path.chars() {
+        match c {
+            '<' => depth += 1,
+            '>' => depth -= 1,
+            c if depth == 0 => stripped.push(c),
+            _ => {}
+        }
+    }
+    stripped
+}
+
+// Token streams are printed with spaces between all tokens. Those around punctuation are removed
+// to get closer to the way rustc prints types and expressions.
+pub fn tokens_to_string(tokens: TokenStream) -> String {
+    let printed = tokens.to_string();
+    let mut result = String::new();
+    let mut chars = printed.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == ' ' {
+            let before = result.chars().last();
+            let after = chars.peek().copied();
+            let tight_before = matches!(before, Some('&' | '<' | '(' | '[' | ':' | '.'));
+            let tight_after = matches!(
+                after,
+                Some('<' | '>' | '(' | ')' | '[' | ']' | ':' | ',' | '.')
+            );
+            if tight_before || tight_after {
+                continue;
+            }
+        }
+        result.push(c);
+    }
+    result
+}
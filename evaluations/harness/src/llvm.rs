@@ -0,0 +1,529 @@
+// Call instructions of LLVM IR, mapped back to the calls of the benchmark sources through their
+// debug info.
+//
+// Call graphs only tell whether a function calls another one. To score IR-level tools at the
+// precision of call sites, each 'call' and 'invoke' instruction of a .ll file emitted with debug
+// info (-C debuginfo=2 --emit=llvm-ir) is attributed to a source location by its !dbg attachment:
+//
+//     %num1 = call i32 @_ZN7structs3lib3fat3Fat6method17h8d4c42929d89a903E(...), !dbg !141
+//     !141 = !DILocation(line: 48, column: 20, scope: !15)
+//
+// Calls inlined by LLVM (or by rustc's MIR inliner) leave no instruction behind. Their location is
+// the 'inlinedAt' location of the instructions of the inlined body, so that an instruction
+// inlined into bench::run at -C opt-level=3 yields the location of the instruction itself and one
+// location per inlined call that it went through, each with the function that was inlined there.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::error::Result;
+use crate::index::Index;
+use crate::names::canonical_path;
+use crate::names::Normalizer;
+use crate::score;
+use crate::score::Layer;
+use crate::tags::Filter;
+use crate::truth::CallSite;
+use crate::truth::Truth;
+
+pub struct Location {
+    pub file: PathBuf,
+    pub line: usize,
+    // The symbol (or, lacking one, the name) of the function the location belongs to. For inlined
+    // code, this is the inlined function rather than the one that contains the instruction.
+    pub function: String,
+}
+
+pub struct Call {
+    // The symbol of the function that contains the instruction.
+    pub function: String,
+    // The symbol of the called function, or None for indirect calls.
+    pub callee: Option<String>,
+    // The location of the instruction followed by the locations of the inlined calls it went
+    // through, innermost first. Empty if the instruction has no debug info.
+    pub locations: Vec<Location>,
+}
+
+impl Call {
+    // The source calls the instruction stands for, innermost first, each with its location and
+    // the called function: the callee of the instruction at its own location and the inlined
+    // function at each inlinedAt location.
+    pub fn source_calls(&self) -> impl Iterator<Item = (&Location, Option<&str>)> {
+        self.locations
+            .iter()
+            .enumerate()
+            .map(move |(pos, location)| {
+                let callee = match pos {
+                    0 => self.callee.as_deref(),
+                    _ => Some(self.locations[pos - 1].function.as_str()),
+                };
+                (location, callee)
+            })
+    }
+}
+
+// A metadata node, e.g., !DILocation(line: 48, column: 20, scope: !15), by its fields.
+struct Node {
+    kind: String,
+    fields: HashMap<String, String>,
+}
+
+impl Node {
+    fn reference(&self, field: &str) -> Option<usize> {
+        self.fields.get(field).and_then(|value| reference(value))
+    }
+
+    fn number(&self, field: &str) -> usize {
+        self.fields
+            .get(field)
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0)
+    }
+
+    fn string(&self, field: &str) -> Option<&str> {
+        self.fields
+            .get(field)
+            .and_then(|value| value.strip_prefix('"'))
+            .and_then(|value| value.strip_suffix('"'))
+    }
+}
+
+pub fn parse(source: &str) -> Result<Vec<Call>> {
+    let mut nodes: HashMap<usize, Node> = HashMap::new();
+    // The calls with the metadata id of their location.
+    let mut calls: Vec<(String, Option<String>, Option<usize>)> = Vec::new();
+    let mut function: Option<String> = None;
+
+    for (number, line) in source.lines().enumerate() {
+        let line = line.trim();
+        if let Some(definition) = line.strip_prefix("define ") {
+            let name = symbol(definition)
+                .ok_or_else(|| Error::new(format!("line {}: malformed definition", number + 1)))?;
+            function = Some(name.to_string());
+        } else if line == "}" {
+            function = None;
+        } else if let Some(node) = line.strip_prefix('!') {
+            if let Some((id, node)) = metadata(node) {
+                nodes.insert(id, node);
+            }
+        } else if let Some(function) = &function {
+            if let Some(callee) = call(line) {
+                if callee.is_some_and(|callee| callee.starts_with("llvm.")) {
+                    continue;
+                }
+                let location = line
+                    .rfind("!dbg !")
+                    .and_then(|pos| reference(&line[pos + "!dbg ".len()..]));
+                calls.push((function.clone(), callee.map(str::to_string), location));
+            }
+        }
+    }
+
+    Ok(calls
+        .into_iter()
+        .map(|(function, callee, location)| Call {
+            function,
+            callee,
+            locations: location.map_or_else(Vec::new, |id| locations(&nodes, id)),
+        })
+        .collect())
+}
+
+// The call graph of the direct calls, as 'opt -dot-callgraph' reports it: functions are labelled
+// by their symbols, and inlined and indirect calls have no edge.
+pub fn call_graph(calls: &[Call]) -> Dot {
+    let mut dot = Dot::default();
+    for call in calls {
+        dot.nodes
+            .insert(call.function.clone(), call.function.clone());
+        if let Some(callee) = &call.callee {
+            dot.nodes.insert(callee.clone(), callee.clone());
+            dot.edges.push((call.function.clone(), callee.clone()));
+        }
+    }
+    dot
+}
+
+// Follows a DILocation and its inlinedAt chain.
+fn locations(nodes: &HashMap<usize, Node>, mut id: usize) -> Vec<Location> {
+    let mut locations = Vec::new();
+    // Bounds malformed, cyclic chains.
+    while locations.len() < nodes.len() {
+        let node = match nodes.get(&id) {
+            Some(node) if node.kind == "DILocation" => node,
+            _ => break,
+        };
+        let scope = node.reference("scope");
+        let (file, function) = scope.map_or((PathBuf::new(), String::new()), |scope| {
+            (scope_file(nodes, scope), subprogram(nodes, scope))
+        });
+        locations.push(Location {
+            file,
+            line: node.number("line"),
+            function,
+        });
+        match node.reference("inlinedAt") {
+            Some(inlined_at) => id = inlined_at,
+            None => break,
+        }
+    }
+    locations
+}
+
+// The file of a scope: lexical blocks and subprograms refer to a DIFile.
+fn scope_file(nodes: &HashMap<usize, Node>, scope: usize) -> PathBuf {
+    let file = nodes
+        .get(&scope)
+        .and_then(|node| node.reference("file"))
+        .and_then(|file| nodes.get(&file));
+    match file {
+        Some(file) => {
+            let name = Path::new(file.string("filename").unwrap_or(""));
+            match file.string("directory") {
+                Some(directory) if name.is_relative() => Path::new(directory).join(name),
+                _ => name.to_path_buf(),
+            }
+        }
+        None => PathBuf::new(),
+    }
+}
+
+// The function a scope belongs to, following the parents of lexical blocks up to the subprogram.
+fn subprogram(nodes: &HashMap<usize, Node>, mut scope: usize) -> String {
+    for _ in 0..nodes.len() {
+        let node = match nodes.get(&scope) {
+            Some(node) => node,
+            None => break,
+        };
+        if node.kind == "DISubprogram" {
+            return node
+                .string("linkageName")
+                .or_else(|| node.string("name"))
+                .unwrap_or("")
+                .to_string();
+        }
+        match node.reference("scope") {
+            Some(parent) => scope = parent,
+            None => break,
+        }
+    }
+    String::new()
+}
+
+// The symbol defined by a 'define' line, e.g., @_ZN...E or @"_ZN...$LT$...E".
+fn symbol(text: &str) -> Option<&str> {
+    let start = text.find('@')? + 1;
+    let text = &text[start..];
+    match text.strip_prefix('"') {
+        Some(quoted) => quoted.find('"').map(|end| &quoted[..end]),
+        None => {
+            let end = text.find(|c: char| !is_name_char(c))?;
+            Some(&text[..end])
+        }
+    }
+}
+
+// The callee of a 'call' or 'invoke' instruction: Some(None) for indirect calls and None if the
+// line is not a call.
+fn call(line: &str) -> Option<Option<&str>> {
+    let instruction = match line.split_once(" = ") {
+        Some((result, instruction)) if result.starts_with('%') => instruction,
+        _ => line,
+    };
+    let instruction = instruction
+        .trim_start_matches("tail ")
+        .trim_start_matches("musttail ")
+        .trim_start_matches("notail ");
+    if !(instruction.starts_with("call ") || instruction.starts_with("invoke ")) {
+        return None;
+    }
+
+    // The callee is the first global (@f) or local (%f) name followed by the argument list.
+    let mut rest = instruction;
+    while let Some(pos) = rest.find(['@', '%']) {
+        let sigil = rest.as_bytes()[pos];
+        let name = &rest[pos + 1..];
+        let (value, after) = match name.strip_prefix('"') {
+            Some(quoted) => match quoted.find('"') {
+                Some(end) => (&quoted[..end], &quoted[end + 1..]),
+                None => return Some(None),
+            },
+            None => {
+                let end = name.find(|c: char| !is_name_char(c)).unwrap_or(name.len());
+                (&name[..end], &name[end..])
+            }
+        };
+        if after.starts_with('(') {
+            return Some(if sigil == b'@' { Some(value) } else { None });
+        }
+        rest = after;
+    }
+    Some(None)
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || ['_', '.', '$', '-'].contains(&c)
+}
+
+// A metadata definition, '141 = !DILocation(line: 48, column: 20, scope: !15)', without the
+// leading '!'.
+fn metadata(text: &str) -> Option<(usize, Node)> {
+    let (id, value) = text.split_once(" = ")?;
+    let id = id.parse().ok()?;
+    let value = value.trim_start_matches("distinct ").strip_prefix('!')?;
+    let open = value.find('(')?;
+    let close = value.rfind(')')?;
+    let kind = value[..open].to_string();
+    let fields = split_fields(&value[open + 1..close])
+        .into_iter()
+        .filter_map(|field| {
+            let (key, value) = field.split_once(": ")?;
+            Some((key.trim().to_string(), value.trim().to_string()))
+        })
+        .collect();
+    Some((id, Node { kind, fields }))
+}
+
+// Splits the fields of a metadata node at the commas outside strings and nested nodes.
+fn split_fields(text: &str) -> Vec<&str> {
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut quoted = false;
+    let mut start = 0;
+    let mut chars = text.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '\\' if quoted => {
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            '(' if !quoted => depth += 1,
+            ')' if !quoted => depth -= 1,
+            ',' if !quoted && depth == 0 => {
+                fields.push(&text[start..pos]);
+                start = pos + 1;
+            }
+            _ => {}
+        }
+    }
+    fields.push(&text[start..]);
+    fields
+}
+
+// Parses a metadata reference, '!141', possibly followed by other text.
+fn reference(text: &str) -> Option<usize> {
+    let digits = text.strip_prefix('!')?;
+    let end = digits
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(digits.len());
+    digits[..end].parse().ok()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resolution {
+    // An instruction calls the target at the site.
+    Called,
+    // The target was inlined at the site.
+    Inlined,
+    // No instruction stands for the call, e.g., it was optimized away.
+    Missed,
+}
+
+impl Resolution {
+    pub fn name(self) -> &'static str {
+        match self {
+            Resolution::Called => "called",
+            Resolution::Inlined => "inlined",
+            Resolution::Missed => "missed",
+        }
+    }
+}
+
+pub struct SiteResolution<'t> {
+    pub site: &'t CallSite,
+    pub resolution: Resolution,
+    // The canonical names of the functions whose instructions resolve the site. Differs from the
+    // site's caller if the caller was inlined.
+    pub functions: BTreeSet<String>,
+}
+
+// Maps the annotated calls of a package to the instructions that stand for them. A call is
+// resolved if an instruction, or an inlinedAt location of one, is on the line of the call and
+// calls (or inlined) each of its targets. Calls inside macro definitions are located at the
+// macro's invocation, as rustc attributes the code expanded from macro_rules! macros to the
+// invocation.
+pub fn resolve<'t>(
+    package: &'t str,
+    truth: &'t Truth,
+    index: &Index,
+    normalizer: &Normalizer,
+    calls: &[Call],
+    filter: &Filter,
+) -> Vec<SiteResolution<'t>> {
+    let sites = truth
+        .package_sites(package)
+        .filter(|site| site.is_scored(Layer::Benchmark) && filter.matches(site));
+    let mut resolutions = Vec::new();
+    for site in sites {
+        let (file, line) = match site.invocation {
+            Some(line) => match index.enclosing_function(&site.caller) {
+                Some(caller) => (index.functions[caller].file.as_path(), line),
+                None => continue,
+            },
+            None => (site.file.as_path(), site.line),
+        };
+
+        let mut resolution = Resolution::Called;
+        let mut functions = BTreeSet::new();
+        for target in &site.layer_targets(Layer::Benchmark) {
+            let mut best = Resolution::Missed;
+            for call in calls {
+                for (pos, (location, callee)) in call.source_calls().enumerate() {
+                    if location.line != line || !location.file.ends_with(file) {
+                        continue;
+                    }
+                    let matched = callee
+                        .and_then(|callee| normalizer.canonical(callee))
+                        .is_some_and(|callee| {
+                            score::matches(index, target, &canonical_path(&callee))
+                        });
+                    if !matched {
+                        continue;
+                    }
+                    let found = if pos == 0 {
+                        Resolution::Called
+                    } else {
+                        Resolution::Inlined
+                    };
+                    best = best.min(found);
+                    if let Some(function) = normalizer.canonical(&call.function) {
+                        functions.insert(canonical_path(&function));
+                    }
+                }
+            }
+            resolution = resolution.max(best);
+        }
+        resolutions.push(SiteResolution {
+            site,
+            resolution,
+            functions,
+        });
+    }
+    resolutions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RUN: &str = "_ZN8generics5bench3run17h89d9cc231ea7a78eE";
+
+    // bench::run calls Fat::method directly, a function pointer, an intrinsic, and a panic that
+    // was inlined from inner through middle.
+    const SOURCE: &str = r#"
+define void @_ZN8generics5bench3run17h89d9cc231ea7a78eE(ptr %f) unnamed_addr #0 !dbg !10 {
+start:
+  %num1 = call i32 @_ZN7structs3lib3fat3Fat6method17h8d4c42929d89a903E(ptr %fat), !dbg !20
+  %num2 = call i32 %f(i32 1), !dbg !21
+  call void @llvm.dbg.declare(metadata ptr %fat, metadata !30, metadata !DIExpression()), !dbg !20
+  %sum = add i32 %num1, %num2, !dbg !20
+  call void @_ZN4core9panicking5panic17h0123456789abcdefE(), !dbg !23
+  ret void
+}
+
+!1 = !DIFile(filename: "src/lib.rs", directory: "/root/crate/src/generics")
+!2 = !DIFile(filename: "/rustc/library/core/src/inner.rs", directory: "/ignored")
+!10 = distinct !DISubprogram(name: "run", linkageName: "_ZN8generics5bench3run17h89d9cc231ea7a78eE", scope: !11, file: !1, line: 40)
+!11 = !DINamespace(name: "bench", scope: null)
+!12 = distinct !DISubprogram(name: "middle", linkageName: "_ZN8generics3lib6middle17h0000000000000001E", scope: null, file: !1, line: 10)
+!13 = distinct !DISubprogram(name: "inner", scope: null, file: !2, line: 5)
+!14 = distinct !DILexicalBlock(scope: !13, file: !2, line: 6, column: 9)
+!20 = !DILocation(line: 48, column: 20, scope: !10)
+!21 = !DILocation(line: 50, column: 9, scope: !10)
+!23 = !DILocation(line: 7, column: 5, scope: !14, inlinedAt: !24)
+!24 = !DILocation(line: 12, column: 5, scope: !12, inlinedAt: !25)
+!25 = !DILocation(line: 55, column: 9, scope: !10)
+"#;
+
+    fn lines(call: &Call) -> Vec<usize> {
+        call.locations
+            .iter()
+            .map(|location| location.line)
+            .collect()
+    }
+
+    #[test]
+    fn parses_direct_and_indirect_calls_and_skips_intrinsics() {
+        let calls = parse(SOURCE).unwrap();
+        assert_eq!(calls.len(), 3);
+        assert!(calls.iter().all(|call| call.function == RUN));
+
+        let direct = &calls[0];
+        assert_eq!(
+            direct.callee.as_deref(),
+            Some("_ZN7structs3lib3fat3Fat6method17h8d4c42929d89a903E")
+        );
+        assert_eq!(lines(direct), [48]);
+        assert_eq!(direct.locations[0].function, RUN);
+
+        let indirect = &calls[1];
+        assert_eq!(indirect.callee, None);
+        assert_eq!(lines(indirect), [50]);
+    }
+
+    #[test]
+    fn joins_relative_file_names_to_their_directory() {
+        let calls = parse(SOURCE).unwrap();
+        assert_eq!(
+            calls[0].locations[0].file,
+            Path::new("/root/crate/src/generics/src/lib.rs")
+        );
+        assert_eq!(
+            calls[2].locations[0].file,
+            Path::new("/rustc/library/core/src/inner.rs")
+        );
+    }
+
+    #[test]
+    fn follows_the_inlined_at_chain() {
+        let calls = parse(SOURCE).unwrap();
+        let inlined = &calls[2];
+        assert_eq!(lines(inlined), [7, 12, 55]);
+        // The call at the location in middle is the inlined inner, named by its name for lack of a
+        // linkage name.
+        let source_calls: Vec<(usize, Option<&str>)> = inlined
+            .source_calls()
+            .map(|(location, callee)| (location.line, callee))
+            .collect();
+        assert_eq!(
+            source_calls,
+            [
+                (7, Some("_ZN4core9panicking5panic17h0123456789abcdefE")),
+                (12, Some("inner")),
+                (55, Some("_ZN8generics3lib6middle17h0000000000000001E")),
+            ]
+        );
+    }
+
+    #[test]
+    fn bounds_cyclic_inlined_at_chains() {
+        let calls = parse(
+            r#"
+define void @f() !dbg !10 {
+  call void @g(), !dbg !20
+}
+!10 = distinct !DISubprogram(name: "f", scope: null)
+!20 = !DILocation(line: 1, column: 1, scope: !10, inlinedAt: !21)
+!21 = !DILocation(line: 2, column: 1, scope: !10, inlinedAt: !20)
+"#,
+        )
+        .unwrap();
+        assert_eq!(calls.len(), 1);
+        // At most one location per metadata node.
+        assert_eq!(lines(&calls[0]), [1, 2, 1]);
+    }
+}
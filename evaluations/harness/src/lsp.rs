@@ -0,0 +1,287 @@
+// Call graphs from the call hierarchy of a language server, as editors show it.
+//
+// The server, rust-analyzer by default, is launched over stdio with the workspace of the benchmark
+// packages as its project. Once it has loaded it, each benchmark function with a body is looked up
+// by the position of its name (textDocument/prepareCallHierarchy) and its outgoing calls are
+// requested (callHierarchy/outgoingCalls). The functions that the calls go to are mapped back to
+// the index by the position of their names, so the resulting graph is named canonically; calls to
+// functions outside the benchmark crates are dropped.
+//
+// Editors attribute the calls inside closures to the function that defines them, and show the
+// trait's method for calls that are dispatched dynamically or on generic receivers.
+
+use std::fs;
+use std::io::BufRead;
+use std::io::BufReader;
+use std::io::Read;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::ChildStdin;
+use std::process::ChildStdout;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+
+use serde_json::json;
+use serde_json::Value;
+
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::error::Result;
+use crate::index::Index;
+
+pub const SERVER: &str = "rust-analyzer";
+
+// JSON-RPC error code of requests that raced with a change of the server's state, e.g., a
+// request sent while the projects were still being loaded. Such requests are retried.
+const CONTENT_MODIFIED: i64 = -32801;
+const RETRIES: usize = 10;
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+struct Client {
+    server: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    next_id: u64,
+    // Set once rust-analyzer reports that it has loaded the projects (experimental/serverStatus).
+    quiescent: bool,
+}
+
+impl Client {
+    fn launch(command: &str, root: &Path) -> Result<Client> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| Error::new("empty language server command"))?;
+        let mut server = Command::new(program)
+            .args(words)
+            .current_dir(root)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::new(format!("cannot run {}: {}", command, e)))?;
+        let input = server.stdin.take().unwrap();
+        let output = BufReader::new(server.stdout.take().unwrap());
+        Ok(Client {
+            server,
+            input,
+            output,
+            next_id: 1,
+            quiescent: false,
+        })
+    }
+
+    fn send(&mut self, message: &Value) -> Result<()> {
+        let body = message.to_string();
+        write!(self.input, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+        self.input.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Value> {
+        let mut length = None;
+        loop {
+            let mut header = String::new();
+            if self.output.read_line(&mut header)? == 0 {
+                return Err(Error::new("the language server exited"));
+            }
+            let header = header.trim_end();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(value) = header.strip_prefix("Content-Length:") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+        let length = length.ok_or_else(|| Error::new("message without Content-Length"))?;
+        let mut body = vec![0; length];
+        self.output.read_exact(&mut body)?;
+        let message: Value = serde_json::from_slice(&body)?;
+
+        // Requests of the server, e.g., to register capabilities, are acknowledged without acting
+        // on them.
+        if let (Some(id), Some(_)) = (message.get("id"), message.get("method")) {
+            let reply = json!({ "jsonrpc": "2.0", "id": id, "result": null });
+            self.send(&reply)?;
+        }
+        if message.get("method").and_then(Value::as_str) == Some("experimental/serverStatus") {
+            self.quiescent = message["params"]["quiescent"].as_bool().unwrap_or(false);
+        }
+        Ok(message)
+    }
+
+    fn notify(&mut self, method: &str, params: Value) -> Result<()> {
+        self.send(&json!({ "jsonrpc": "2.0", "method": method, "params": params }))
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> Result<Value> {
+        for _ in 0..RETRIES {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.send(&json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))?;
+            let response = loop {
+                let message = self.receive()?;
+                if message.get("id") == Some(&json!(id)) && message.get("method").is_none() {
+                    break message;
+                }
+            };
+            match response.get("error") {
+                None => return Ok(response.get("result").cloned().unwrap_or(Value::Null)),
+                Some(error) if error["code"].as_i64() == Some(CONTENT_MODIFIED) => {
+                    thread::sleep(RETRY_DELAY);
+                }
+                Some(error) => {
+                    return Err(Error::new(format!(
+                        "{} failed: {}",
+                        method,
+                        error["message"].as_str().unwrap_or("unknown error")
+                    )))
+                }
+            }
+        }
+        Err(Error::new(format!(
+            "{} failed: the server kept changing",
+            method
+        )))
+    }
+
+    fn wait_until_quiescent(&mut self) -> Result<()> {
+        while !self.quiescent {
+            self.receive()?;
+        }
+        Ok(())
+    }
+
+    fn shutdown(mut self) -> Result<()> {
+        self.request("shutdown", Value::Null)?;
+        self.notify("exit", Value::Null)?;
+        drop(self.input);
+        self.server.wait()?;
+        Ok(())
+    }
+}
+
+// Builds the call graph of the given packages with the language server started by 'command'.
+// Nodes are labelled by canonical names.
+pub fn call_graph(command: &str, root: &Path, index: &Index, packages: &[&str]) -> Result<Dot> {
+    let root = fs::canonicalize(root)?;
+    let mut client = Client::launch(command, &root)?;
+
+    let project = root.join("Cargo.toml").to_string_lossy().into_owned();
+    client.request(
+        "initialize",
+        json!({
+            "processId": std::process::id(),
+            "rootUri": uri(&root),
+            "workspaceFolders": [{ "uri": uri(&root), "name": "benchmark" }],
+            "capabilities": {
+                "textDocument": { "callHierarchy": {} },
+                "experimental": { "serverStatusNotification": true },
+            },
+            "initializationOptions": {
+                "linkedProjects": [project],
+                "checkOnSave": false,
+            },
+        }),
+    )?;
+    client.notify("initialized", json!({}))?;
+    client.wait_until_quiescent()?;
+
+    let mut dot = Dot::default();
+    let functions = index
+        .functions
+        .iter()
+        .filter(|function| function.has_body && packages.contains(&function.package.as_str()));
+    for function in functions {
+        let position = json!({
+            "textDocument": { "uri": uri(&root.join(&function.file)) },
+            "position": {
+                "line": function.ident.line - 1,
+                "character": function.ident.column,
+            },
+        });
+        let items = client.request("textDocument/prepareCallHierarchy", position)?;
+        // The function itself, as the server knows it.
+        let item = match items.as_array().and_then(|items| items.first()) {
+            Some(item) => item.clone(),
+            None => continue,
+        };
+        dot.nodes
+            .insert(function.name.clone(), function.name.clone());
+
+        let calls = client.request("callHierarchy/outgoingCalls", json!({ "item": item }))?;
+        for call in calls.as_array().map(Vec::as_slice).unwrap_or(&[]) {
+            let callee = match item_function(index, &root, &call["to"]) {
+                Some(callee) => callee,
+                None => continue,
+            };
+            dot.nodes.insert(callee.clone(), callee.clone());
+            dot.edges.push((function.name.clone(), callee));
+        }
+    }
+
+    client.shutdown()?;
+    Ok(dot)
+}
+
+// The canonical name of the function a call hierarchy item stands for, looked up by the position
+// of its name.
+fn item_function(index: &Index, root: &Path, item: &Value) -> Option<String> {
+    let path = path(item["uri"].as_str()?)?;
+    let file = path.strip_prefix(root).ok()?;
+    let start = &item["selectionRange"]["start"];
+    let line = start["line"].as_u64()? as usize + 1;
+    let column = start["character"].as_u64()? as usize;
+    let mut candidates = index
+        .functions
+        .iter()
+        .filter(|function| function.file == file && function.ident.line == line);
+    let first = candidates.next()?;
+    let function = std::iter::once(first)
+        .chain(candidates)
+        .find(|function| function.ident.column == column)
+        .unwrap_or(first);
+    Some(function.name.clone())
+}
+
+fn uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut bytes = Vec::new();
+    let mut pos = 0;
+    while pos < encoded.len() {
+        let decoded = match encoded[pos] {
+            b'%' => encoded
+                .get(pos + 1..pos + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match decoded {
+            Some(byte) => {
+                bytes.push(byte);
+                pos += 3;
+            }
+            None => {
+                bytes.push(encoded[pos]);
+                pos += 1;
+            }
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
@@ -0,0 +1,1249 @@
+// Evaluation harness of the call-graph benchmark.
+//
+// The harness extracts the ground truth from the annotated calls of the benchmark packages, brings
+// the call graphs produced by call-graph generators to a canonical form, scores them against the
+// ground truth and keeps a history of the results. See README.md for its usage.
+
+mod dot;
+mod error;
+mod expand;
+mod explorer;
+mod graph;
+mod index;
+mod llvm;
+mod lsp;
+mod mir;
+mod names;
+mod packages;
+mod readme;
+mod run;
+mod rustdoc;
+mod schema;
+mod score;
+mod store;
+mod sweep;
+mod tags;
+mod truth;
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::process::Command;
+use std::time::Duration;
+
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::index::Index;
+use crate::llvm::Resolution;
+use crate::names::Normalizer;
+use crate::run::Adapter;
+use crate::run::RunMetrics;
+use crate::run::RunStatus;
+use crate::schema::ScoreReport;
+use crate::schema::TruthManifest;
+use crate::score::Layer;
+use crate::score::MatchMode;
+use crate::store::PackageResult;
+use crate::store::Record;
+use crate::store::RecordKey;
+use crate::store::Store;
+use crate::sweep::Config;
+use crate::sweep::ConfigScores;
+use crate::tags::Filter;
+use crate::truth::Truth;
+
+const USAGE: &str = "\
+usage: harness <command> [options] [arguments]
+
+commands:
+    truth                       Print the annotated calls that make up the ground truth.
+    score <package>=<dot>...    Score a tool's call graphs against the ground truth.
+    score --adapter <command> [<package>...]
+                                Run an adapter that produces a tool's call graphs and score
+                                them, with the cost of each run (see README.md).
+    record <package>=<dot>...   Score a tool's call graphs and add the result to the store.
+    record --adapter <command> [<package>...]
+    records                     List the results in the store.
+    compare <old> <new>         List the edges newly missed or newly reported as spurious per
+                                package between two stored results. Exits with status 1 if
+                                there are any.
+    locate <package>=<ll>...    Map the call instructions of LLVM IR files, emitted with debug
+                                info, to the annotated calls and list the calls that are
+                                called, inlined or missed.
+    lsp [<package>...]          Print the call graph of the packages (all by default) that the
+                                call hierarchy of a language server shows, in DOT format.
+    mir graph [<package>...]    Print the call graph of the MIR that rustc prints for the
+                                packages (all by default) in DOT format. Virtual and indirect
+                                calls go to nodes of unresolved calls.
+    mir check [<package>...]    Check the statically dispatched calls of the ground truth
+                                against the MIR. Exits with status 1 if they differ.
+    readme update               Rewrite the links of the call examples of src/README.md to the
+                                annotated calls they name (see README.md).
+    readme check                Check that the links of the call examples are up to date.
+                                Exits with status 1 otherwise.
+    rustdoc [<package>...]      Check that the annotated targets of the packages (all by
+                                default) name exactly one function of the benchmark crates,
+                                as documented by rustdoc. Exits with status 1 otherwise.
+    sweep                       Build main at each opt-level and LTO mode and score the graph
+                                of the direct calls of its LLVM IR, and the graphs of the tool
+                                of --adapter, which are recorded per configuration, telling the
+                                calls inlined away from the missed ones.
+    query <query> <function>... Query a call graph, the expected one by default:
+                                callers <f>, callees <f>, reachable <f>, path <from> <to>,
+                                scc [<f>]. Functions are given by canonical names or suffixes
+                                of them, e.g., '<Thin as DefaultTrait>::default_method'.
+    explore                     Write a static HTML explorer of the call sites and of the
+                                results in the store to --output (see README.md).
+    schema <document>           Print the JSON Schema of a document ('truth' or 'report').
+    schema write|check          Write the published schemas to evaluations/schema, or check
+                                that they are up to date. Exits with status 1 if they are not.
+    validate <document> <file>  Validate a document against its schema, migrating it from an
+                                older schema version if needed.
+    migrate <document> <file>   Print a document migrated to the current schema version.
+
+options:
+    --root <dir>                Root of the benchmark repository.
+    --store <dir>               Results store (default: <root>/evaluations/results).
+    --tool <name>               Name of the evaluated tool (record, sweep).
+    --tool-version <version>    Version of the evaluated tool (record, sweep).
+    --rustc <version>           Version of rustc (record, sweep, default: output of 'rustc -V').
+    --commit <commit>           Benchmark commit (record, sweep, default: HEAD of the repository).
+    --cfg <cfg>                 cfg configuration the benchmark was compiled with (record, sweep,
+                                default: 'default').
+    --format text|json          Output format of truth and score (default: text). JSON output
+                                is a truth manifest or a score report (see schema).
+    --truth <manifest>          Score against a truth manifest instead of the annotations
+                                (score, record, readme, explore).
+    --filter <tags>             Restrict truth and score to the calls with the given tags, e.g.,
+                                'syntax=ufcs,dispatch=dynamic,cross_crate'. See README.md for
+                                the tags. Spurious edges are not reported by filtered scores.
+    --graph <source>            Graph to query: 'truth' (default), a DOT file or a stored
+                                record (query).
+    --match <mode>              Granularity at which calls of generic functions are matched
+                                (score, record, query): 'instance', 'definition' or
+                                'collapsed' (default). See README.md.
+    --layer <layer>             Calls to score (score, record, query, mir graph): 'benchmark'
+                                (default) for the calls between the benchmark crates, or 'std'
+                                to add the calls into the standard library. See README.md.
+    --adapter <command>         Shell command that writes the call graph of the package
+                                $HARNESS_PACKAGE to $HARNESS_OUTPUT (score, record, sweep).
+    --timeout <seconds>         Timeout of each adapter run (default: 600).
+    --server <command>          Language server to run (lsp, default: rust-analyzer).
+    --opt-levels <levels>       opt-levels to sweep (default: 0,1,2,3,s,z).
+    --lto <modes>               LTO modes to sweep: 'off', 'thin' or 'fat' (default: off,fat).
+    --output <dir>              Directory to write the explorer to (explore, default:
+                                <root>/target/explorer).
+    --target-dir <dir>          Target directory of the builds of sweep, mir and rustdoc
+                                (default: <root>/target/<command>).
+";
+
+// The lines of the standard error of a failed adapter run that are shown with its scores.
+const STDERR_LINES: usize = 5;
+
+struct Args {
+    command: String,
+    options: HashMap<String, String>,
+    arguments: Vec<String>,
+}
+
+impl Args {
+    fn parse() -> Result<Args> {
+        let mut args = std::env::args().skip(1);
+        let command = args.next().ok_or_else(|| Error::new(USAGE))?;
+        let mut options = HashMap::new();
+        let mut arguments = Vec::new();
+        while let Some(arg) = args.next() {
+            match arg.strip_prefix("--") {
+                Some(name) => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Error::new(format!("missing value of option --{}", name)))?;
+                    options.insert(name.to_string(), value);
+                }
+                None => arguments.push(arg),
+            }
+        }
+        Ok(Args {
+            command,
+            options,
+            arguments,
+        })
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn required(&self, name: &str) -> Result<&str> {
+        self.option(name)
+            .ok_or_else(|| Error::new(format!("option --{} is required", name)))
+    }
+
+    fn root(&self) -> PathBuf {
+        self.option("root")
+            .map(PathBuf::from)
+            .unwrap_or_else(packages::default_root)
+    }
+
+    fn filter(&self) -> Result<Filter> {
+        Filter::parse(self.option("filter").unwrap_or(""))
+    }
+
+    fn match_mode(&self) -> Result<MatchMode> {
+        self.option("match")
+            .map_or(Ok(MatchMode::default()), MatchMode::parse)
+    }
+
+    fn layer(&self) -> Result<Layer> {
+        self.option("layer")
+            .map_or(Ok(Layer::default()), Layer::parse)
+    }
+
+    fn timeout(&self) -> Result<Duration> {
+        match self.option("timeout") {
+            None => Ok(Duration::from_secs(run::TIMEOUT)),
+            Some(seconds) => seconds
+                .parse()
+                .map(Duration::from_secs)
+                .map_err(|_| Error::new(format!("malformed timeout '{}'", seconds))),
+        }
+    }
+
+    fn json(&self) -> Result<bool> {
+        match self.option("format") {
+            None | Some("text") => Ok(false),
+            Some("json") => Ok(true),
+            Some(format) => Err(Error::new(format!("unknown output format '{}'", format))),
+        }
+    }
+
+    fn store(&self) -> Store {
+        match self.option("store") {
+            Some(dir) => Store::new(dir),
+            None => Store::new(self.root().join("evaluations").join("results")),
+        }
+    }
+}
+
+fn main() {
+    let result = Args::parse().and_then(|args| match args.command.as_str() {
+        "help" | "--help" | "-h" => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => packages::load(&args.root()).and_then(|_| run(&args)),
+    });
+
+    if let Err(error) = result {
+        eprintln!("error: {}", error);
+        process::exit(2);
+    }
+}
+
+fn run(args: &Args) -> Result<()> {
+    match args.command.as_str() {
+        "truth" => truth(args),
+        "score" => score(args),
+        "locate" => locate(args),
+        "lsp" => lsp(args),
+        "mir" => mir(args),
+        "readme" => readme(args),
+        "rustdoc" => rustdoc(args),
+        "sweep" => sweep(args),
+        "record" => record(args),
+        "records" => records(args),
+        "compare" => compare(args),
+        "query" => query(args),
+        "explore" => explore(args),
+        "schema" => schema(args),
+        "validate" => validate(args),
+        "migrate" => migrate(args),
+        command => Err(Error::new(format!(
+            "unknown command '{}'\n\n{}",
+            command, USAGE
+        ))),
+    }
+}
+
+fn truth(args: &Args) -> Result<()> {
+    let root = args.root();
+    let index = Index::build(&root)?;
+    let mut truth = Truth::extract(&root, &index)?;
+    let filter = args.filter()?;
+    truth.sites.retain(|site| filter.matches(site));
+    if args.json()? {
+        let manifest = TruthManifest {
+            schema_version: schema::TRUTH_VERSION,
+            sites: truth.sites,
+        };
+        print!("{}", schema::to_json(&manifest));
+        return Ok(());
+    }
+    for site in &truth.sites {
+        println!(
+            "{}:{} [{}]",
+            site.file.display(),
+            site.line,
+            site.kind.annotation()
+        );
+        if let Some(tags) = &site.tags {
+            println!("    {}", tags);
+        }
+        println!("    {}", site.caller);
+        if site.targets.is_empty() {
+            println!("    -> ? ({})", site.annotation);
+        }
+        for target in &site.targets {
+            println!("    -> {}", target);
+        }
+        for instance in &site.instances {
+            println!("    instance {} -> {}", instance.caller, instance.callee);
+        }
+        for callback in &site.callbacks {
+            println!("    callback {} -> {}", callback.caller, callback.callee);
+        }
+        for candidate in &site.candidates {
+            println!("    candidate {}", candidate);
+        }
+    }
+    for warning in &truth.warnings {
+        eprintln!("warning: {}", warning);
+    }
+    Ok(())
+}
+
+// The scores of a tool's call graphs and, if an adapter produced them, the cost of each run.
+struct Evaluation {
+    results: Vec<(String, PackageResult)>,
+    runs: BTreeMap<String, RunMetrics>,
+}
+
+// Scores the call graphs given as <package>=<dot file> arguments, or those that the adapter given
+// by --adapter produces for the packages given as arguments (all packages by default).
+fn evaluate(args: &Args) -> Result<Evaluation> {
+    let root = args.root();
+    let mut graphs = Vec::new();
+    let mut runs = BTreeMap::new();
+    match args.option("adapter") {
+        Some(command) => {
+            let adapter = Adapter {
+                command: command.to_string(),
+                timeout: args.timeout()?,
+                env: Vec::new(),
+            };
+            let names: Vec<&str> = if args.arguments.is_empty() {
+                packages::all()
+                    .iter()
+                    .map(|package| package.name.as_str())
+                    .collect()
+            } else {
+                args.arguments.iter().map(String::as_str).collect()
+            };
+            for name in names {
+                let package = packages::find(name)
+                    .ok_or_else(|| Error::new(format!("unknown benchmark package '{}'", name)))?;
+                eprintln!("running the adapter on {}", name);
+                let (metrics, dot) = adapter.run(name, &root.join(&package.dir))?;
+                runs.insert(name.to_string(), metrics);
+                graphs.extend(dot.map(|dot| (name.to_string(), dot)));
+            }
+        }
+        None => {
+            if args.arguments.is_empty() {
+                return Err(Error::new(
+                    "no call graphs given, expected <package>=<dot file> arguments",
+                ));
+            }
+            for argument in &args.arguments {
+                let (package, file) = match argument.split_once('=') {
+                    Some(parts) => parts,
+                    None => {
+                        return Err(Error::new(format!(
+                            "expected <package>=<dot file>, got '{}'",
+                            argument
+                        )))
+                    }
+                };
+                if packages::find(package).is_none() {
+                    return Err(Error::new(format!(
+                        "unknown benchmark package '{}'",
+                        package
+                    )));
+                }
+                let source = fs::read_to_string(file)
+                    .map_err(|e| Error::new(format!("cannot read {}: {}", file, e)))?;
+                let dot =
+                    dot::parse(&source).map_err(|e| Error::new(format!("{}: {}", file, e)))?;
+                graphs.push((package.to_string(), dot));
+            }
+        }
+    }
+
+    let index = Index::build(&root)?;
+    let truth = match args.option("truth") {
+        Some(manifest) => Truth::from_manifest(schema::load(manifest.as_ref())?),
+        None => Truth::extract(&root, &index)?,
+    };
+    let layer = args.layer()?;
+    let normalizer = Normalizer::with_layer(&index, layer);
+    let filter = args.filter()?;
+    let mode = args.match_mode()?;
+
+    let mut results = Vec::new();
+    for (package, dot) in graphs {
+        let graph = mode.graph(CallGraph::from_dot(&dot, &normalizer));
+        let score = score::score(&package, &truth, &index, &graph, &filter, mode, layer);
+        results.push((package, PackageResult { graph, score }));
+    }
+    Ok(Evaluation { results, runs })
+}
+
+fn print_scores(evaluation: &Evaluation) {
+    let runs = &evaluation.runs;
+    print!(
+        "{:<24} {:>6} {:>9} {:>7} {:>9}",
+        "package", "sites", "resolved", "missed", "spurious"
+    );
+    if !runs.is_empty() {
+        print!(" {:>8} {:>9} {:>10}", "status", "time", "peak RSS");
+    }
+    println!();
+
+    // Packages whose adapter run failed have no result and come last.
+    let scored: Vec<&str> = evaluation
+        .results
+        .iter()
+        .map(|(package, _)| package.as_str())
+        .collect();
+    let failed = runs
+        .keys()
+        .map(String::as_str)
+        .filter(|package| !scored.contains(package));
+    let packages: Vec<&str> = scored.iter().copied().chain(failed).collect();
+    for package in packages {
+        let score = evaluation
+            .results
+            .iter()
+            .find(|(name, _)| name == package)
+            .map(|(_, result)| &result.score);
+        match score {
+            Some(score) => {
+                // Filtered scores may leave no calls of a package to resolve.
+                let resolved = if score.sites == 0 {
+                    "-".to_string()
+                } else {
+                    format!("{:.0}%", score.percentage())
+                };
+                print!(
+                    "{:<24} {:>6} {:>9} {:>7} {:>9}",
+                    package,
+                    score.sites,
+                    resolved,
+                    score.missed.len(),
+                    score.spurious.len()
+                );
+            }
+            // The adapter failed, there is no graph to score.
+            None => print!(
+                "{:<24} {:>6} {:>9} {:>7} {:>9}",
+                package, "-", "-", "-", "-"
+            ),
+        }
+        if let Some(run) = runs.get(package) {
+            print!(
+                " {:>8} {:>9} {:>10}",
+                run.status.name(),
+                format!("{:.1} s", run.wall_time),
+                format!("{} MiB", run.peak_rss / 1024)
+            );
+        }
+        println!();
+    }
+
+    for (package, run) in runs {
+        if let Some(error) = &run.error {
+            println!("{}: {}: {}", package, run.status.name(), error);
+            let lines: Vec<&str> = run.stderr.lines().collect();
+            for line in &lines[lines.len().saturating_sub(STDERR_LINES)..] {
+                println!("    {}", line);
+            }
+        }
+    }
+}
+
+fn score(args: &Args) -> Result<()> {
+    let evaluation = evaluate(args)?;
+    if args.json()? {
+        let report = ScoreReport {
+            schema_version: schema::REPORT_VERSION,
+            mode: args.match_mode()?,
+            layer: args.layer()?,
+            packages: evaluation
+                .results
+                .into_iter()
+                .map(|(package, result)| (package, result.score))
+                .collect(),
+            runs: evaluation.runs,
+        };
+        print!("{}", schema::to_json(&report));
+        return Ok(());
+    }
+    print_scores(&evaluation);
+    for (package, result) in &evaluation.results {
+        for edge in &result.score.missed {
+            println!("{}: missed {} -> {}", package, edge.caller, edge.callee);
+        }
+        for edge in &result.score.spurious {
+            println!("{}: spurious {} -> {}", package, edge.caller, edge.callee);
+        }
+    }
+    Ok(())
+}
+
+fn locate(args: &Args) -> Result<()> {
+    if args.arguments.is_empty() {
+        return Err(Error::new(
+            "no LLVM IR files given, expected <package>=<ll file> arguments",
+        ));
+    }
+    let root = args.root();
+    let index = Index::build(&root)?;
+    let truth = match args.option("truth") {
+        Some(manifest) => Truth::from_manifest(schema::load(manifest.as_ref())?),
+        None => Truth::extract(&root, &index)?,
+    };
+    let normalizer = Normalizer::new(&index);
+    let filter = args.filter()?;
+
+    let mut packages = Vec::new();
+    for argument in &args.arguments {
+        let (package, file) = match argument.split_once('=') {
+            Some(parts) => parts,
+            None => {
+                return Err(Error::new(format!(
+                    "expected <package>=<ll file>, got '{}'",
+                    argument
+                )))
+            }
+        };
+        if packages::find(package).is_none() {
+            return Err(Error::new(format!(
+                "unknown benchmark package '{}'",
+                package
+            )));
+        }
+        let source = fs::read_to_string(file)
+            .map_err(|e| Error::new(format!("cannot read {}: {}", file, e)))?;
+        let calls = llvm::parse(&source).map_err(|e| Error::new(format!("{}: {}", file, e)))?;
+        let resolutions = llvm::resolve(package, &truth, &index, &normalizer, &calls, &filter);
+        packages.push((package.to_string(), resolutions));
+    }
+
+    println!(
+        "{:<24} {:>6} {:>7} {:>8} {:>7}",
+        "package", "sites", "called", "inlined", "missed"
+    );
+    for (package, resolutions) in &packages {
+        let count = |resolution| {
+            resolutions
+                .iter()
+                .filter(|r| r.resolution == resolution)
+                .count()
+        };
+        println!(
+            "{:<24} {:>6} {:>7} {:>8} {:>7}",
+            package,
+            resolutions.len(),
+            count(Resolution::Called),
+            count(Resolution::Inlined),
+            count(Resolution::Missed)
+        );
+    }
+    for (package, resolutions) in &packages {
+        for resolution in resolutions {
+            let site = resolution.site;
+            let functions: Vec<&str> = resolution.functions.iter().map(String::as_str).collect();
+            let location = match site.invocation {
+                Some(line) => format!(
+                    "{}:{} (invoked at line {})",
+                    site.file.display(),
+                    site.line,
+                    line
+                ),
+                None => format!("{}:{}", site.file.display(), site.line),
+            };
+            print!(
+                "{}: {} {} {}",
+                package,
+                resolution.resolution.name(),
+                location,
+                site.targets.join(", ")
+            );
+            if functions.is_empty() || functions == [site.caller.as_str()] {
+                println!();
+            } else {
+                println!(" (in {})", functions.join(", "));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn lsp(args: &Args) -> Result<()> {
+    let packages: Vec<&str> = if args.arguments.is_empty() {
+        packages::all()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect()
+    } else {
+        args.arguments.iter().map(String::as_str).collect()
+    };
+    if let Some(package) = packages.iter().find(|name| packages::find(name).is_none()) {
+        return Err(Error::new(format!(
+            "unknown benchmark package '{}'",
+            package
+        )));
+    }
+    let root = args.root();
+    let index = Index::build(&root)?;
+    let server = args.option("server").unwrap_or(lsp::SERVER);
+    let dot = lsp::call_graph(server, &root, &index, &packages)?;
+    print!("{}", dot::write(&dot, "call hierarchy"));
+    Ok(())
+}
+
+fn mir(args: &Args) -> Result<()> {
+    let (command, names) = match args.arguments.split_first() {
+        Some((command, names)) if command == "graph" || command == "check" => (command, names),
+        _ => return Err(Error::new(format!("malformed mir command\n\n{}", USAGE))),
+    };
+    let names: Vec<&str> = if names.is_empty() {
+        packages::all()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
+    let root = args.root();
+    let target_dir = match args.option("target-dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => root.join("target").join("mir"),
+    };
+    let index = Index::build(&root)?;
+    let layer = args.layer()?;
+    if command == "check" && layer != Layer::Benchmark {
+        return Err(Error::new("mir check checks the benchmark layer only"));
+    }
+    let normalizer = Normalizer::with_layer(&index, layer);
+
+    let mut dot = Dot::default();
+    for name in &names {
+        let package = packages::find(name)
+            .ok_or_else(|| Error::new(format!("unknown benchmark package '{}'", name)))?;
+        let source = mir::dump(&root, package, &target_dir)?;
+        mir::add_calls(&mut dot, name, &source, &index, &normalizer);
+    }
+    if command == "graph" {
+        print!("{}", dot::write(&dot, "mir"));
+        return Ok(());
+    }
+
+    // Static calls that the MIR lacks are missed, and calls between benchmark functions that no
+    // annotation explains are spurious.
+    let truth = Truth::extract(&root, &index)?;
+    let mode = MatchMode::Collapsed;
+    let graph = mode.graph(CallGraph::from_dot(&dot, &normalizer));
+    let statically_dispatched = Filter::parse("dispatch=static")?;
+    // The MIR is not monomorphized, so the calls of a generic function that only its instances
+    // resolve into the benchmark crates, e.g., 'a + b' in operators::lib::sum, are not in it.
+    let instantiated: Vec<(&str, String)> = truth
+        .sites
+        .iter()
+        .filter(|site| site.layer_targets(layer) != site.targets)
+        .flat_map(|site| {
+            site.layer_targets(layer)
+                .into_iter()
+                .map(move |target| (site.caller.as_str(), target))
+        })
+        .collect();
+    let mut differences = 0;
+    for name in &names {
+        let mut missed = score::score(
+            name,
+            &truth,
+            &index,
+            &graph,
+            &statically_dispatched,
+            mode,
+            layer,
+        );
+        missed.missed.retain(|edge| {
+            !instantiated
+                .iter()
+                .any(|(caller, callee)| edge.caller == *caller && edge.callee == *callee)
+        });
+        let spurious = score::score(
+            name,
+            &truth,
+            &index,
+            &graph,
+            &Filter::default(),
+            mode,
+            layer,
+        );
+        for edge in &missed.missed {
+            println!(
+                "{}: not in the MIR {} -> {}",
+                name, edge.caller, edge.callee
+            );
+        }
+        for edge in &spurious.spurious {
+            println!("{}: not annotated {} -> {}", name, edge.caller, edge.callee);
+        }
+        differences += missed.missed.len() + spurious.spurious.len();
+    }
+    if differences > 0 {
+        process::exit(1);
+    }
+    println!("the static calls of the ground truth match the MIR");
+    Ok(())
+}
+
+fn readme(args: &Args) -> Result<()> {
+    let check = match args.arguments.as_slice() {
+        [command] if command == "update" => false,
+        [command] if command == "check" => true,
+        _ => return Err(Error::new(format!("malformed readme command\n\n{}", USAGE))),
+    };
+    let root = args.root();
+    let truth = match args.option("truth") {
+        Some(manifest) => Truth::from_manifest(schema::load(manifest.as_ref())?),
+        None => Truth::extract(&root, &Index::build(&root)?)?,
+    };
+    let path = root.join("src").join("README.md");
+    let current = fs::read_to_string(&path)
+        .map_err(|e| Error::new(format!("cannot read {}: {}", path.display(), e)))?;
+    if !check {
+        fs::write(&path, readme::update(&current, &truth)?)?;
+        return Ok(());
+    }
+    let stale = readme::stale_links(&current, &truth)?;
+    if !stale.is_empty() {
+        for link in stale {
+            println!("{}", link);
+        }
+        println!(
+            "the links of the call examples in src/README.md are out of date, run 'readme update'"
+        );
+        process::exit(1);
+    }
+    println!("the links of the call examples in src/README.md are up to date");
+    Ok(())
+}
+
+fn rustdoc(args: &Args) -> Result<()> {
+    let names: Vec<&str> = if args.arguments.is_empty() {
+        packages::all()
+            .iter()
+            .map(|package| package.name.as_str())
+            .collect()
+    } else {
+        args.arguments.iter().map(String::as_str).collect()
+    };
+    if let Some(package) = names.iter().find(|name| packages::find(name).is_none()) {
+        return Err(Error::new(format!(
+            "unknown benchmark package '{}'",
+            package
+        )));
+    }
+    let root = args.root();
+    let target_dir = match args.option("target-dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => root.join("target").join("rustdoc"),
+    };
+    let index = Index::build(&root)?;
+    let truth = Truth::extract(&root, &index)?;
+
+    // Targets name items of other benchmark crates, so all of them are documented.
+    let mut items = rustdoc::Items::default();
+    for package in packages::all() {
+        items.add(&rustdoc::generate(&root, package, &target_dir)?);
+    }
+    let mut problems = 0;
+    for name in &names {
+        for reference in rustdoc::check(&truth, &index, &items, name) {
+            let problem = if reference.items.is_empty() {
+                "dangling".to_string()
+            } else {
+                format!("ambiguous ({})", reference.items.join(", "))
+            };
+            println!(
+                "{}:{}: {} target {} of '{}'",
+                reference.file.display(),
+                reference.line,
+                problem,
+                reference.target,
+                reference.annotation
+            );
+            problems += 1;
+        }
+    }
+    if problems > 0 {
+        process::exit(1);
+    }
+    println!("the annotated targets name the items documented by rustdoc");
+    Ok(())
+}
+
+fn sweep(args: &Args) -> Result<()> {
+    let configs = Config::parse(
+        args.option("opt-levels").unwrap_or("0,1,2,3,s,z"),
+        args.option("lto").unwrap_or("off,fat"),
+    )?;
+    let root = args.root();
+    let target_dir = match args.option("target-dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => root.join("target").join("sweep"),
+    };
+    // The tool run by the adapter, if any, whose results are recorded per configuration.
+    let tool = match args.option("adapter") {
+        Some(command) => {
+            if args.option("filter").is_some() {
+                return Err(Error::new(
+                    "records hold unfiltered scores, --filter is not supported with --adapter",
+                ));
+            }
+            Some((command, record_key(args, sweep::MODE, Layer::Benchmark)?))
+        }
+        None => None,
+    };
+    let index = Index::build(&root)?;
+    let truth = match args.option("truth") {
+        Some(manifest) => Truth::from_manifest(schema::load(manifest.as_ref())?),
+        None => Truth::extract(&root, &index)?,
+    };
+    let normalizer = Normalizer::new(&index);
+    let filter = args.filter()?;
+    let json = args.json()?;
+
+    let mut results = Vec::new();
+    for config in &configs {
+        eprintln!("building main with {}", config.name());
+        let files = config.build(&root, &target_dir)?;
+        let calls = sweep::read_ir(&files)?;
+        let mut tools = BTreeMap::new();
+
+        let graph = sweep::ir_graph(&calls, &normalizer);
+        let mut scores = BTreeMap::new();
+        for package in packages::all() {
+            let (_, score) = sweep::evaluate(
+                &package.name,
+                &graph,
+                &calls,
+                &truth,
+                &index,
+                &normalizer,
+                &filter,
+            );
+            scores.insert(package.name.clone(), score);
+        }
+        tools.insert(sweep::IR_TOOL.to_string(), scores);
+
+        if let Some((command, key)) = &tool {
+            let adapter = Adapter {
+                command: command.to_string(),
+                timeout: args.timeout()?,
+                env: config.adapter_env(&files),
+            };
+            // The records of a sweep are told apart by the build configuration in their cfg.
+            let mut record = Record::new(RecordKey {
+                cfg: format!("{} {}", key.cfg, config.name()),
+                ..key.clone()
+            });
+            let mut scores = BTreeMap::new();
+            for package in packages::all() {
+                eprintln!(
+                    "running the adapter on {} with {}",
+                    package.name,
+                    config.name()
+                );
+                let (metrics, dot) = adapter.run(&package.name, &root.join(&package.dir))?;
+                if let Some(error) = &metrics.error {
+                    eprintln!("the adapter failed on {}: {}", package.name, error);
+                }
+                record.runs.insert(package.name.clone(), metrics);
+                let dot = match dot {
+                    Some(dot) => dot,
+                    None => continue,
+                };
+                let graph = sweep::MODE.graph(CallGraph::from_dot(&dot, &normalizer));
+                let (package_score, score) = sweep::evaluate(
+                    &package.name,
+                    &graph,
+                    &calls,
+                    &truth,
+                    &index,
+                    &normalizer,
+                    &filter,
+                );
+                record.packages.insert(
+                    package.name.clone(),
+                    PackageResult {
+                        graph,
+                        score: package_score,
+                    },
+                );
+                scores.insert(package.name.clone(), score);
+            }
+            let path = args.store().save(&record)?;
+            eprintln!("stored {}", path.display());
+            tools.insert(key.tool.clone(), scores);
+        }
+        results.push(ConfigScores {
+            config: config.clone(),
+            tools,
+        });
+    }
+    if json {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
+
+    println!(
+        "{:<10} {:<5} {:<12} {:<24} {:>6} {:>9} {:>8} {:>7}",
+        "opt-level", "lto", "tool", "package", "sites", "resolved", "inlined", "missed"
+    );
+    for result in &results {
+        for (tool, packages) in &result.tools {
+            for (package, score) in packages {
+                if score.sites == 0 {
+                    continue;
+                }
+                println!(
+                    "{:<10} {:<5} {:<12} {:<24} {:>6} {:>9} {:>8} {:>7}",
+                    result.config.opt_level,
+                    result.config.lto,
+                    tool,
+                    package,
+                    score.sites,
+                    format!("{:.0}%", score.percentage()),
+                    score.inlined,
+                    score.missed
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn record(args: &Args) -> Result<()> {
+    if args.option("filter").is_some() {
+        return Err(Error::new(
+            "records hold unfiltered scores, --filter is not supported",
+        ));
+    }
+    let key = record_key(args, args.match_mode()?, args.layer()?)?;
+
+    let evaluation = evaluate(args)?;
+    print_scores(&evaluation);
+    let mut record = Record::new(key);
+    record.packages.extend(evaluation.results);
+    record.runs = evaluation.runs;
+    let path = args.store().save(&record)?;
+    println!("stored {}", path.display());
+    Ok(())
+}
+
+// The key of the record of a tool's results, from the options of record and sweep.
+fn record_key(args: &Args, mode: MatchMode, layer: Layer) -> Result<RecordKey> {
+    Ok(RecordKey {
+        tool: args.required("tool")?.to_string(),
+        tool_version: args.required("tool-version")?.to_string(),
+        rustc_version: match args.option("rustc") {
+            Some(version) => version.to_string(),
+            None => output("rustc", &["-V"])?,
+        },
+        commit: match args.option("commit") {
+            Some(commit) => commit.to_string(),
+            None => output(
+                "git",
+                &["-C", &args.root().to_string_lossy(), "rev-parse", "HEAD"],
+            )?,
+        },
+        cfg: args.option("cfg").unwrap_or("default").to_string(),
+        mode,
+        layer,
+    })
+}
+
+fn records(args: &Args) -> Result<()> {
+    for (path, record) in args.store().records()? {
+        let key = &record.key;
+        println!(
+            "{}\n    {} {}, {}, commit {}, cfg {}, {} match, {} layer",
+            path.display(),
+            key.tool,
+            key.tool_version,
+            key.rustc_version,
+            key.commit,
+            key.cfg,
+            key.mode.name(),
+            key.layer.name()
+        );
+    }
+    Ok(())
+}
+
+fn compare(args: &Args) -> Result<()> {
+    let (old, new) = match args.arguments.as_slice() {
+        [old, new] => (Record::load(old.as_ref())?, Record::load(new.as_ref())?),
+        _ => return Err(Error::new("expected two records to compare")),
+    };
+
+    let mut regression = false;
+    for (package, diff) in store::compare(&old, &new)? {
+        regression |= diff.is_regression();
+        if diff.added {
+            println!("{}: added by the newer record", package);
+        }
+        if diff.dropped {
+            match new.runs.get(&package) {
+                Some(run) if run.status != RunStatus::Ok => println!(
+                    "{}: the newer record's run {}: {}",
+                    package,
+                    run.status.name(),
+                    run.error.as_deref().unwrap_or("")
+                ),
+                _ => println!("{}: not scored by the newer record", package),
+            }
+        }
+        for edge in &diff.newly_missed {
+            println!(
+                "{}: newly missed {} -> {}",
+                package, edge.caller, edge.callee
+            );
+        }
+        for edge in &diff.newly_spurious {
+            println!(
+                "{}: newly spurious {} -> {}",
+                package, edge.caller, edge.callee
+            );
+        }
+        for edge in &diff.no_longer_missed {
+            println!(
+                "{}: no longer missed {} -> {}",
+                package, edge.caller, edge.callee
+            );
+        }
+        for edge in &diff.no_longer_spurious {
+            println!(
+                "{}: no longer spurious {} -> {}",
+                package, edge.caller, edge.callee
+            );
+        }
+    }
+
+    if regression {
+        process::exit(1);
+    }
+    println!("no regressions");
+    Ok(())
+}
+
+fn query(args: &Args) -> Result<()> {
+    let root = args.root();
+    let index = Index::build(&root)?;
+    let graph = match args.option("graph").unwrap_or("truth") {
+        "truth" => Truth::extract(&root, &index)?.graph(&index, args.layer()?),
+        file if file.ends_with(".json") => Record::load(file.as_ref())?.graph(),
+        file => {
+            let source = fs::read_to_string(file)
+                .map_err(|e| Error::new(format!("cannot read {}: {}", file, e)))?;
+            let dot = dot::parse(&source).map_err(|e| Error::new(format!("{}: {}", file, e)))?;
+            args.match_mode()?.graph(CallGraph::from_dot(
+                &dot,
+                &Normalizer::with_layer(&index, args.layer()?),
+            ))
+        }
+    };
+    let find = |name: &str| -> Result<&str> {
+        match graph.find(&index, name).as_slice() {
+            [node] => Ok(node),
+            [] => Err(Error::new(format!("no function '{}' in the graph", name))),
+            nodes => Err(Error::new(format!(
+                "'{}' is ambiguous, it matches:\n    {}",
+                name,
+                nodes.join("\n    ")
+            ))),
+        }
+    };
+
+    match args
+        .arguments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["callers", function] => {
+            for caller in graph.callers(find(function)?) {
+                println!("{}", caller);
+            }
+        }
+        ["callees", function] => {
+            for callee in graph.callees(find(function)?) {
+                println!("{}", callee);
+            }
+        }
+        ["reachable", function] => {
+            for node in graph.reachable(find(function)?) {
+                println!("{}", node);
+            }
+        }
+        ["path", from, to] => match graph.shortest_path(find(from)?, find(to)?) {
+            Some(path) => println!("{}", path.join("\n    -> ")),
+            None => {
+                println!("no path");
+                process::exit(1);
+            }
+        },
+        ["scc", rest @ ..] if rest.len() <= 1 => {
+            let node = rest.first().map(|function| find(function)).transpose()?;
+            for component in graph.recursive_components() {
+                if node.is_none_or(|node| component.contains(&node)) {
+                    println!("{}", component.join("\n    "));
+                }
+            }
+        }
+        _ => return Err(Error::new(format!("malformed query\n\n{}", USAGE))),
+    }
+    Ok(())
+}
+
+fn explore(args: &Args) -> Result<()> {
+    let root = args.root();
+    let index = Index::build(&root)?;
+    let truth = match args.option("truth") {
+        Some(manifest) => Truth::from_manifest(schema::load(manifest.as_ref())?),
+        None => Truth::extract(&root, &index)?,
+    };
+    let tools: Vec<explorer::Tool> = args
+        .store()
+        .records()?
+        .into_iter()
+        .map(|(_, record)| explorer::Tool::new(record))
+        .collect();
+    let output = match args.option("output") {
+        Some(dir) => PathBuf::from(dir),
+        None => root.join("target").join("explorer"),
+    };
+    explorer::generate(&root, &index, &truth, &tools, &output)?;
+    println!("wrote {}", output.join("index.html").display());
+    Ok(())
+}
+
+fn schema(args: &Args) -> Result<()> {
+    let root = args.root();
+    let schemas = [
+        (
+            schema::path::<TruthManifest>(),
+            schema::schema::<TruthManifest>(),
+        ),
+        (
+            schema::path::<ScoreReport>(),
+            schema::schema::<ScoreReport>(),
+        ),
+    ];
+    match args
+        .arguments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["truth"] => print!("{}", schema::to_json(&schemas[0].1)),
+        ["report"] => print!("{}", schema::to_json(&schemas[1].1)),
+        ["write"] => {
+            for (path, schema) in &schemas {
+                let path = root.join(path);
+                fs::create_dir_all(path.parent().unwrap())?;
+                fs::write(&path, schema::to_json(schema))?;
+                println!("wrote {}", path.display());
+            }
+        }
+        ["check"] => {
+            let mut stale = false;
+            for (path, schema) in &schemas {
+                let published = fs::read_to_string(root.join(path)).unwrap_or_default();
+                if published != schema::to_json(schema) {
+                    println!(
+                        "{} is out of date, run 'harness schema write'",
+                        path.display()
+                    );
+                    stale = true;
+                }
+            }
+            if stale {
+                process::exit(1);
+            }
+        }
+        _ => return Err(Error::new(format!("malformed schema command\n\n{}", USAGE))),
+    }
+    Ok(())
+}
+
+fn validate(args: &Args) -> Result<()> {
+    match args
+        .arguments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["truth", file] => schema::load::<TruthManifest>(file.as_ref()).map(drop)?,
+        ["report", file] => schema::load::<ScoreReport>(file.as_ref()).map(drop)?,
+        _ => {
+            return Err(Error::new(
+                "expected a document ('truth' or 'report') and a file",
+            ))
+        }
+    }
+    println!("valid");
+    Ok(())
+}
+
+fn migrate(args: &Args) -> Result<()> {
+    let json = match args
+        .arguments
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        ["truth", file] => schema::to_json(&schema::load::<TruthManifest>(file.as_ref())?),
+        ["report", file] => schema::to_json(&schema::load::<ScoreReport>(file.as_ref())?),
+        _ => {
+            return Err(Error::new(
+                "expected a document ('truth' or 'report') and a file",
+            ))
+        }
+    };
+    print!("{}", json);
+    Ok(())
+}
+
+fn output(program: &str, args: &[&str]) -> Result<String> {
+    let output = Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| Error::new(format!("cannot run {}: {}", program, e)))?;
+    if !output.status.success() {
+        return Err(Error::new(format!("{} {} failed", program, args.join(" "))));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
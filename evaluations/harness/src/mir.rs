@@ -0,0 +1,522 @@
+// Call graphs from the MIR that rustc prints for a crate (-Zunpretty=mir).
+//
+// Each function of the printed MIR is a header followed by basic blocks whose terminators include
+// the calls of the function:
+//
+//     fn bench::run() -> () {
+//         ...
+//         _2 = lib::indirection(copy _3, move _4) -> [return: bb1, unwind continue];
+//         _0 = <dyn traits::lib::FooTrait as traits::lib::FooTrait>::method(copy _1) -> [...];
+//         _0 = copy _2(copy _1) -> [return: bb1, unwind continue];
+//
+// The callee of a call is known statically unless it is a method of a trait object (a virtual
+// call) or an operand, i.e., a function pointer (an indirect call). Calls on generic receivers,
+// <T as Trait>::method, are calls of the trait's method. Virtual and indirect calls are kept in the
+// graph as edges to nodes that stand for no function, so that they are shown as unresolved and
+// ignored by scores.
+//
+// Paths of the printed crate are relative to it (lib::indirection) and implementations are named
+// by their span (<impl at src/base.rs:11:1: 11:31>), so both are looked up in the index.
+
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::error::Result;
+use crate::index::FunctionKind;
+use crate::index::Index;
+use crate::names::Normalizer;
+use crate::packages;
+use crate::packages::Package;
+
+// Prints the MIR of a package. -Z flags are enabled on any toolchain with RUSTC_BOOTSTRAP.
+// Packages with features are printed with the default features and with all of them, one after
+// the other, so that the MIR covers both variants of conditionally compiled functions.
+pub fn dump(root: &Path, package: &Package, target_dir: &Path) -> Result<String> {
+    let dir = root.join(&package.dir);
+    let target = package.target_args();
+    let manifest = fs::read_to_string(dir.join("Cargo.toml"))?;
+    let mut feature_sets = vec![None];
+    if manifest.lines().any(|line| line.trim() == "[features]") {
+        feature_sets.push(Some("--all-features"));
+    }
+
+    let mut mir = String::new();
+    for features in feature_sets {
+        let output = Command::new("cargo")
+            .arg("rustc")
+            .arg("--quiet")
+            .args(&target)
+            .args(features)
+            .arg("--target-dir")
+            .arg(target_dir)
+            .args(["--", "-Zunpretty=mir", "-Ztrim-diagnostic-paths=false"])
+            .current_dir(&dir)
+            .env("RUSTC_BOOTSTRAP", "1")
+            .output()
+            .map_err(|e| Error::new(format!("cannot run cargo: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "printing the MIR of {} failed:\n{}",
+                package.name,
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+        mir.push_str(&String::from_utf8_lossy(&output.stdout));
+    }
+    Ok(mir)
+}
+
+// Adds the calls of the printed MIR of a package to a graph whose nodes are canonical names.
+pub fn add_calls(dot: &mut Dot, package: &str, mir: &str, index: &Index, normalizer: &Normalizer) {
+    let mut caller: Option<String> = None;
+    for line in mir.lines() {
+        if let Some(header) = line.strip_prefix("fn ") {
+            let path = &header[..header_end(header)];
+            caller = function_name(package, path, index, normalizer);
+            if let Some(caller) = &caller {
+                dot.nodes.insert(caller.clone(), caller.clone());
+            }
+            continue;
+        }
+        if !line.is_empty() && !line.starts_with(' ') {
+            // Constants, promoted values and allocations hold no calls of interest.
+            caller = None;
+            continue;
+        }
+        let (caller, callee) = match (&caller, call(line.trim())) {
+            (Some(caller), Some(callee)) => (caller, callee),
+            _ => continue,
+        };
+
+        let unresolved = if is_operand(callee) {
+            Some("indirect")
+        } else if callee.starts_with("<dyn ") {
+            Some("virtual")
+        } else {
+            None
+        };
+        match unresolved {
+            // The node is named after the call, its label is no function's name.
+            Some(kind) => {
+                let id = format!("unresolved {} call: {}", kind, callee);
+                let label = format!("unresolved {} call", kind);
+                dot.nodes.insert(id.clone(), label);
+                add_edge(dot, caller, id);
+            }
+            None => {
+                if let Some(callee) = function_name(package, callee, index, normalizer) {
+                    dot.nodes.insert(callee.clone(), callee.clone());
+                    add_edge(dot, caller, callee);
+                }
+            }
+        }
+    }
+}
+
+// Functions that are printed more than once, in each feature set, call the same functions.
+fn add_edge(dot: &mut Dot, caller: &str, callee: String) {
+    let edge = (caller.to_string(), callee);
+    if !dot.edges.contains(&edge) {
+        dot.edges.push(edge);
+    }
+}
+
+// The end of the path of a function header, 'lib::f(_1: i32) -> i32 {', at the first parenthesis
+// outside of angle brackets, as implementations are named by spans like <impl at src/lib.rs:3:1>.
+fn header_end(header: &str) -> usize {
+    let mut depth = 0;
+    for (pos, c) in header.char_indices() {
+        match c {
+            '<' => depth += 1,
+            '>' if depth > 0 => depth -= 1,
+            '(' if depth == 0 => return pos,
+            _ => {}
+        }
+    }
+    header.len()
+}
+
+// The callee of a call terminator, '_2 = lib::f(copy _3) -> [return: bb1, unwind continue];',
+// which is told apart from other terminators and statements by its targets.
+fn call(statement: &str) -> Option<&str> {
+    let statement = statement.strip_suffix(';')?;
+    let (call, targets) = statement.rsplit_once(" -> ")?;
+    let is_call = targets.starts_with("[return:")
+        || targets.starts_with("unwind ")
+        || (targets.starts_with("bb") && targets[2..].chars().all(|c| c.is_ascii_digit()));
+    if !is_call {
+        return None;
+    }
+    let call = match call.split_once(" = ") {
+        Some((place, call)) if !place.contains('(') => call,
+        _ => call,
+    };
+
+    // The arguments are the last parenthesized group, the callee is what precedes it.
+    let call = call.strip_suffix(')')?;
+    let mut depth = 1;
+    for (pos, c) in call.char_indices().rev() {
+        match c {
+            ')' => depth += 1,
+            '(' => {
+                depth -= 1;
+                if depth == 0 {
+                    let callee = call[..pos].trim();
+                    // Drop terminators look like calls of 'drop'.
+                    return (callee != "drop" && !callee.is_empty()).then_some(callee);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// Operands are locals, possibly copied or moved: _3, copy _3, move _3.
+fn is_operand(callee: &str) -> bool {
+    let local = callee
+        .trim_start_matches("copy ")
+        .trim_start_matches("move ");
+    local.starts_with('_') && local[1..].chars().all(|c| c.is_ascii_digit())
+}
+
+// The canonical name of a function printed in the MIR of a package.
+fn function_name(
+    package: &str,
+    path: &str,
+    index: &Index,
+    normalizer: &Normalizer,
+) -> Option<String> {
+    let path = qualify(package, path, index);
+    let path = resolve_impls(package, &path, index);
+    let path = trait_method_of_parameter(&path).unwrap_or(path);
+    let path = closure_spans(package, &path);
+    let path = closure_call(&path).unwrap_or(path);
+    normalizer.canonical(&path)
+}
+
+// Prefixes the paths of the package's own items with the package's name: base::One becomes
+// generics::base::One, and main, a function of the crate root, main::main. Paths of other crates
+// start with the name of their crate.
+fn qualify(package: &str, text: &str, index: &Index) -> String {
+    if index
+        .function_by_def_path(&format!("{}::{}", package, text))
+        .is_some()
+    {
+        return format!("{}::{}", package, text);
+    }
+    let modules: Vec<&str> = index
+        .functions
+        .iter()
+        .filter(|function| function.package == package)
+        .filter_map(|function| function.def_path.split("::").nth(1))
+        .collect();
+
+    let mut output = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| c.is_alphabetic() || c == '_') {
+        let end = rest[start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(rest.len(), |end| start + end);
+        let ident = &rest[start..end];
+        let starts_path = !output.ends_with("::") && !rest[..start].ends_with("::");
+        output.push_str(&rest[..start]);
+        if starts_path
+            && rest[end..].starts_with("::")
+            && modules.contains(&ident)
+            && !packages::is_benchmark_crate(ident)
+        {
+            output.push_str(package);
+            output.push_str("::");
+        }
+        output.push_str(ident);
+        rest = &rest[end..];
+    }
+    output.push_str(rest);
+    output
+}
+
+// Replaces the implementations named by their span, e.g.,
+// generics::base::<impl at src/base.rs:11:1: 11:31>::method, by the name of the method in the
+// index. The span is the implementation's, or the derive attribute's for derived implementations,
+// so the method is the first one of that name that starts at or after it.
+fn resolve_impls(package: &str, path: &str, index: &Index) -> String {
+    let start = match path.find("<impl at ") {
+        Some(start) => start,
+        None => return path.to_string(),
+    };
+    let end = match path[start..].find(">::") {
+        Some(end) => start + end,
+        None => return path.to_string(),
+    };
+    let span = &path[start + "<impl at ".len()..end];
+    let rest = &path[end + ">::".len()..];
+    let (method, after) = match rest.find("::") {
+        Some(pos) => (&rest[..pos], &rest[pos..]),
+        None => (rest, ""),
+    };
+    let mut location = span.split(':');
+    let (file, line) = match (
+        location.next(),
+        location.next().and_then(|l| l.parse().ok()),
+    ) {
+        (Some(file), Some(line)) => (file, line),
+        _ => return path.to_string(),
+    };
+    let suffix = format!("::{}", method);
+    let function = index
+        .functions
+        .iter()
+        .filter(|function| {
+            function.package == package
+                && matches!(
+                    function.kind,
+                    FunctionKind::Inherent | FunctionKind::TraitImpl
+                )
+                && function.file.ends_with(file)
+                && function.start >= line
+                && function.name.ends_with(&suffix)
+        })
+        .min_by_key(|function| function.start);
+    match function {
+        Some(function) => format!("{}{}", function.name, after),
+        None => path.to_string(),
+    }
+}
+
+// Calls on generic receivers, <T as traits::lib::FooTrait>::method or
+// <impl FooTrait as traits::lib::FooTrait>::method, call the trait's method.
+fn trait_method_of_parameter(path: &str) -> Option<String> {
+    let inner = path.strip_prefix('<')?;
+    let (self_ty, rest) = inner.split_once(" as ")?;
+    let is_parameter = self_ty.starts_with("impl ")
+        || (self_ty.chars().next()?.is_uppercase()
+            && self_ty.chars().all(|c| c.is_alphanumeric() || c == '_'));
+    if !is_parameter {
+        return None;
+    }
+    let (trait_, method) = rest.rsplit_once(">::")?;
+    let trait_ = match trait_.find('<') {
+        Some(pos) => &trait_[..pos],
+        None => trait_,
+    };
+    Some(format!("{}::{}", trait_, method))
+}
+
+// MIR names closures by their span relative to the package, {closure@src/lib.rs:10:17: 10:20};
+// the index knows them by their start, {closure@src/closures/src/lib.rs:10:17}.
+fn closure_spans(package: &str, path: &str) -> String {
+    let dir = packages::find(package).map_or("", |package| package.dir.as_str());
+    let mut output = String::new();
+    let mut rest = path;
+    while let Some(start) = rest.find("{closure@") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let span = &rest[start + "{closure@".len()..end];
+        output.push_str(&rest[..start]);
+        output.push_str(&format!(
+            "{{closure@{}/{}}}",
+            dir,
+            span.split(": ").next().unwrap_or(span)
+        ));
+        rest = &rest[end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
+
+// Calls of closures, <{closure@src/lib.rs:10:17} as std::ops::Fn<(i32,)>>::call, call the closure.
+fn closure_call(path: &str) -> Option<String> {
+    let inner = path.strip_prefix("<{closure@")?;
+    let end = inner.find('}')?;
+    Some(format!("{{closure@{}}}", &inner[..end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_the_callees_of_call_terminators() {
+        let callees = [
+            (
+                "_3 = lib::dynamic(move _4) -> [return: bb1, unwind continue];",
+                Some("lib::dynamic"),
+            ),
+            (
+                "_12 = lib::dynamic_generic::<u32>(copy _13) -> [return: bb4, unwind: bb27];",
+                Some("lib::dynamic_generic::<u32>"),
+            ),
+            (
+                "_0 = <dyn traits::lib::FooTrait as traits::lib::FooTrait>::method(copy _1) \
+                 -> [return: bb1, unwind continue];",
+                Some("<dyn traits::lib::FooTrait as traits::lib::FooTrait>::method"),
+            ),
+            (
+                "_0 = copy _2(copy _1) -> [return: bb1, unwind continue];",
+                Some("copy _2"),
+            ),
+            (
+                "_34 = std::fmt::Arguments::<'_>::new::<62, 1>(move _48, copy _49) -> bb22;",
+                Some("std::fmt::Arguments::<'_>::new::<62, 1>"),
+            ),
+            (
+                "_4 = std::boxed::Box::<{closure@src/closures/src/lib.rs:80:22: 80:33}>::new(\
+                 const ZeroSized: {closure@src/closures/src/lib.rs:80:22: 80:33}) \
+                 -> [return: bb2, unwind continue];",
+                Some("std::boxed::Box::<{closure@src/closures/src/lib.rs:80:22: 80:33}>::new"),
+            ),
+            (
+                "core::panicking::panic(const \"explicit panic\") -> unwind continue;",
+                Some("core::panicking::panic"),
+            ),
+            // Drops, jumps and branches are no calls.
+            ("drop(_15) -> [return: bb26, unwind continue];", None),
+            ("goto -> bb10;", None),
+            ("switchInt(move _5) -> [0: bb2, otherwise: bb1];", None),
+            ("_2 = lib::f(copy _3)", None),
+        ];
+        for (statement, callee) in callees {
+            assert_eq!(call(statement), callee, "{}", statement);
+        }
+    }
+
+    #[test]
+    fn ends_headers_at_the_parameters() {
+        let header =
+            "base::<impl at src/generics/src/base.rs:11:1: 11:31>::method(_1: &One) -> i32 {";
+        assert_eq!(
+            &header[..header_end(header)],
+            "base::<impl at src/generics/src/base.rs:11:1: 11:31>::method"
+        );
+    }
+
+    #[test]
+    fn tells_operands_from_functions() {
+        assert!(is_operand("copy _2"));
+        assert!(is_operand("move _3"));
+        assert!(is_operand("_12"));
+        assert!(!is_operand("lib::f"));
+        assert!(!is_operand("_f"));
+    }
+
+    #[test]
+    fn calls_the_trait_method_on_generic_receivers() {
+        assert_eq!(
+            trait_method_of_parameter("<T as traits::lib::GenericFooTrait<i32>>::method").unwrap(),
+            "traits::lib::GenericFooTrait::method"
+        );
+        assert_eq!(
+            trait_method_of_parameter(
+                "<impl ForeignBoundTrait as traits::lib::bounds::BoundTrait>::method"
+            )
+            .unwrap(),
+            "traits::lib::bounds::BoundTrait::method"
+        );
+        assert_eq!(
+            trait_method_of_parameter("<structs::lib::fat::Fat as traits::lib::FooTrait>::method"),
+            None
+        );
+    }
+
+    #[test]
+    fn names_closures_by_their_start() {
+        assert_eq!(
+            closure_spans(
+                "closures",
+                "lib::boxed::<{closure@src/lib.rs:80:22: 80:33}>"
+            ),
+            "lib::boxed::<{closure@src/closures/src/lib.rs:80:22}>"
+        );
+        assert_eq!(
+            closure_call("<{closure@src/closures/src/lib.rs:69:9} as std::ops::Fn<(i32,)>>::call")
+                .unwrap(),
+            "{closure@src/closures/src/lib.rs:69:9}"
+        );
+    }
+
+    // The graph of the MIR of a package, as (caller, callee label) pairs.
+    fn edges(package: &str, mir: &str) -> Vec<(String, String)> {
+        let root = packages::default_root();
+        packages::load(&root).unwrap();
+        let index = Index::build(&root).unwrap();
+        let mut dot = Dot::default();
+        add_calls(&mut dot, package, mir, &index, &Normalizer::new(&index));
+        dot.edges
+            .iter()
+            .map(|(caller, callee)| (caller.clone(), dot.nodes[callee].clone()))
+            .collect()
+    }
+
+    #[test]
+    fn adds_static_calls_and_marks_virtual_and_indirect_calls_unresolved() {
+        let mir = r#"
+fn lib::indirection(_1: &structs::lib::fat::Fat, _2: for<'a> fn(&'a structs::lib::fat::Fat) -> u32) -> u32 {
+    bb0: {
+        _0 = copy _2(copy _1) -> [return: bb1, unwind continue];
+    }
+}
+
+fn bench::helpers::m1(_1: &dyn traits::lib::FooTrait) -> u32 {
+    bb0: {
+        _0 = <dyn traits::lib::FooTrait as traits::lib::FooTrait>::method(copy _1) -> [return: bb1, unwind continue];
+    }
+}
+
+const bench::run::promoted[0]: &u32 = {
+    bb0: {
+        _0 = lib::indirection(copy _1, copy _2) -> [return: bb1, unwind continue];
+    }
+}
+
+fn bench::run() -> std::vec::Vec<(&str, i64)> {
+    bb0: {
+        _3 = lib::indirection(move _4, move _5) -> [return: bb1, unwind continue];
+        _6 = lib::indirection(move _4, move _5) -> [return: bb2, unwind continue];
+        drop(_15) -> [return: bb3, unwind continue];
+    }
+}
+"#;
+        // The call of the promoted constant is not kept, and the repeated call is one edge.
+        assert_eq!(
+            edges("function_pointers", mir),
+            [
+                (
+                    "function_pointers::lib::indirection".to_string(),
+                    "unresolved indirect call".to_string()
+                ),
+                (
+                    "function_pointers::bench::helpers::m1".to_string(),
+                    "unresolved virtual call".to_string()
+                ),
+                (
+                    "function_pointers::bench::run".to_string(),
+                    "function_pointers::lib::indirection".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn resolves_implementations_named_by_their_span() {
+        let mir = r#"
+fn base::<impl at src/generics/src/base.rs:31:1: 31:32>::method_wrapper(_1: &base::Wrapper<T>) -> i32 {
+    bb0: {
+        _0 = <T as traits::lib::bounds::BoundTrait>::method(move _2) -> [return: bb1, unwind: bb3];
+    }
+}
+"#;
+        assert_eq!(
+            edges("generics", mir),
+            [(
+                "generics::base::Wrapper::method_wrapper".to_string(),
+                "traits::lib::bounds::BoundTrait::method".to_string()
+            )]
+        );
+    }
+}
@@ -0,0 +1,573 @@
+// Translation of the function names reported by call-graph generators to the canonical names used
+// by the index (see index.rs).
+//
+// Functions of the standard library are only kept with the std layer (see score.rs). They are named
+// after the crate that defines them rather than the std facade that re-exports them, e.g.,
+// std::vec::Vec::push and alloc::vec::Vec<T,A>::push are both alloc::vec::Vec::push, and are
+// compared by their definitions.
+
+use crate::index::strip_generics;
+use crate::index::Index;
+use crate::packages::is_benchmark_crate;
+use crate::score::Layer;
+
+// The crates of the standard library.
+const STD_CRATES: &[&str] = &["std", "core", "alloc"];
+
+// The modules of std that re-export the items of core and alloc, and the modules of std whose
+// items are named differently by their definitions.
+const STD_FACADE: &[(&str, &str)] = &[
+    ("std::boxed::", "alloc::boxed::"),
+    ("std::clone::", "core::clone::"),
+    ("std::cmp::", "core::cmp::"),
+    ("std::convert::", "core::convert::"),
+    ("std::fmt::", "core::fmt::"),
+    ("std::future::", "core::future::"),
+    ("std::io::_print", "std::io::stdio::_print"),
+    ("std::iter::", "core::iter::"),
+    ("std::mem::", "core::mem::"),
+    ("std::ops::", "core::ops::"),
+    ("std::option::", "core::option::"),
+    ("std::rc::", "alloc::rc::"),
+    ("std::result::", "core::result::"),
+    ("std::string::", "alloc::string::"),
+    ("std::task::Waker", "core::task::wake::Waker"),
+    ("std::vec::", "alloc::vec::"),
+];
+
+pub struct Normalizer<'a> {
+    index: &'a Index,
+    layer: Layer,
+}
+
+impl<'a> Normalizer<'a> {
+    pub fn new(index: &'a Index) -> Self {
+        Normalizer::with_layer(index, Layer::default())
+    }
+
+    pub fn with_layer(index: &'a Index, layer: Layer) -> Self {
+        Normalizer { index, layer }
+    }
+
+    // Returns the canonical name of the function a tool's node refers to, or None if the function
+    // is not defined in one of the benchmark crates, e.g., it belongs to the standard library and
+    // the standard library is not part of the layer. Instances of generic functions keep their
+    // generic arguments (see instance_path).
+    pub fn canonical(&self, name: &str) -> Option<String> {
+        let name = name.trim();
+        let demangled;
+        let name = match rustc_demangle::try_demangle(name) {
+            // The alternate format omits the hash suffix of legacy symbol names.
+            Ok(symbol) => {
+                demangled = format!("{:#}", symbol);
+                demangled.as_str()
+            }
+            Err(_) => name,
+        };
+
+        let segments = split_path(name);
+        if let Some(pos) = segments.iter().position(|s| is_closure(s)) {
+            return self.closure(&segments[..pos], &segments[pos..]);
+        }
+
+        // MIRAI names implementations after their DefPath, e.g., structs::lib::fat::{impl#1}.
+        if name.contains("{impl#") {
+            if let Some(function) = self.index.function_by_def_path(name) {
+                return Some(function.name.clone());
+            }
+        }
+
+        // Unqualified symbols, e.g., the C 'main' function that rustc generates for binaries, do
+        // not belong to a crate, even if they happen to be named like one.
+        let canonical = instance_path(name);
+        if !canonical.contains("::") {
+            None
+        } else if is_benchmark_path(&canonical) {
+            Some(self.std_trait_impl(&canonical).unwrap_or(canonical))
+        } else if self.layer == Layer::Std && is_std_path(&canonical) {
+            Some(std_path(name))
+        } else {
+            None
+        }
+    }
+}
+
+impl Normalizer<'_> {
+    // Implementations of the traits of the standard library are named by the index after the path
+    // of the trait in the sources, e.g., <async_await::lib::YieldNow as std::future::Future>::poll,
+    // and by tools after its definition, e.g., core::future::future::Future. Returns the name of
+    // the index's implementation of the same trait for the self type, if it differs.
+    fn std_trait_impl(&self, name: &str) -> Option<String> {
+        if self.index.function(name).is_some() {
+            return None;
+        }
+        let segments = split_path(name);
+        let (self_ty, trait_) = qualified_parts(segments.first()?)?;
+        let method = segments.get(1)?;
+        let traits: Vec<String> = self
+            .index
+            .functions
+            .iter()
+            .filter_map(|function| {
+                let segments = split_path(&function.name);
+                let (ty, index_trait) = qualified_parts(segments.first()?)?;
+                let matches = segments.get(1) == Some(method)
+                    && strip_generics(ty) == strip_generics(self_ty)
+                    && is_same_std_trait(&strip_generics(index_trait), &strip_generics(trait_));
+                Some(index_trait.to_string()).filter(|_| matches)
+            })
+            .collect();
+        // A type may implement the trait for several generic arguments, e.g., Add<Thin> and
+        // Add<&Fat>, which are told apart by their arguments.
+        let arguments = |path: &str| path[strip_generics(path).len()..].to_string();
+        let index_trait = match traits.as_slice() {
+            [index_trait] => index_trait,
+            _ => traits
+                .iter()
+                .find(|index_trait| arguments(index_trait) == arguments(trait_))?,
+        };
+        let mut renamed = vec![format!("<{} as {}>", self_ty, index_trait)];
+        renamed.extend(segments[1..].iter().cloned());
+        Some(renamed.join("::"))
+    }
+
+    // Closures are named after the function that defines them, e.g., MIRAI and v0 symbol names
+    // use closures::lib::apply::{closure#0}, like the index does. Legacy symbol names do not number
+    // closures, closures::lib::apply::{{closure}}, which only tells a closure apart if it is the
+    // only one of its parent. rust-analyzer names closures by their span,
+    // {closure@src/closures/src/lib.rs:12:17}, which is looked up in the index.
+    fn closure(&self, parent: &[String], segments: &[String]) -> Option<String> {
+        let mut name = if parent.is_empty() {
+            None
+        } else {
+            Some(self.canonical(&parent.join("::"))?)
+        };
+        for segment in segments {
+            if let Some(span) = segment
+                .strip_prefix("{closure@")
+                .and_then(|s| s.strip_suffix('}'))
+            {
+                match self.closure_at(span) {
+                    Some(closure) => name = Some(closure),
+                    None => name = Some(format!("{}::{}", name?, segment)),
+                }
+                continue;
+            }
+            let parent = name?;
+            let segment = if segment == "{{closure}}" {
+                let mut closures = self.index.closures_of(&parent);
+                match (closures.next(), closures.next()) {
+                    (Some(closure), None) => {
+                        name = Some(closure.name.clone());
+                        continue;
+                    }
+                    _ => "{closure}",
+                }
+            } else {
+                segment.as_str()
+            };
+            name = Some(format!("{}::{}", parent, segment));
+        }
+        name
+    }
+
+    // The closure defined at <file>:<line>[:<column>], where the file may be given relative to
+    // any directory.
+    fn closure_at(&self, span: &str) -> Option<String> {
+        let (rest, last) = span.rsplit_once(':')?;
+        let last: usize = last.parse().ok()?;
+        let line = rest
+            .rsplit_once(':')
+            .and_then(|(file, line)| Some((file, line.parse::<usize>().ok()?)));
+        let (file, line, column) = match line {
+            Some((file, line)) => (file, line, Some(last)),
+            None => (rest, last, None),
+        };
+        self.index
+            .closures
+            .iter()
+            .find(|closure| {
+                closure.file.ends_with(file)
+                    && closure.start == line
+                    && column.is_none_or(|column| column == closure.column)
+            })
+            .map(|closure| closure.name.clone())
+    }
+}
+
+fn is_closure(segment: &str) -> bool {
+    segment.starts_with("{closure") || segment == "{{closure}}"
+}
+
+// Brings a demangled or printed path to the canonical form:
+//
+// - generics::bench::<impl traits::lib::GenericFooTrait<i32> for generics::base::Two>::method
+//   becomes <generics::base::Two as traits::lib::GenericFooTrait<i32>>::method
+// - generics::base::Wrapper<T>::new becomes generics::base::Wrapper::new
+// - generics::lib::monomorphized::<structs::lib::One> becomes generics::lib::monomorphized
+//
+// Instances of generic functions are named after their definition; see instance_path.
+pub fn canonical_path(path: &str) -> String {
+    let instance = instance_path(path);
+    let mut segments = split_path(&instance);
+    if segments.len() > 1 && segments.last().is_some_and(|s| s.starts_with('<')) {
+        segments.pop();
+    }
+    segments.join("::")
+}
+
+// Like canonical_path, but keeps the generic arguments of instances, as printed by v0 symbol
+// names, as a trailing segment. The arguments of the self type come first:
+//
+// - generics::lib::monomorphized::<structs::lib::One> is kept as is
+// - <generics::base::Wrapper<structs::lib::One>>::new becomes
+//   generics::base::Wrapper::new::<structs::lib::One>
+//
+// The generic parameters printed by legacy symbol names, e.g., generics::base::Wrapper<T>::new, do
+// not identify an instance and are dropped.
+pub fn instance_path(path: &str) -> String {
+    let mut segments: Vec<String> = Vec::new();
+    let mut args: Vec<String> = Vec::new();
+    for segment in split_path(path) {
+        if let Some(inner) = segment
+            .strip_prefix("<impl ")
+            .and_then(|s| s.strip_suffix('>'))
+        {
+            segments.clear();
+            args.clear();
+            match split_top_level(inner, " for ") {
+                Some((trait_, self_ty)) => segments.push(format!("<{} as {}>", self_ty, trait_)),
+                None => segments.push(strip_generics(inner)),
+            }
+        } else if segment.starts_with('<') {
+            let inner = &segment[1..segment.len() - 1];
+            if split_top_level(inner, " as ").is_some() {
+                segments.clear();
+                args.clear();
+                segments.push(segment);
+            } else if segments.is_empty() {
+                // The self type of an inherent method, e.g., <Wrapper<One>>::new.
+                segments.push(strip_generics(inner));
+                if let (Some(start), Some(end)) = (inner.find('<'), inner.rfind('>')) {
+                    args.extend(split_arguments(&inner[start + 1..end]));
+                }
+            } else {
+                // The generic arguments of an instance.
+                args.extend(split_arguments(inner));
+            }
+        } else {
+            segments.push(strip_generics(&segment));
+        }
+    }
+    if !args.is_empty() {
+        segments.push(format!("<{}>", args.join(", ")));
+    }
+    segments.join("::")
+}
+
+// Splits a list of generic arguments at its top level commas.
+fn split_arguments(mut text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    while let Some((arg, rest)) = split_top_level(text, ",") {
+        args.push(arg.trim().to_string());
+        text = rest;
+    }
+    if !text.trim().is_empty() {
+        args.push(text.trim().to_string());
+    }
+    args
+}
+
+// Splits a path at its top level '::' separators, e.g., <A as B>::method is split into
+// ["<A as B>", "method"] and a::{impl Foo for Bar}::method into ["a", "{impl Foo for Bar}",
+// "method"].
+pub fn split_path(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0;
+    let mut chars = path.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '<' | '{' | '(' | '[' => depth += 1,
+            '>' if current.ends_with('-') => {}
+            '>' | '}' | ')' | ']' => depth -= 1,
+            ':' if depth == 0 && chars.peek() == Some(&':') => {
+                chars.next();
+                segments.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    if !current.is_empty() {
+        segments.push(current);
+    }
+    segments
+}
+
+// The self type and the trait of a qualified path segment, e.g., <A as B>.
+fn qualified_parts(segment: &str) -> Option<(&str, &str)> {
+    let inner = segment.strip_prefix('<')?.strip_suffix('>')?;
+    split_top_level(inner, " as ")
+}
+
+// Whether two paths, without generic arguments, name the same trait of the standard library, one
+// by a re-export and the other by its definition, e.g., std::ops::Add and core::ops::arith::Add:
+// the traits have the same name and one's module, once the std facade is resolved, contains the
+// other's.
+pub fn is_same_std_trait(a: &str, b: &str) -> bool {
+    if a == b {
+        return true;
+    }
+    if !is_std_path(a) || !is_std_path(b) {
+        return false;
+    }
+    let (a, b) = (std_path(a), std_path(b));
+    match (a.rsplit_once("::"), b.rsplit_once("::")) {
+        (Some((a_module, a_name)), Some((b_module, b_name))) => {
+            a_name == b_name && (a_module.starts_with(b_module) || b_module.starts_with(a_module))
+        }
+        _ => false,
+    }
+}
+
+// Splits 'text' at the first top level occurrence of 'separator'.
+pub fn split_top_level<'t>(text: &'t str, separator: &str) -> Option<(&'t str, &'t str)> {
+    let mut depth = 0;
+    for (pos, c) in text.char_indices() {
+        match c {
+            '<' | '{' | '(' | '[' => depth += 1,
+            '>' if text[..pos].ends_with('-') => {}
+            '>' | '}' | ')' | ']' => depth -= 1,
+            _ if depth == 0 && text[pos..].starts_with(separator) => {
+                return Some((&text[..pos], &text[pos + separator.len()..]));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+// The crate a canonical path belongs to. Methods of trait implementations belong to the crate of
+// their self type or their trait, thus both are returned.
+pub fn crates_of(path: &str) -> Vec<&str> {
+    match path.strip_prefix('<') {
+        Some(qualified) => {
+            let end = qualified.rfind(">::").unwrap_or(qualified.len());
+            match split_top_level(&qualified[..end], " as ") {
+                Some((self_ty, trait_)) => vec![crate_name(self_ty), crate_name(trait_)],
+                None => vec![crate_name(qualified)],
+            }
+        }
+        None => vec![crate_name(path)],
+    }
+}
+
+fn crate_name(path: &str) -> &str {
+    let path = path.trim_start_matches(['&', '*']);
+    let path = path
+        .trim_start_matches("mut ")
+        .trim_start_matches("const ")
+        .trim_start_matches("dyn ");
+    let end = path
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(path.len());
+    &path[..end]
+}
+
+pub fn is_benchmark_path(path: &str) -> bool {
+    crates_of(path).into_iter().any(is_benchmark_crate)
+}
+
+pub fn is_std_path(path: &str) -> bool {
+    crates_of(path)
+        .into_iter()
+        .any(|name| STD_CRATES.contains(&name))
+}
+
+// The canonical name of a function on the std layer, which names the functions of the standard
+// library after their definitions.
+pub fn std_layer_path(path: &str) -> String {
+    if is_std_path(path) && !is_benchmark_path(path) {
+        std_path(path)
+    } else {
+        path.to_string()
+    }
+}
+
+// The canonical name of a function of the standard library, named after its definition rather
+// than its re-export, e.g., <std::vec::Vec<u32>>::push becomes alloc::vec::Vec::push.
+pub fn std_path(path: &str) -> String {
+    let mut path = canonical_path(path);
+    for (facade, definition) in STD_FACADE {
+        let mut renamed = String::new();
+        let mut rest = path.as_str();
+        while let Some(pos) = rest.find(facade) {
+            // Only whole paths, e.g., not my_std::vec::f.
+            let whole = rest[..pos]
+                .chars()
+                .last()
+                .or_else(|| renamed.chars().last())
+                .is_none_or(|c| !(c.is_alphanumeric() || c == '_' || c == ':'));
+            renamed.push_str(&rest[..pos]);
+            renamed.push_str(if whole { definition } else { facade });
+            rest = &rest[pos + facade.len()..];
+        }
+        renamed.push_str(rest);
+        path = renamed;
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages;
+
+    fn index() -> Index {
+        let root = packages::default_root();
+        packages::load(&root).unwrap();
+        Index::build(&root).unwrap()
+    }
+
+    #[test]
+    fn canonical_paths() {
+        assert_eq!(
+            canonical_path(
+                "generics::bench::<impl traits::lib::GenericFooTrait<i32> for generics::base::Two>::method"
+            ),
+            "<generics::base::Two as traits::lib::GenericFooTrait<i32>>::method"
+        );
+        assert_eq!(
+            canonical_path("generics::base::Wrapper<T>::new"),
+            "generics::base::Wrapper::new"
+        );
+        assert_eq!(
+            canonical_path("generics::lib::monomorphized::<structs::lib::One>"),
+            "generics::lib::monomorphized"
+        );
+    }
+
+    #[test]
+    fn instance_paths() {
+        assert_eq!(
+            instance_path("generics::lib::monomorphized::<structs::lib::One>"),
+            "generics::lib::monomorphized::<structs::lib::One>"
+        );
+        assert_eq!(
+            instance_path("<generics::base::Wrapper<structs::lib::One>>::new"),
+            "generics::base::Wrapper::new::<structs::lib::One>"
+        );
+        assert_eq!(
+            instance_path("generics::base::Wrapper<T>::new"),
+            "generics::base::Wrapper::new"
+        );
+    }
+
+    #[test]
+    fn split_paths() {
+        assert_eq!(split_path("<A as B>::method"), ["<A as B>", "method"]);
+        assert_eq!(
+            split_path("a::{impl Foo for Bar}::method"),
+            ["a", "{impl Foo for Bar}", "method"]
+        );
+        assert_eq!(
+            split_path("<F as Fn() -> u32>::call"),
+            ["<F as Fn() -> u32>", "call"]
+        );
+    }
+
+    #[test]
+    fn std_paths() {
+        assert_eq!(
+            std_path("<std::vec::Vec<u32>>::push"),
+            "alloc::vec::Vec::push"
+        );
+        assert_eq!(
+            std_path("alloc::vec::Vec<T,A>::push"),
+            "alloc::vec::Vec::push"
+        );
+        assert_eq!(std_path("my_std::vec::f"), "my_std::vec::f");
+        assert!(is_same_std_trait("std::ops::Add", "core::ops::arith::Add"));
+        assert!(!is_same_std_trait("std::ops::Add", "core::ops::arith::Sub"));
+    }
+
+    #[test]
+    fn normalizes_the_names_of_tools() {
+        let index = index();
+        let normalizer = Normalizer::new(&index);
+        // A legacy symbol name, as LLVM opt prints it.
+        assert_eq!(
+            normalizer
+                .canonical("_ZN8generics5bench3run17h89d9cc231ea7a78eE")
+                .as_deref(),
+            Some("generics::bench::run")
+        );
+        // MIRAI's DefPath of a method of a trait implementation.
+        let method = index
+            .functions
+            .iter()
+            .find(|function| {
+                function.name == "<structs::lib::fat::Fat as traits::lib::FooTrait>::method"
+            })
+            .unwrap();
+        assert_eq!(
+            normalizer.canonical(&method.def_path).as_deref(),
+            Some(method.name.as_str())
+        );
+        // Functions of the standard library are only kept on the std layer.
+        assert_eq!(normalizer.canonical("std::io::_print"), None);
+        assert_eq!(
+            Normalizer::with_layer(&index, Layer::Std)
+                .canonical("std::io::_print")
+                .as_deref(),
+            Some("std::io::stdio::_print")
+        );
+        // Symbols outside of any crate, e.g., the C main function.
+        assert_eq!(normalizer.canonical("main"), None);
+    }
+
+    #[test]
+    fn normalizes_the_names_of_closures() {
+        let index = index();
+        let normalizer = Normalizer::new(&index);
+        let canonical = |name: &str| normalizer.canonical(name);
+        // MIRAI and v0 symbol names number closures like the index.
+        assert_eq!(
+            canonical("closures::lib::boxed::{closure#1}").as_deref(),
+            Some("closures::lib::boxed::{closure#1}")
+        );
+        // Legacy symbol names do not number closures, which tells the only closure of a parent
+        // apart, but not one of several.
+        assert_eq!(
+            canonical("closures::lib::foo_of::{{closure}}").as_deref(),
+            Some("closures::lib::foo_of::{closure#0}")
+        );
+        assert_eq!(
+            canonical("closures::lib::boxed::{{closure}}").as_deref(),
+            Some("closures::lib::boxed::{closure}")
+        );
+        // rust-analyzer names closures by their span, whose file may be relative to any directory
+        // and whose column may be missing.
+        assert_eq!(
+            canonical("{closure@src/closures/src/lib.rs:87:22}").as_deref(),
+            Some("closures::lib::boxed::{closure#1}")
+        );
+        assert_eq!(
+            canonical("closures::lib::boxed::{closure@lib.rs:80:22}").as_deref(),
+            Some("closures::lib::boxed::{closure#0}")
+        );
+        assert_eq!(
+            canonical("{closure@closures/src/lib.rs:69}").as_deref(),
+            Some("closures::lib::foo_of::{closure#0}")
+        );
+        // A span at which the index knows no closure is kept under its parent, and has no name
+        // without one.
+        assert_eq!(
+            canonical("closures::lib::boxed::{closure@src/closures/src/lib.rs:80:9}").as_deref(),
+            Some("closures::lib::boxed::{closure@src/closures/src/lib.rs:80:9}")
+        );
+        assert_eq!(canonical("{closure@src/closures/src/lib.rs:1:1}"), None);
+    }
+}
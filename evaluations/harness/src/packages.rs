@@ -0,0 +1,159 @@
+// The benchmark packages, discovered with 'cargo metadata' from the workspace manifest at the root
+// of the repository. The '[workspace.metadata.benchmark]' table of the manifest lists the packages
+// in the order in which they are indexed, with the category of the calls of each:
+//
+//     [workspace.metadata.benchmark]
+//     packages = [
+//         { name = "traits", category = "support" },
+//         { name = "static_dispatch", category = "static" },
+//         ...
+//     ]
+//
+// The packages are loaded once per run of the harness, before any command reads them.
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::error::Result;
+
+// A benchmark package as described in src/README.md. 'dir' is relative to the repository root.
+pub struct Package {
+    pub name: String,
+    pub dir: String,
+    pub category: String,
+    // Whether the package has a library crate, otherwise it has a binary crate of its name.
+    pub library: bool,
+    // The names of the packages that the package depends on directly.
+    dependencies: Vec<String>,
+}
+
+static PACKAGES: OnceLock<Vec<Package>> = OnceLock::new();
+
+// Discovers the benchmark packages of the workspace at 'root'. Procedural macro packages, i.e.,
+// 'macros-derive', are not benchmark packages; the code they generate is accounted for through
+// the packages that use them.
+pub fn load(root: &Path) -> Result<()> {
+    if PACKAGES.get().is_some() {
+        return Ok(());
+    }
+    let output = Command::new("cargo")
+        .args(["metadata", "--quiet", "--no-deps", "--format-version", "1"])
+        .current_dir(root)
+        .output()
+        .map_err(|e| Error::new(format!("cannot run cargo: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::new(format!(
+            "cargo metadata failed in {}:\n{}",
+            root.display(),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    let metadata: Value = serde_json::from_slice(&output.stdout)?;
+    let _ = PACKAGES.set(packages(&metadata)?);
+    Ok(())
+}
+
+// The packages listed by the metadata of the workspace, with their locations and dependencies.
+fn packages(metadata: &Value) -> Result<Vec<Package>> {
+    let workspace_root = PathBuf::from(metadata["workspace_root"].as_str().unwrap_or_default());
+    let listed = metadata["metadata"]["benchmark"]["packages"]
+        .as_array()
+        .ok_or_else(|| {
+            Error::new("the workspace lists no [workspace.metadata.benchmark] packages")
+        })?;
+    let members = metadata["packages"].as_array().cloned().unwrap_or_default();
+
+    let mut packages = Vec::new();
+    for entry in listed {
+        let name = entry["name"].as_str().unwrap_or_default();
+        let member = members
+            .iter()
+            .find(|member| member["name"] == name)
+            .ok_or_else(|| Error::new(format!("package '{}' is not a workspace member", name)))?;
+        let kinds: Vec<&str> = member["targets"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .flat_map(|target| target["kind"].as_array().into_iter().flatten())
+            .filter_map(Value::as_str)
+            .collect();
+        if kinds.contains(&"proc-macro") {
+            continue;
+        }
+        let manifest = Path::new(member["manifest_path"].as_str().unwrap_or_default());
+        let dir = manifest
+            .parent()
+            .and_then(|dir| dir.strip_prefix(&workspace_root).ok())
+            .ok_or_else(|| Error::new(format!("package '{}' is outside the workspace", name)))?;
+        let dependencies = member["dependencies"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|dependency| dependency["kind"].is_null())
+            .filter_map(|dependency| dependency["name"].as_str())
+            .map(str::to_string)
+            .collect();
+        packages.push(Package {
+            name: name.to_string(),
+            dir: dir.to_string_lossy().into_owned(),
+            category: entry["category"].as_str().unwrap_or_default().to_string(),
+            library: kinds.contains(&"lib"),
+            dependencies,
+        });
+    }
+    Ok(packages)
+}
+
+// The benchmark packages, in the order of the workspace metadata.
+pub fn all() -> &'static [Package] {
+    PACKAGES
+        .get()
+        .expect("the benchmark packages are loaded before they are read")
+}
+
+impl Package {
+    // The cargo options that select the package's crate: its library, or its binary if it has no
+    // library.
+    pub fn target_args(&self) -> Vec<String> {
+        if self.library {
+            vec!["--lib".to_string()]
+        } else {
+            vec!["--bin".to_string(), self.name.clone()]
+        }
+    }
+
+    // The benchmark packages that the package depends on, directly or through other packages.
+    pub fn dependencies(&self) -> Vec<&'static Package> {
+        let mut dependencies: Vec<&'static Package> = Vec::new();
+        let mut pending: Vec<&str> = self.dependencies.iter().map(String::as_str).collect();
+        while let Some(name) = pending.pop() {
+            let package = match find(name) {
+                Some(package) => package,
+                None => continue,
+            };
+            if !dependencies.iter().any(|known| known.name == package.name) {
+                dependencies.push(package);
+                pending.extend(package.dependencies.iter().map(String::as_str));
+            }
+        }
+        dependencies
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Package> {
+    all().iter().find(|package| package.name == name)
+}
+
+pub fn is_benchmark_crate(name: &str) -> bool {
+    find(name).is_some()
+}
+
+// The harness lives in evaluations/harness, two levels below the repository root.
+pub fn default_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("..").join("..")
+}
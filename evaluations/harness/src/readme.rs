@@ -0,0 +1,183 @@
+// The links of the call examples of the benchmark's readme (src/README.md).
+//
+// Each example links to an annotated call through a reference definition whose title names the
+// call by its caller and the target of its annotation:
+//
+//     * [Call to inherent public method inside another method.][1]
+//
+//     [1]: structs/src/lib.rs#L37 "structs::lib::Two::method_2 -> structs::lib::Two::method_1"
+//
+// The titles are written by hand and stay put when code moves; the destinations are derived from
+// the ground truth. Callers that make the same call more than once tell the calls apart by their
+// order, e.g., "static_dispatch::bench::run -> structs::lib::One::method_2 #2" is the second one.
+// Destinations are relative to the readme, so that they point to the same commit as the readme
+// itself.
+
+use std::path::Path;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::truth::CallSite;
+use crate::truth::Truth;
+
+// The readme with the destination of each example's link derived from the ground truth.
+pub fn update(readme: &str, truth: &Truth) -> Result<String> {
+    let mut updated = String::new();
+    for (number, line) in readme.lines().enumerate() {
+        match definition(line) {
+            Some((label, title)) => {
+                let site = site(truth, title)
+                    .map_err(|e| Error::new(format!("README.md:{}: {}", number + 1, e)))?;
+                let file = site.file.strip_prefix("src").unwrap_or(&site.file);
+                updated.push_str(&format!(
+                    "[{}]: {}#L{} \"{}\"",
+                    label,
+                    link(file),
+                    site.line,
+                    title
+                ));
+            }
+            None => updated.push_str(line),
+        }
+        updated.push('\n');
+    }
+    Ok(updated)
+}
+
+// The links whose destination is not the one derived from the ground truth, each with the line
+// of the readme that defines it and the destination it should have.
+pub fn stale_links(readme: &str, truth: &Truth) -> Result<Vec<String>> {
+    let updated = update(readme, truth)?;
+    Ok(readme
+        .lines()
+        .zip(updated.lines())
+        .enumerate()
+        .filter(|(_, (current, updated))| current != updated)
+        .map(|(number, (_, updated))| format!("README.md:{}: expected {}", number + 1, updated))
+        .collect())
+}
+
+// The label and title of a reference definition with a title, '[3]: <destination> "<title>"'.
+fn definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix('[')?;
+    let (label, rest) = rest.split_once("]: ")?;
+    let (_, title) = rest.split_once(" \"")?;
+    let title = title.strip_suffix('"')?;
+    Some((label, title))
+}
+
+// The annotated call named by the title of an example's link.
+fn site<'a>(truth: &'a Truth, title: &str) -> Result<&'a CallSite> {
+    let (call, nth) = match title.rsplit_once(" #") {
+        Some((call, nth)) if nth.parse::<usize>().is_ok() => (call, nth.parse().unwrap()),
+        _ => (title, 1),
+    };
+    let (caller, annotation) = call.split_once(" -> ").ok_or_else(|| {
+        Error::new(format!(
+            "'{}' does not name a call as '<caller> -> <target>'",
+            title
+        ))
+    })?;
+    let mut sites: Vec<&CallSite> = truth
+        .sites
+        .iter()
+        .filter(|site| site.caller == caller && site.annotation == annotation)
+        .collect();
+    sites.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    sites.dedup_by(|a, b| a.file == b.file && a.line == b.line);
+    match (sites.len(), nth) {
+        (0, _) => Err(Error::new(format!("no annotated call '{}'", call))),
+        (1, 1) => Ok(sites[0]),
+        (1, _) => Err(Error::new(format!(
+            "'{}' is the only call of its kind, it takes no number",
+            call
+        ))),
+        (count, 0) => Err(Error::new(format!(
+            "'{}' is made {} times, number the calls from 1",
+            call, count
+        ))),
+        (count, _) if title == call => Err(Error::new(format!(
+            "'{}' is made {} times, add a number, e.g., '{} #1'",
+            call, count, call
+        ))),
+        (count, _) => sites.get(nth - 1).copied().ok_or_else(|| {
+            Error::new(format!(
+                "'{}' is made {} times, there is no call #{}",
+                call, count, nth
+            ))
+        }),
+    }
+}
+
+// Paths in links are separated by slashes on every platform.
+fn link(file: &Path) -> String {
+    let segments: Vec<_> = file
+        .iter()
+        .map(|segment| segment.to_string_lossy())
+        .collect();
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::index::Index;
+    use crate::packages;
+
+    fn truth() -> Truth {
+        let root = packages::default_root();
+        packages::load(&root).unwrap();
+        Truth::extract(&root, &Index::build(&root).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn checks_the_destinations_of_the_links() {
+        let truth = truth();
+        let current = update(
+            "[1]: structs/src/lib.rs#L1 \"structs::lib::Two::method_2 -> structs::lib::Two::method_1\"\n",
+            &truth,
+        )
+        .unwrap();
+        assert!(stale_links(&current, &truth).unwrap().is_empty());
+
+        // A link to a line that the call has moved from.
+        let readme = format!(
+            "* [Call to inherent public method inside another method.][1]\n\n{}",
+            current.replace("#L", "#L1")
+        );
+        assert_eq!(
+            stale_links(&readme, &truth).unwrap(),
+            [format!("README.md:3: expected {}", current.trim_end())]
+        );
+    }
+
+    #[test]
+    fn rejects_titles_that_name_no_single_call() {
+        let truth = truth();
+        let error = |title: &str| {
+            update(&format!("[1]: lib.rs#L1 \"{}\"", title), &truth)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            error("structs::lib::Two::method_2 -> structs::lib::Two::method_3"),
+            "README.md:1: no annotated call 'structs::lib::Two::method_2 -> structs::lib::Two::method_3'"
+        );
+        assert_eq!(
+            error("structs::lib::Two::method_2"),
+            "README.md:1: 'structs::lib::Two::method_2' does not name a call as '<caller> -> <target>'"
+        );
+        assert!(
+            error("static_dispatch::bench::run -> structs::lib::One::method_2")
+                .contains("add a number")
+        );
+        assert!(
+            error("static_dispatch::bench::run -> structs::lib::One::method_2 #9")
+                .contains("there is no call #9")
+        );
+        assert!(
+            error("structs::lib::Two::method_2 -> structs::lib::Two::method_1 #2")
+                .contains("it takes no number")
+        );
+    }
+}
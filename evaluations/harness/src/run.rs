@@ -0,0 +1,338 @@
+// Runs of tool adapters, with their cost.
+//
+// An adapter is a shell command that produces the call graph of a benchmark package in DOT format.
+// It is run in the package's directory with
+//
+//     HARNESS_PACKAGE     the name of the package, e.g., static_dispatch
+//     HARNESS_OUTPUT      the file to write the graph to
+//
+// as well as the variables of the adapter's 'env', e.g., the build configuration of a sweep (see
+// sweep.rs), and may print the graph to its standard output instead. Each run is given a timeout,
+// after which the adapter and all the processes it started are killed. The wall time, the peak
+// resident set size of the adapter and its descendants (from the rusage that wait4 reports), the
+// exit status and the tail of the standard error are kept with the result, so that a run that
+// crashed or timed out is reported as a failure rather than scored as an empty graph.
+
+use std::fs;
+use std::io::Read;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process;
+use std::process::Command;
+use std::process::Stdio;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::dot;
+use crate::dot::Dot;
+use crate::error::Error;
+use crate::error::Result;
+
+// The default timeout of a run, in seconds.
+pub const TIMEOUT: u64 = 600;
+
+// Only the end of the standard error of a run is kept, which is where tools report why they failed.
+const STDERR_LIMIT: usize = 16 * 1024;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    // The adapter exited successfully and produced a call graph.
+    Ok,
+    // The adapter exited with a non-zero status or produced no (or a malformed) call graph.
+    Failed,
+    // The adapter was killed by a signal.
+    Crashed,
+    // The adapter did not finish in time and was killed.
+    Timeout,
+}
+
+impl RunStatus {
+    pub fn name(self) -> &'static str {
+        match self {
+            RunStatus::Ok => "ok",
+            RunStatus::Failed => "failed",
+            RunStatus::Crashed => "crashed",
+            RunStatus::Timeout => "timeout",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RunMetrics {
+    pub status: RunStatus,
+    // Why the run failed, if it did.
+    pub error: Option<String>,
+    // Wall time in seconds.
+    pub wall_time: f64,
+    // Peak resident set size of the adapter and its descendants in KiB.
+    pub peak_rss: u64,
+    // None if the adapter was killed by a signal.
+    pub exit_code: Option<i32>,
+    pub signal: Option<i32>,
+    pub stderr: String,
+}
+
+impl RunMetrics {
+    fn fail(&mut self, status: RunStatus, error: String) {
+        self.status = status;
+        self.error = Some(error);
+    }
+}
+
+pub struct Adapter {
+    pub command: String,
+    pub timeout: Duration,
+    // Variables set for the runs in addition to HARNESS_PACKAGE and HARNESS_OUTPUT.
+    pub env: Vec<(String, String)>,
+}
+
+impl Adapter {
+    // Runs the adapter for a package and reads the call graph it produced, if the run succeeded.
+    pub fn run(&self, package: &str, dir: &Path) -> Result<(RunMetrics, Option<Dot>)> {
+        let output =
+            std::env::temp_dir().join(format!("harness-{}-{}.dot", process::id(), package));
+        // A graph left over by an earlier run must not be taken for this run's.
+        let _ = fs::remove_file(&output);
+
+        let start = Instant::now();
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .current_dir(dir)
+            .env("HARNESS_PACKAGE", package)
+            .env("HARNESS_OUTPUT", &output)
+            .envs(self.env.clone())
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            // The adapter leads a process group of its own, so that a timeout kills the processes
+            // it started as well.
+            .process_group(0)
+            .spawn()
+            .map_err(|e| Error::new(format!("cannot run adapter '{}': {}", self.command, e)))?;
+        let stdout = drain(child.stdout.take());
+        let stderr = drain(child.stderr.take());
+
+        let pid = child.id() as libc::pid_t;
+        let mut status = 0;
+        // SAFETY: rusage is plain old data, for which all zeroes is a valid value.
+        let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+        let mut timed_out = false;
+        loop {
+            // SAFETY: 'status' and 'usage' are valid for writes, and 'pid' is a child of this
+            // process that has not been reaped.
+            let waited = unsafe { libc::wait4(pid, &mut status, libc::WNOHANG, &mut usage) };
+            if waited == pid {
+                break;
+            }
+            if waited < 0 {
+                return Err(Error::new(format!(
+                    "cannot wait for adapter '{}': {}",
+                    self.command,
+                    std::io::Error::last_os_error()
+                )));
+            }
+            if !timed_out && start.elapsed() >= self.timeout {
+                // SAFETY: kill has no memory safety preconditions.
+                unsafe { libc::kill(-pid, libc::SIGKILL) };
+                timed_out = true;
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        let wall_time = start.elapsed().as_secs_f64();
+        let stdout = stdout.join().unwrap_or_default();
+        let stderr = stderr.join().unwrap_or_default();
+
+        let exited = libc::WIFEXITED(status);
+        let mut metrics = RunMetrics {
+            status: RunStatus::Ok,
+            error: None,
+            wall_time,
+            peak_rss: usage.ru_maxrss.max(0) as u64,
+            exit_code: if exited {
+                Some(libc::WEXITSTATUS(status))
+            } else {
+                None
+            },
+            signal: if libc::WIFSIGNALED(status) {
+                Some(libc::WTERMSIG(status))
+            } else {
+                None
+            },
+            stderr: tail(&stderr, STDERR_LIMIT),
+        };
+
+        if timed_out {
+            metrics.fail(
+                RunStatus::Timeout,
+                format!("timed out after {} s", self.timeout.as_secs()),
+            );
+        } else if let Some(signal) = metrics.signal {
+            metrics.fail(RunStatus::Crashed, format!("killed by signal {}", signal));
+        } else if metrics.exit_code != Some(0) {
+            metrics.fail(
+                RunStatus::Failed,
+                format!("exited with status {}", metrics.exit_code.unwrap_or(-1)),
+            );
+        }
+        if metrics.status != RunStatus::Ok {
+            let _ = fs::remove_file(&output);
+            return Ok((metrics, None));
+        }
+
+        let source = match fs::read_to_string(&output) {
+            Ok(source) => {
+                let _ = fs::remove_file(&output);
+                source
+            }
+            Err(_) => String::from_utf8_lossy(&stdout).into_owned(),
+        };
+        if source.trim().is_empty() {
+            metrics.fail(
+                RunStatus::Failed,
+                "wrote no call graph to $HARNESS_OUTPUT or its standard output".to_string(),
+            );
+            return Ok((metrics, None));
+        }
+        match dot::parse(&source) {
+            Ok(dot) => Ok((metrics, Some(dot))),
+            Err(e) => {
+                metrics.fail(RunStatus::Failed, format!("malformed call graph: {}", e));
+                Ok((metrics, None))
+            }
+        }
+    }
+}
+
+// Reads a pipe to its end on a thread of its own, so that an adapter that fills one pipe while
+// the other is read does not block.
+fn drain(pipe: Option<impl Read + Send + 'static>) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buffer);
+        }
+        buffer
+    })
+}
+
+// The last 'limit' bytes of an output, cut at a character boundary.
+fn tail(output: &[u8], limit: usize) -> String {
+    let text = String::from_utf8_lossy(output);
+    let mut start = text.len().saturating_sub(limit);
+    while !text.is_char_boundary(start) {
+        start += 1;
+    }
+    text[start..].to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Runs a command as the adapter of a package. The graph file of a run is named after the
+    // package, so each test runs a package of its own.
+    fn run(command: &str, package: &str, timeout: Duration) -> (RunMetrics, Option<Dot>) {
+        let adapter = Adapter {
+            command: command.to_string(),
+            timeout,
+            env: Vec::new(),
+        };
+        adapter.run(package, &std::env::temp_dir()).unwrap()
+    }
+
+    #[test]
+    fn reads_the_graph_from_the_output_file_or_the_standard_output() {
+        let timeout = Duration::from_secs(TIMEOUT);
+        let (metrics, dot) = run(
+            r#"echo 'digraph { a -> b }' > "$HARNESS_OUTPUT""#,
+            "run_test_output_file",
+            timeout,
+        );
+        assert_eq!(metrics.status, RunStatus::Ok);
+        assert_eq!(metrics.exit_code, Some(0));
+        assert_eq!(dot.unwrap().edges.len(), 1);
+
+        let (metrics, dot) = run(
+            r#"echo "digraph { $HARNESS_PACKAGE -> b }""#,
+            "run_test_stdout",
+            timeout,
+        );
+        assert_eq!(metrics.status, RunStatus::Ok);
+        assert!(dot.unwrap().nodes.contains_key("run_test_stdout"));
+    }
+
+    #[test]
+    fn sets_the_variables_of_the_adapter() {
+        let adapter = Adapter {
+            command: r#"echo "digraph { $HARNESS_PACKAGE -> opt_$HARNESS_OPT_LEVEL }""#.to_string(),
+            timeout: Duration::from_secs(TIMEOUT),
+            env: vec![("HARNESS_OPT_LEVEL".to_string(), "3".to_string())],
+        };
+        let (metrics, dot) = adapter.run("run_test_env", &std::env::temp_dir()).unwrap();
+        assert_eq!(metrics.status, RunStatus::Ok);
+        assert!(dot.unwrap().nodes.contains_key("opt_3"));
+    }
+
+    #[test]
+    fn fails_runs_that_exit_with_a_non_zero_status() {
+        let (metrics, dot) = run(
+            "echo 'digraph { a -> b }'; echo oops >&2; exit 3",
+            "run_test_exit",
+            Duration::from_secs(TIMEOUT),
+        );
+        assert_eq!(metrics.status, RunStatus::Failed);
+        assert_eq!(metrics.exit_code, Some(3));
+        assert_eq!(metrics.signal, None);
+        assert_eq!(metrics.error.as_deref(), Some("exited with status 3"));
+        assert_eq!(metrics.stderr, "oops\n");
+        assert!(dot.is_none());
+    }
+
+    #[test]
+    fn fails_runs_that_produce_no_graph() {
+        let (metrics, dot) = run("true", "run_test_empty", Duration::from_secs(TIMEOUT));
+        assert_eq!(metrics.status, RunStatus::Failed);
+        assert_eq!(metrics.exit_code, Some(0));
+        assert!(dot.is_none());
+    }
+
+    #[test]
+    fn kills_runs_that_exceed_the_timeout() {
+        let (metrics, dot) = run("sleep 10", "run_test_timeout", Duration::from_millis(100));
+        assert_eq!(metrics.status, RunStatus::Timeout);
+        assert_eq!(metrics.signal, Some(libc::SIGKILL));
+        assert!(metrics.wall_time < 10.0);
+        assert!(dot.is_none());
+    }
+
+    #[test]
+    fn reports_runs_killed_by_a_signal_as_crashed() {
+        let (metrics, dot) = run(
+            "kill -SEGV $$",
+            "run_test_crash",
+            Duration::from_secs(TIMEOUT),
+        );
+        assert_eq!(metrics.status, RunStatus::Crashed);
+        assert_eq!(metrics.exit_code, None);
+        assert_eq!(metrics.signal, Some(libc::SIGSEGV));
+        assert!(dot.is_none());
+    }
+
+    #[test]
+    fn keeps_the_tail_of_the_standard_error() {
+        assert_eq!(tail(b"abcdef", 3), "def");
+        // The cut is moved past the first bytes of a split character.
+        assert_eq!(tail("aé".as_bytes(), 1), "");
+        assert_eq!(tail("aé".as_bytes(), 2), "é");
+    }
+}
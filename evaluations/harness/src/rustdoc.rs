@@ -0,0 +1,355 @@
+// Validation of the annotated targets against the items that rustdoc documents.
+//
+// The targets of the annotations are free text, e.g.,
+// structs::lib::fat::{impl FooTrait for Fat}::method, which the harness resolves to canonical
+// names with its own index of the sources. To catch annotations that name no item at all, the
+// canonical names are looked up independently in the JSON output of rustdoc (--output-format json,
+// enabled with RUSTC_BOOTSTRAP) for each benchmark crate, with private items documented:
+//
+//     crate::module::f            a function of a module
+//     crate::module::Trait::m     a method of a trait
+//     crate::module::Type::m      a method of an inherent impl of the type
+//     <Type as Trait<Args>>::m    a method of an impl of the trait for the type, implemented or
+//                                 provided
+//
+// A target that names none of them is dangling. A target that names more than one is ambiguous,
+// e.g., Type::m of a type without an inherent method m that implements several traits with a
+// method m, or <Type as Trait>::m of a type that implements Trait for several generic arguments.
+// rustdoc names the traits of the standard library after their definitions, e.g.,
+// core::ops::arith::Add, which match the re-exports that the annotations use, e.g., std::ops::Add.
+// rustdoc omits the generic arguments of a trait that are its defaults, e.g., the Rhs = Self of
+// Add<&Fat> for &Fat. Closures and the impls for references to trait objects are not documented,
+// so the targets that name them, e.g., closures::lib::apply::{closure#0} or
+// <&dyn operators::lib::Weigh as std::ops::Add>::add, are looked up in the index instead.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::index::strip_generics;
+use crate::index::Index;
+use crate::names::is_benchmark_path;
+use crate::names::is_same_std_trait;
+use crate::names::split_top_level;
+use crate::packages::Package;
+use crate::truth::Truth;
+
+// Documents a package in JSON and returns the document.
+pub fn generate(root: &Path, package: &Package, target_dir: &Path) -> Result<Value> {
+    let output = Command::new("cargo")
+        .arg("rustdoc")
+        .arg("--quiet")
+        .args(package.target_args())
+        .arg("--target-dir")
+        .arg(target_dir)
+        .args([
+            "--",
+            "-Zunstable-options",
+            "--output-format",
+            "json",
+            "--document-private-items",
+        ])
+        .current_dir(root.join(&package.dir))
+        .env("RUSTC_BOOTSTRAP", "1")
+        .output()
+        .map_err(|e| Error::new(format!("cannot run cargo: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::new(format!(
+            "documenting {} failed:\n{}",
+            package.name,
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    let file = target_dir
+        .join("doc")
+        .join(format!("{}.json", package.name));
+    let source = fs::read_to_string(&file)
+        .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+    Ok(serde_json::from_str(&source)?)
+}
+
+// The items of the benchmark crates, by their paths.
+#[derive(Default)]
+pub struct Items {
+    functions: BTreeSet<String>,
+    // The methods of each trait.
+    traits: BTreeMap<String, BTreeSet<String>>,
+    // The methods of the inherent impls of each type.
+    inherent: BTreeMap<String, BTreeSet<String>>,
+    // The methods, implemented or provided, of each trait impl: type, trait, trait arguments.
+    impls: BTreeMap<(String, String, String), BTreeSet<String>>,
+}
+
+impl Items {
+    // Adds the items of a crate documented by rustdoc.
+    pub fn add(&mut self, document: &Value) {
+        let crate_ = Crate { document };
+        if let Some(root) = crate_.item(&document["root"]) {
+            let name = root["name"].as_str().unwrap_or_default().to_string();
+            self.add_module(&crate_, &name, root);
+        }
+
+        let index = document["index"].as_object().into_iter().flatten();
+        for item in index.map(|(_, item)| item) {
+            if let Some(trait_) = item["inner"].get("trait") {
+                let path = match crate_.path(&item["id"]) {
+                    Some(path) => path,
+                    None => continue,
+                };
+                let methods = crate_.names(&trait_["items"]);
+                self.traits.entry(path).or_default().extend(methods);
+            }
+            let impl_ = match item["inner"].get("impl") {
+                Some(impl_) if impl_["is_synthetic"] != true && impl_["blanket_impl"].is_null() => {
+                    impl_
+                }
+                _ => continue,
+            };
+            // Impls for tuples or type parameters are not methods of a type.
+            let ty = match crate_.impl_type(&impl_["for"]) {
+                Some(ty) => ty,
+                None => continue,
+            };
+            let mut methods = crate_.names(&impl_["items"]);
+            if impl_["trait"].is_null() {
+                self.inherent.entry(ty).or_default().extend(methods);
+                continue;
+            }
+            let trait_ = match crate_.path(&impl_["trait"]["id"]) {
+                Some(trait_) => trait_,
+                None => continue,
+            };
+            let provided = impl_["provided_trait_methods"]
+                .as_array()
+                .into_iter()
+                .flatten();
+            methods.extend(provided.filter_map(|name| name.as_str().map(str::to_string)));
+            let arguments = crate_.arguments(&impl_["trait"]["args"]);
+            self.impls
+                .entry((ty, trait_, arguments))
+                .or_default()
+                .extend(methods);
+        }
+    }
+
+    fn add_module(&mut self, crate_: &Crate, path: &str, module: &Value) {
+        let items = module["inner"]["module"]["items"]
+            .as_array()
+            .into_iter()
+            .flatten();
+        for item in items.filter_map(|id| crate_.item(id)) {
+            let name = match item["name"].as_str() {
+                Some(name) => name,
+                None => continue,
+            };
+            let item_path = format!("{}::{}", path, name);
+            if item["inner"].get("function").is_some() {
+                self.functions.insert(item_path);
+            } else if item["inner"].get("module").is_some() {
+                self.add_module(crate_, &item_path, item);
+            }
+        }
+    }
+
+    // The items that a canonical name refers to, by their canonical names.
+    pub fn resolve(&self, target: &str) -> Vec<String> {
+        if let Some(qualified) = target.strip_prefix('<') {
+            let (ty, rest) = match split_top_level(qualified, " as ") {
+                Some(parts) => parts,
+                None => return Vec::new(),
+            };
+            let (trait_, method) = match rest.rsplit_once(">::") {
+                Some(parts) => parts,
+                None => return Vec::new(),
+            };
+            let ty = strip_generics(ty);
+            let trait_path = strip_generics(trait_);
+            let arguments = &trait_[trait_path.len()..];
+            return self
+                .impls
+                .iter()
+                .filter(|((impl_ty, impl_trait, impl_arguments), methods)| {
+                    *impl_ty == ty
+                        && is_same_std_trait(impl_trait, &trait_path)
+                        && (arguments.is_empty()
+                            || impl_arguments == arguments
+                            || impl_arguments.is_empty() && arguments == format!("<{}>", ty))
+                        && methods.contains(method)
+                })
+                .map(|((ty, trait_, arguments), _)| {
+                    format!("<{} as {}{}>::{}", ty, trait_, arguments, method)
+                })
+                .collect();
+        }
+
+        let target = strip_generics(target);
+        let (path, name) = match target.rsplit_once("::") {
+            Some(parts) => parts,
+            None => return Vec::new(),
+        };
+        let contains = |items: &BTreeMap<String, BTreeSet<String>>| {
+            items
+                .get(path)
+                .is_some_and(|methods| methods.contains(name))
+        };
+        let mut items = Vec::new();
+        if self.functions.contains(&target) || contains(&self.traits) || contains(&self.inherent) {
+            items.push(target.clone());
+        }
+        if items.is_empty() {
+            // Type::m calls the method m of the trait impl of the type that has one.
+            items.extend(
+                self.impls
+                    .iter()
+                    .filter(|((ty, _, _), methods)| ty == path && methods.contains(name))
+                    .map(|((ty, trait_, arguments), _)| {
+                        format!("<{} as {}{}>::{}", ty, trait_, arguments, name)
+                    }),
+            );
+        }
+        items
+    }
+}
+
+// A crate documented by rustdoc, whose items refer to each other by ids.
+struct Crate<'a> {
+    document: &'a Value,
+}
+
+impl<'a> Crate<'a> {
+    fn item(&self, id: &Value) -> Option<&'a Value> {
+        self.document["index"].get(key(id))
+    }
+
+    // The path of a type or trait, of this crate or another one.
+    fn path(&self, id: &Value) -> Option<String> {
+        let path = self.document["paths"].get(key(id))?["path"].as_array()?;
+        let segments: Vec<&str> = path.iter().filter_map(Value::as_str).collect();
+        Some(segments.join("::"))
+    }
+
+    // The type of an impl if it is a named type or a reference to one, e.g.,
+    // &structs::lib::fat::Fat.
+    fn impl_type(&self, ty: &Value) -> Option<String> {
+        if let Some(path) = ty.get("resolved_path") {
+            return self.path(&path["id"]);
+        }
+        let reference = ty.get("borrowed_ref")?;
+        let mutability = if reference["is_mutable"] == true {
+            "mut "
+        } else {
+            ""
+        };
+        Some(format!(
+            "&{}{}",
+            mutability,
+            self.impl_type(&reference["type"])?
+        ))
+    }
+
+    fn names(&self, ids: &Value) -> BTreeSet<String> {
+        ids.as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|id| self.item(id))
+            .filter(|item| item["inner"].get("function").is_some())
+            .filter_map(|item| item["name"].as_str().map(str::to_string))
+            .collect()
+    }
+
+    // The generic arguments of a trait as the harness names them, e.g., <i32>. Empty if the trait
+    // has none.
+    fn arguments(&self, args: &Value) -> String {
+        let args = match args["angle_bracketed"]["args"].as_array() {
+            Some(args) if !args.is_empty() => args,
+            _ => return String::new(),
+        };
+        let types: Vec<String> = args
+            .iter()
+            .map(|arg| self.type_name(&arg["type"]))
+            .collect();
+        format!("<{}>", types.join(", "))
+    }
+
+    fn type_name(&self, ty: &Value) -> String {
+        if let Some(primitive) = ty["primitive"].as_str() {
+            return primitive.to_string();
+        }
+        if let Some(generic) = ty["generic"].as_str() {
+            return generic.to_string();
+        }
+        if let Some(path) = ty.get("resolved_path") {
+            let name = self
+                .path(&path["id"])
+                .unwrap_or_else(|| path["path"].as_str().unwrap_or_default().to_string());
+            return format!("{}{}", name, self.arguments(&path["args"]));
+        }
+        "_".to_string()
+    }
+}
+
+// Ids are numbers in recent versions of the format and strings in older ones; the keys of the
+// index are strings either way.
+fn key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+// An annotated target that names no item or more than one.
+pub struct Reference {
+    pub file: PathBuf,
+    pub line: usize,
+    pub annotation: String,
+    pub target: String,
+    // The items the target names.
+    pub items: Vec<String>,
+}
+
+// The targets of the annotated calls of a package that are dangling or ambiguous. Targets outside
+// the benchmark crates are not checked.
+pub fn check(truth: &Truth, index: &Index, items: &Items, package: &str) -> Vec<Reference> {
+    let mut references = Vec::new();
+    for site in truth.package_sites(package) {
+        for target in site
+            .targets
+            .iter()
+            .filter(|target| is_benchmark_path(target))
+        {
+            let resolved = if target.contains("{closure#") {
+                index
+                    .closure(target)
+                    .map(|closure| closure.name.clone())
+                    .into_iter()
+                    .collect()
+            } else if target.starts_with("<&dyn ") {
+                index
+                    .function(target)
+                    .map(|function| function.name.clone())
+                    .into_iter()
+                    .collect()
+            } else {
+                items.resolve(target)
+            };
+            if resolved.len() == 1 {
+                continue;
+            }
+            references.push(Reference {
+                file: site.file.clone(),
+                line: site.line,
+                annotation: site.annotation.clone(),
+                target: target.clone(),
+                items: resolved,
+            });
+        }
+    }
+    references
+}
@@ -0,0 +1,353 @@
+// The JSON documents that the harness exchanges with other tools: the truth manifest (the
+// annotated calls of the benchmark) and score reports. Their layout is defined by JSON Schemas that
+// are generated from the types below and published in evaluations/schema, one file per document
+// and schema version.
+//
+// Documents carry the version of the schema they were written with. Truth manifests and score
+// reports are versioned separately, so that a change to one does not publish a new schema of the
+// other; up to version 11 they were versioned together. The schemas of all the versions that were
+// written stay published. Older documents are migrated to the current version on load and all
+// documents are validated against the current schema before they are deserialized, so that a
+// mismatch is reported with the location of the offending value rather than as a deserialization
+// error.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use jsonschema::JSONSchema;
+use schemars::schema::RootSchema;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::run::RunMetrics;
+use crate::score::Layer;
+use crate::score::MatchMode;
+use crate::score::PackageScore;
+use crate::truth::CallSite;
+
+// Bumped whenever the layout of a document or the vocabulary of its values, e.g., the kinds of
+// calls, changes. Each bump adds a migration from the previous version to MIGRATIONS and publishes
+// the schema of the new version; published schemas are never edited, so that the readers of a
+// version keep rejecting the values added after it.
+pub const TRUTH_VERSION: u32 = 11;
+pub const REPORT_VERSION: u32 = 11;
+
+// Migrations of documents from one schema version to the next, indexed by the version they migrate
+// from. A migration rewrites the document in place, e.g., renames a call kind, and is given the
+// name of the document as documents may need different rewrites. The migrations up to version 11
+// are shared by both documents and leave those they do not concern as they are.
+type Migration = fn(document: &str, value: &mut Value) -> Result<()>;
+const MIGRATIONS: &[(u32, Migration)] = &[
+    (1, add_tags),
+    (2, add_instances),
+    (3, add_runs),
+    (4, add_callbacks),
+    (5, add_candidates),
+    (6, add_closure_kinds),
+    (7, add_future_poll),
+    (8, add_operator_syntax),
+    (9, add_auto_deref_syntax),
+    (10, add_drops),
+];
+
+// Version 2 tags the call sites of truth manifests (see tags.rs). The tags of older manifests are
+// unknown.
+fn add_tags(document: &str, value: &mut Value) -> Result<()> {
+    if document != TruthManifest::NAME {
+        return Ok(());
+    }
+    let sites = value
+        .get_mut("sites")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::new("missing 'sites'"))?;
+    for site in sites.iter_mut().filter_map(Value::as_object_mut) {
+        site.entry("tags").or_insert(Value::Null);
+    }
+    Ok(())
+}
+
+// Version 3 adds the expected edges between instances to the call sites of truth manifests, and
+// the granularity at which generic calls were matched to score reports. Older manifests know no
+// instances and older reports collapsed instances into their definitions.
+fn add_instances(document: &str, value: &mut Value) -> Result<()> {
+    if document == ScoreReport::NAME {
+        let report = value
+            .as_object_mut()
+            .ok_or_else(|| Error::new("expected an object"))?;
+        report
+            .entry("mode")
+            .or_insert(Value::from(MatchMode::Collapsed.name()));
+        return Ok(());
+    }
+    let sites = value
+        .get_mut("sites")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::new("missing 'sites'"))?;
+    for site in sites.iter_mut().filter_map(Value::as_object_mut) {
+        site.entry("instances").or_insert(Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+// Version 4 adds the cost of the adapter runs that produced the scored graphs to score reports.
+// Older reports scored graphs given as files.
+fn add_runs(document: &str, value: &mut Value) -> Result<()> {
+    if document != ScoreReport::NAME {
+        return Ok(());
+    }
+    let report = value
+        .as_object_mut()
+        .ok_or_else(|| Error::new("expected an object"))?;
+    report
+        .entry("runs")
+        .or_insert(Value::Object(Default::default()));
+    Ok(())
+}
+
+// Version 5 adds the std layer (see score.rs): the callbacks of the standard library to the call
+// sites of truth manifests, and the layer that was scored to score reports. Older manifests know no
+// callbacks and older reports scored the benchmark layer.
+fn add_callbacks(document: &str, value: &mut Value) -> Result<()> {
+    if document == ScoreReport::NAME {
+        let report = value
+            .as_object_mut()
+            .ok_or_else(|| Error::new("expected an object"))?;
+        report
+            .entry("layer")
+            .or_insert(Value::from(Layer::Benchmark.name()));
+        return Ok(());
+    }
+    let sites = value
+        .get_mut("sites")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::new("missing 'sites'"))?;
+    for site in sites.iter_mut().filter_map(Value::as_object_mut) {
+        site.entry("callbacks").or_insert(Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+// Version 6 adds the candidates of function pointer calls to the call sites of truth manifests.
+// Older manifests know none.
+fn add_candidates(document: &str, value: &mut Value) -> Result<()> {
+    if document != TruthManifest::NAME {
+        return Ok(());
+    }
+    let sites = value
+        .get_mut("sites")
+        .and_then(Value::as_array_mut)
+        .ok_or_else(|| Error::new("missing 'sites'"))?;
+    for site in sites.iter_mut().filter_map(Value::as_object_mut) {
+        site.entry("candidates").or_insert(Value::Array(Vec::new()));
+    }
+    Ok(())
+}
+
+// Version 7 adds the kinds of the calls of closures through FnMut and FnOnce. Older documents use
+// neither, so they are valid as they are.
+fn add_closure_kinds(_document: &str, _value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+// Version 8 adds the kind of the polls of futures, by '.await' or by an executor, and the 'await'
+// dispatch tag. Older documents use neither, so they are valid as they are.
+fn add_future_poll(_document: &str, _value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+// Version 9 adds the 'operator' syntax tag of the calls that operator expressions make, e.g.,
+// 'a + b'. Older documents have no such calls, so they are valid as they are.
+fn add_operator_syntax(_document: &str, _value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+// Version 10 adds the 'auto_deref' syntax tag of the implicit derefs of the receivers of method
+// calls. Older documents have no such calls, so they are valid as they are.
+fn add_auto_deref_syntax(_document: &str, _value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+// Version 11 adds the kind of the drops of values, which call Drop implementations through the
+// drop glue, and the 'drop' dispatch and syntax tags. Older documents have no drops, so they are
+// valid as they are.
+fn add_drops(_document: &str, _value: &mut Value) -> Result<()> {
+    Ok(())
+}
+
+pub trait Document: Serialize + DeserializeOwned + JsonSchema {
+    // Name of the document, used in the names of its schema files.
+    const NAME: &'static str;
+    // The current version of the document's schema.
+    const VERSION: u32;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct TruthManifest {
+    pub schema_version: u32,
+    pub sites: Vec<CallSite>,
+}
+
+impl Document for TruthManifest {
+    const NAME: &'static str = "truth";
+    const VERSION: u32 = TRUTH_VERSION;
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ScoreReport {
+    pub schema_version: u32,
+    pub mode: MatchMode,
+    pub layer: Layer,
+    pub packages: BTreeMap<String, PackageScore>,
+    // The adapter runs by package, empty if the graphs were given as files. Packages whose run
+    // failed have no score.
+    pub runs: BTreeMap<String, RunMetrics>,
+}
+
+impl Document for ScoreReport {
+    const NAME: &'static str = "report";
+    const VERSION: u32 = REPORT_VERSION;
+}
+
+pub fn schema<D: Document>() -> Value {
+    let mut schema: RootSchema = schemars::schema_for!(D);
+    let object = schema.schema.object();
+    object.required.insert("schema_version".to_string());
+    object.properties.insert(
+        "schema_version".to_string(),
+        serde_json::from_value(serde_json::json!({ "const": D::VERSION })).unwrap(),
+    );
+    serde_json::to_value(schema).unwrap()
+}
+
+// The published schema of a document, relative to the repository root.
+pub fn path<D: Document>() -> PathBuf {
+    Path::new("evaluations")
+        .join("schema")
+        .join(file_name(D::NAME, D::VERSION))
+}
+
+fn file_name(name: &str, version: u32) -> String {
+    format!("{}.v{}.schema.json", name, version)
+}
+
+pub fn to_json<T: Serialize>(document: &T) -> String {
+    let mut json = serde_json::to_string_pretty(document).unwrap();
+    json.push('\n');
+    json
+}
+
+pub fn load<D: Document>(path: &Path) -> Result<D> {
+    let source = fs::read_to_string(path)
+        .map_err(|e| Error::new(format!("cannot read {}: {}", path.display(), e)))?;
+    let value = serde_json::from_str(&source)
+        .map_err(|e| Error::new(format!("{}: {}", path.display(), e)))?;
+    parse(value).map_err(|e| Error::new(format!("{}: {}", path.display(), e)))
+}
+
+pub fn parse<D: Document>(mut value: Value) -> Result<D> {
+    migrate::<D>(&mut value)?;
+    validate::<D>(&value)?;
+    serde_json::from_value(value).map_err(Error::from)
+}
+
+// Brings a document of an older schema version to the current version.
+pub fn migrate<D: Document>(value: &mut Value) -> Result<()> {
+    let version = match value.get("schema_version").and_then(Value::as_u64) {
+        Some(version) => version as u32,
+        None => return Err(Error::new("missing or malformed 'schema_version'")),
+    };
+    if version > D::VERSION {
+        return Err(Error::new(format!(
+            "schema version {} is newer than the supported version {}",
+            version,
+            D::VERSION
+        )));
+    }
+    for version in version..D::VERSION {
+        let migration = MIGRATIONS
+            .iter()
+            .find(|(from, _)| *from == version)
+            .map(|(_, migration)| migration)
+            .ok_or_else(|| Error::new(format!("no migration from schema version {}", version)))?;
+        migration(D::NAME, value)?;
+    }
+    value["schema_version"] = Value::from(D::VERSION);
+    Ok(())
+}
+
+pub fn validate<D: Document>(value: &Value) -> Result<()> {
+    let schema = JSONSchema::compile(&schema::<D>())
+        .map_err(|e| Error::new(format!("invalid {} schema: {}", D::NAME, e)))?;
+    let result = schema.validate(value);
+    if let Err(errors) = result {
+        let errors: Vec<String> = errors
+            .map(|error| {
+                let path = error.instance_path.to_string();
+                let path = if path.is_empty() {
+                    "/".to_string()
+                } else {
+                    path
+                };
+                format!("    {}: {}", path, error)
+            })
+            .collect();
+        return Err(Error::new(format!(
+            "does not match the {} schema (version {}):\n{}",
+            D::NAME,
+            D::VERSION,
+            errors.join("\n")
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_documents_of_the_first_version() {
+        let report: ScoreReport = parse(serde_json::json!({
+            "schema_version": 1,
+            "packages": {
+                "generics": { "sites": 1, "resolved": 0, "missed": [], "spurious": [] }
+            }
+        }))
+        .unwrap();
+        assert_eq!(report.schema_version, REPORT_VERSION);
+        assert_eq!(report.mode, MatchMode::Collapsed);
+        assert_eq!(report.layer, Layer::Benchmark);
+
+        let manifest: TruthManifest =
+            parse(serde_json::json!({ "schema_version": 1, "sites": [] })).unwrap();
+        assert_eq!(manifest.schema_version, TRUTH_VERSION);
+    }
+
+    #[test]
+    fn loads_reports_of_the_versions_shared_with_truth_manifests() {
+        let report: ScoreReport = parse(serde_json::json!({
+            "schema_version": 8,
+            "mode": "collapsed",
+            "layer": "benchmark",
+            "packages": {},
+            "runs": {}
+        }))
+        .unwrap();
+        assert_eq!(report.schema_version, REPORT_VERSION);
+    }
+
+    #[test]
+    fn rejects_documents_of_newer_versions() {
+        let report = serde_json::json!({ "schema_version": REPORT_VERSION + 1, "packages": {} });
+        assert!(parse::<ScoreReport>(report).is_err());
+    }
+}
@@ -0,0 +1,344 @@
+// Scoring of a tool's call graph against the ground truth.
+//
+// A call site is resolved if the graph contains an edge from the site's caller to each of its
+// targets. For calls whose target is a trait's method, e.g., dynamic dispatch calls and calls on
+// generic receivers, an edge to the method of any implementation of the trait is accepted as well,
+// as tools report the concrete methods that the call might dispatch to.
+//
+// Edges that match no annotated call are spurious. An edge can only be judged if the ground truth
+// knows all the calls of its caller, i.e., the caller is a benchmark function with a body whose
+// annotated calls all name their targets. Function pointer calls are resolved by the functions
+// they point to, but edges to the other candidates of the call, the functions whose type matches
+// the pointer's, are not spurious, as analyses that resolve them by types alone are sound.
+//
+// Scores can be restricted to the calls whose tags match a filter (see tags.rs). Spurious edges
+// belong to no call, so they are only reported by unfiltered scores.
+//
+// Calls of generic functions can be matched at three granularities (see MatchMode). Tools that
+// analyze MIR report the definitions of generic functions, while tools that analyze the generated
+// code report their instances, which only v0 symbol names tell apart.
+//
+// By default, graphs are reduced to the benchmark crates and calls into the standard library are
+// not scored. Tools that analyze the standard library can be scored on the std layer (see Layer),
+// which adds the calls into std, core and alloc and the calls back from them into the callbacks
+// they are given, e.g., from core::fmt::write to the Display::fmt of a formatted value. Calls
+// into the standard library are only annotated where they matter to the benchmark, so edges into
+// it are never spurious. On the benchmark layer, a call of a standard library trait's method on a
+// generic operand, e.g., 'a + b' in operators::lib::sum, is resolved by the implementations that
+// its instances call, as the trait's method is outside the layer. On the std layer, the Drop
+// implementations that a drop runs are called by the drop glue, core::ptr::drop_in_place.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::graph::Edge;
+use crate::index::strip_generics;
+use crate::index::FunctionKind;
+use crate::index::Index;
+use crate::names::canonical_path;
+use crate::names::is_benchmark_path;
+use crate::names::is_std_path;
+use crate::names::split_path;
+use crate::names::split_top_level;
+use crate::names::std_layer_path;
+use crate::tags::Filter;
+use crate::truth::CallKind;
+use crate::truth::CallSite;
+use crate::truth::Truth;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PackageScore {
+    pub sites: usize,
+    pub resolved: usize,
+    pub missed: BTreeSet<Edge>,
+    pub spurious: BTreeSet<Edge>,
+}
+
+impl PackageScore {
+    pub fn percentage(&self) -> f64 {
+        if self.sites == 0 {
+            100.0
+        } else {
+            100.0 * self.resolved as f64 / self.sites as f64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchMode {
+    // The graph's nodes are compared as reported with the instances of the ground truth, e.g.,
+    // generics::bench::run calls generics::lib::monomorphized::<structs::lib::One>. Calls whose
+    // instances are not annotated are compared with their definitions.
+    Instance,
+    // The graph's nodes are compared as reported with the definitions of the ground truth, e.g.,
+    // generics::bench::run calls generics::lib::monomorphized.
+    Definition,
+    // The instances reported by the graph are merged into their definitions, which are compared
+    // with the definitions of the ground truth.
+    #[default]
+    Collapsed,
+}
+
+impl MatchMode {
+    pub fn parse(text: &str) -> Result<MatchMode> {
+        match text {
+            "instance" => Ok(MatchMode::Instance),
+            "definition" => Ok(MatchMode::Definition),
+            "collapsed" => Ok(MatchMode::Collapsed),
+            _ => Err(Error::new(format!(
+                "unknown match mode '{}', expected instance, definition or collapsed",
+                text
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchMode::Instance => "instance",
+            MatchMode::Definition => "definition",
+            MatchMode::Collapsed => "collapsed",
+        }
+    }
+
+    // Brings a tool's graph to the granularity of the mode.
+    pub fn graph(self, graph: CallGraph) -> CallGraph {
+        match self {
+            MatchMode::Collapsed => graph.collapse(),
+            MatchMode::Instance | MatchMode::Definition => graph,
+        }
+    }
+
+    // The edges that resolve a call site, given its targets on the layer.
+    fn expected(self, site: &CallSite, targets: &[String]) -> Vec<Edge> {
+        if self == MatchMode::Instance && !site.instances.is_empty() {
+            return site.instances.clone();
+        }
+        targets
+            .iter()
+            .map(|target| Edge::new(site.caller.as_str(), target.as_str()))
+            .collect()
+    }
+}
+
+// The drop glue of all types, as the std layer names it.
+const DROP_GLUE: &str = "core::ptr::drop_in_place";
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Layer {
+    // The calls between the functions of the benchmark crates.
+    #[default]
+    Benchmark,
+    // The calls between the functions of the benchmark crates and the standard library, and the
+    // calls back from the standard library into the functions it is given.
+    Std,
+}
+
+impl Layer {
+    pub fn parse(text: &str) -> Result<Layer> {
+        match text {
+            "benchmark" => Ok(Layer::Benchmark),
+            "std" => Ok(Layer::Std),
+            _ => Err(Error::new(format!(
+                "unknown layer '{}', expected benchmark or std",
+                text
+            ))),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layer::Benchmark => "benchmark",
+            Layer::Std => "std",
+        }
+    }
+
+    // Whether the layer holds the function of a canonical name.
+    pub fn contains(self, path: &str) -> bool {
+        is_benchmark_path(path) || (self == Layer::Std && is_std_path(path))
+    }
+
+    // The edges that resolve a call site on the layer. Functions of the standard library are
+    // named after their definitions.
+    fn expected(self, mode: MatchMode, site: &CallSite) -> Vec<Edge> {
+        let mut expected = mode.expected(site, &site.layer_targets(self));
+        if self == Layer::Benchmark {
+            return expected;
+        }
+        // The Drop implementations are called by the drop glue, which is part of core.
+        if site.kind == CallKind::Drop {
+            for edge in &mut expected {
+                edge.caller = DROP_GLUE.to_string();
+            }
+        }
+        expected.extend(site.callbacks.iter().cloned());
+        expected
+            .iter()
+            .map(|edge| Edge::new(std_layer_path(&edge.caller), std_layer_path(&edge.callee)))
+            .collect()
+    }
+}
+
+pub fn score(
+    package: &str,
+    truth: &Truth,
+    index: &Index,
+    graph: &CallGraph,
+    filter: &Filter,
+    mode: MatchMode,
+    layer: Layer,
+) -> PackageScore {
+    let mut score = PackageScore::default();
+
+    let sites = truth
+        .package_sites(package)
+        .filter(|site| site.is_scored(layer) && filter.matches(site));
+    for site in sites {
+        score.sites += 1;
+        let mut resolved = true;
+        for expected in layer.expected(mode, site) {
+            if !graph
+                .callees(&expected.caller)
+                .any(|callee| matches(index, &expected.callee, callee))
+            {
+                score.missed.insert(expected);
+                resolved = false;
+            }
+        }
+        if resolved {
+            score.resolved += 1;
+        }
+    }
+
+    if !filter.is_empty() {
+        return score;
+    }
+    let mut expected: BTreeMap<&str, Vec<&CallSite>> = BTreeMap::new();
+    for site in &truth.sites {
+        expected.entry(site.caller.as_str()).or_default().push(site);
+    }
+    for edge in &graph.edges {
+        if !is_benchmark_path(&edge.callee) {
+            continue;
+        }
+        // Instances are judged by the calls of their definition.
+        let caller = match index.function(&canonical_path(&edge.caller)) {
+            Some(caller) if caller.package == package && caller.has_body => caller,
+            _ => continue,
+        };
+        let sites = expected
+            .get(caller.name.as_str())
+            .map(Vec::as_slice)
+            .unwrap_or(&[]);
+        if sites.iter().any(|site| site.targets.is_empty()) {
+            continue;
+        }
+        let explained = sites
+            .iter()
+            .flat_map(|site| layer.expected(mode, site))
+            .any(|expected| {
+                (expected.caller == edge.caller || expected.caller == caller.name)
+                    && matches(index, &expected.callee, &edge.callee)
+            })
+            || sites
+                .iter()
+                .any(|site| site.candidates.contains(&canonical_path(&edge.callee)));
+        if !explained {
+            score.spurious.insert(edge.clone());
+        }
+    }
+
+    score
+}
+
+// Whether a reported callee matches an expected target.
+pub fn matches(index: &Index, target: &str, callee: &str) -> bool {
+    if target == callee {
+        return true;
+    }
+    let is_trait_method = index
+        .function(target)
+        .is_some_and(|function| function.kind == FunctionKind::TraitMethod);
+    if !is_trait_method {
+        return false;
+    }
+
+    let (trait_, method) = match target.rfind("::") {
+        Some(pos) => (&target[..pos], &target[pos + 2..]),
+        None => return false,
+    };
+    let segments = split_path(callee);
+    match segments.as_slice() {
+        [qualified, callee_method] if qualified.starts_with('<') => {
+            let inner = &qualified[1..qualified.len() - 1];
+            match split_top_level(inner, " as ") {
+                Some((_, callee_trait)) => {
+                    strip_generics(callee_trait) == trait_ && callee_method == method
+                }
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packages;
+
+    fn index() -> Index {
+        let root = packages::default_root();
+        packages::load(&root).unwrap();
+        Index::build(&root).unwrap()
+    }
+
+    #[test]
+    fn matches_the_implementations_of_trait_methods() {
+        let index = index();
+        let target = "traits::lib::FooTrait::method";
+        assert!(matches(&index, target, target));
+        assert!(matches(
+            &index,
+            target,
+            "<structs::lib::fat::Fat as traits::lib::FooTrait>::method"
+        ));
+        assert!(!matches(
+            &index,
+            target,
+            "<structs::lib::fat::Fat as traits::lib::FooTrait>::another_method"
+        ));
+        assert!(!matches(
+            &index,
+            target,
+            "<structs::lib::fat::Fat as traits::lib::BarTrait>::method"
+        ));
+        assert!(!matches(&index, target, "structs::lib::fat::Fat::method"));
+    }
+
+    #[test]
+    fn matches_other_targets_exactly() {
+        let index = index();
+        let target = "<structs::lib::fat::Fat as traits::lib::FooTrait>::method";
+        assert!(matches(&index, target, target));
+        assert!(!matches(
+            &index,
+            target,
+            "<structs::lib::thin::Thin as traits::lib::FooTrait>::method"
+        ));
+        assert!(!matches(
+            &index,
+            "structs::lib::fat::Fat::method",
+            "<structs::lib::fat::Fat as traits::lib::FooTrait>::method"
+        ));
+    }
+}
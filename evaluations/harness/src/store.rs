@@ -0,0 +1,386 @@
+// The results store: a directory of JSON records, one per evaluated configuration.
+//
+// A record is identified by the tool and its version, the rustc version the benchmark was compiled
+// with, the benchmark commit, the cfg configuration (e.g., 'default' or 'feature=foo') and the
+// granularity generic calls were matched at and the layer of calls that was scored (see score.rs).
+// Records are laid out as <store>/<tool>/<tool version>/<rustc version>_<commit>_<cfg>.json, so
+// that the history of a tool can be browsed and diffed with ordinary tools. Records of a match
+// mode or layer other than the default one get the mode or layer appended to their file name.
+//
+// Records of graphs produced by an adapter (see run.rs) also hold the cost of each run. Packages
+// whose run failed have no graph and no score, only the metrics of the run.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::graph::Edge;
+use crate::run::RunMetrics;
+use crate::score::Layer;
+use crate::score::MatchMode;
+use crate::score::PackageScore;
+
+// Bumped whenever the layout of a record changes in an incompatible way.
+pub const FORMAT: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordKey {
+    pub tool: String,
+    pub tool_version: String,
+    pub rustc_version: String,
+    pub commit: String,
+    pub cfg: String,
+    // Records that predate match modes were scored with collapsed instances.
+    #[serde(default)]
+    pub mode: MatchMode,
+    // Records that predate layers were scored on the benchmark layer.
+    #[serde(default)]
+    pub layer: Layer,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackageResult {
+    pub graph: CallGraph,
+    pub score: PackageScore,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Record {
+    pub format: u32,
+    pub key: RecordKey,
+    pub packages: BTreeMap<String, PackageResult>,
+    // Empty unless the graphs were produced by an adapter.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub runs: BTreeMap<String, RunMetrics>,
+}
+
+impl Record {
+    pub fn new(key: RecordKey) -> Self {
+        Record {
+            format: FORMAT,
+            key,
+            packages: BTreeMap::new(),
+            runs: BTreeMap::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Record> {
+        let source = fs::read_to_string(path)
+            .map_err(|e| Error::new(format!("cannot read {}: {}", path.display(), e)))?;
+        let value: serde_json::Value = serde_json::from_str(&source)
+            .map_err(|e| Error::new(format!("{}: {}", path.display(), e)))?;
+        let format = value.get("format").and_then(|format| format.as_u64());
+        if format != Some(FORMAT as u64) {
+            return Err(Error::new(format!(
+                "{}: unsupported record format {:?}, expected {}",
+                path.display(),
+                format,
+                FORMAT
+            )));
+        }
+        serde_json::from_value(value).map_err(|e| Error::new(format!("{}: {}", path.display(), e)))
+    }
+
+    // The union of the call graphs of all packages.
+    pub fn graph(&self) -> CallGraph {
+        let mut graph = CallGraph::default();
+        for result in self.packages.values() {
+            graph.nodes.extend(result.graph.nodes.iter().cloned());
+            graph.edges.extend(result.graph.edges.iter().cloned());
+        }
+        graph
+    }
+}
+
+pub struct Store {
+    dir: PathBuf,
+}
+
+impl Store {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Store { dir: dir.into() }
+    }
+
+    pub fn path_of(&self, key: &RecordKey) -> PathBuf {
+        let rustc = key
+            .rustc_version
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or(&key.rustc_version);
+        let commit: String = key.commit.chars().take(12).collect();
+        let mut file = format!(
+            "{}_{}_{}",
+            sanitize(rustc),
+            sanitize(&commit),
+            sanitize(&key.cfg)
+        );
+        if key.mode != MatchMode::default() {
+            file = format!("{}_{}", file, key.mode.name());
+        }
+        if key.layer != Layer::default() {
+            file = format!("{}_{}", file, key.layer.name());
+        }
+        file.push_str(".json");
+        self.dir
+            .join(sanitize(&key.tool))
+            .join(sanitize(&key.tool_version))
+            .join(file)
+    }
+
+    pub fn save(&self, record: &Record) -> Result<PathBuf> {
+        let path = self.path_of(&record.key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut json = serde_json::to_string_pretty(record)?;
+        json.push('\n');
+        fs::write(&path, json)?;
+        Ok(path)
+    }
+
+    pub fn records(&self) -> Result<Vec<(PathBuf, Record)>> {
+        let mut records = Vec::new();
+        if self.dir.exists() {
+            collect(&self.dir, &mut records)?;
+        }
+        records.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(records)
+    }
+}
+
+fn collect(dir: &Path, records: &mut Vec<(PathBuf, Record)>) -> Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect(&path, records)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let record = Record::load(&path)?;
+            records.push((path, record));
+        }
+    }
+    Ok(())
+}
+
+fn sanitize(component: &str) -> String {
+    component
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+// The differences between two records of a package. Edges missed or reported as spurious only by
+// the newer record are regressions. Packages scored by the newer record only are added, their
+// edges are not compared with anything.
+#[derive(Debug, Default)]
+pub struct PackageDiff {
+    // The package was scored by the older record only.
+    pub dropped: bool,
+    // The package was scored by the newer record only.
+    pub added: bool,
+    pub newly_missed: BTreeSet<Edge>,
+    pub newly_spurious: BTreeSet<Edge>,
+    pub no_longer_missed: BTreeSet<Edge>,
+    pub no_longer_spurious: BTreeSet<Edge>,
+}
+
+impl PackageDiff {
+    pub fn is_regression(&self) -> bool {
+        self.dropped || !self.newly_missed.is_empty() || !self.newly_spurious.is_empty()
+    }
+}
+
+// Records can only be compared if they were scored the same way: the edges of records of different
+// match modes or layers differ even if the tool's graphs do not, and records of different benchmark
+// commits were scored against different truths, whose calls annotated by one commit only would be
+// taken for changes of the tool. Commits given abbreviated match the full ones they abbreviate.
+pub fn compare(old: &Record, new: &Record) -> Result<BTreeMap<String, PackageDiff>> {
+    if old.key.mode != new.key.mode {
+        return Err(Error::new(format!(
+            "cannot compare records of different match modes, {} and {}",
+            old.key.mode.name(),
+            new.key.mode.name()
+        )));
+    }
+    if old.key.layer != new.key.layer {
+        return Err(Error::new(format!(
+            "cannot compare records of different layers, {} and {}",
+            old.key.layer.name(),
+            new.key.layer.name()
+        )));
+    }
+    let (old_commit, new_commit) = (old.key.commit.as_str(), new.key.commit.as_str());
+    if !old_commit.starts_with(new_commit) && !new_commit.starts_with(old_commit) {
+        return Err(Error::new(format!(
+            "cannot compare records of different benchmark commits, {} and {}",
+            old_commit, new_commit
+        )));
+    }
+
+    let empty = PackageScore::default();
+    let packages: BTreeSet<&String> = old.packages.keys().chain(new.packages.keys()).collect();
+    Ok(packages
+        .into_iter()
+        .map(|package| {
+            let old = match old.packages.get(package) {
+                Some(old) => &old.score,
+                None => {
+                    let diff = PackageDiff {
+                        added: true,
+                        ..PackageDiff::default()
+                    };
+                    return (package.clone(), diff);
+                }
+            };
+            let dropped = !new.packages.contains_key(package);
+            let new = new
+                .packages
+                .get(package)
+                .map_or(&empty, |result| &result.score);
+            let diff = PackageDiff {
+                dropped,
+                added: false,
+                newly_missed: new.missed.difference(&old.missed).cloned().collect(),
+                newly_spurious: new.spurious.difference(&old.spurious).cloned().collect(),
+                no_longer_missed: old.missed.difference(&new.missed).cloned().collect(),
+                no_longer_spurious: old.spurious.difference(&new.spurious).cloned().collect(),
+            };
+            (package.clone(), diff)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(mode: MatchMode, layer: Layer) -> RecordKey {
+        RecordKey {
+            tool: "tool".to_string(),
+            tool_version: "1.0.0".to_string(),
+            rustc_version: "rustc 1.80.0".to_string(),
+            commit: "0123456789abcdef".to_string(),
+            cfg: "default".to_string(),
+            mode,
+            layer,
+        }
+    }
+
+    type Edges<'a> = &'a [(&'a str, &'a str)];
+
+    // A record of the default mode and layer scoring each package with the given missed and
+    // spurious edges.
+    fn record(packages: &[(&str, Edges, Edges)]) -> Record {
+        let mut record = Record::new(key(MatchMode::default(), Layer::default()));
+        for (package, missed, spurious) in packages {
+            let edges = |edges: Edges| {
+                edges
+                    .iter()
+                    .map(|(caller, callee)| Edge::new(*caller, *callee))
+                    .collect()
+            };
+            let score = PackageScore {
+                sites: missed.len(),
+                resolved: 0,
+                missed: edges(missed),
+                spurious: edges(spurious),
+            };
+            let result = PackageResult {
+                graph: CallGraph::default(),
+                score,
+            };
+            record.packages.insert(package.to_string(), result);
+        }
+        record
+    }
+
+    #[test]
+    fn compare_lists_the_changed_edges() {
+        let old = record(&[("generics", &[("a", "b"), ("a", "c")], &[("a", "d")])]);
+        let new = record(&[("generics", &[("a", "c"), ("a", "e")], &[])]);
+        let diffs = compare(&old, &new).unwrap();
+        let diff = &diffs["generics"];
+        assert!(diff.is_regression());
+        assert_eq!(diff.newly_missed, BTreeSet::from([Edge::new("a", "e")]));
+        assert!(diff.newly_spurious.is_empty());
+        assert_eq!(diff.no_longer_missed, BTreeSet::from([Edge::new("a", "b")]));
+        assert_eq!(
+            diff.no_longer_spurious,
+            BTreeSet::from([Edge::new("a", "d")])
+        );
+    }
+
+    #[test]
+    fn compare_of_the_same_scores_is_no_regression() {
+        let old = record(&[("generics", &[("a", "b")], &[("a", "d")])]);
+        let diffs = compare(&old, &old.clone()).unwrap();
+        assert!(!diffs["generics"].is_regression());
+    }
+
+    #[test]
+    fn compare_reports_added_packages_without_regression() {
+        let old = record(&[("generics", &[], &[])]);
+        let new = record(&[
+            ("generics", &[], &[]),
+            ("closures", &[("a", "b")], &[("a", "c")]),
+        ]);
+        let diffs = compare(&old, &new).unwrap();
+        let diff = &diffs["closures"];
+        assert!(diff.added);
+        assert!(!diff.is_regression());
+        assert!(diff.newly_missed.is_empty() && diff.newly_spurious.is_empty());
+    }
+
+    #[test]
+    fn compare_reports_dropped_packages_as_regression() {
+        let old = record(&[("generics", &[], &[]), ("closures", &[], &[])]);
+        let new = record(&[("generics", &[], &[])]);
+        let diffs = compare(&old, &new).unwrap();
+        assert!(diffs["closures"].dropped);
+        assert!(diffs["closures"].is_regression());
+    }
+
+    #[test]
+    fn compare_refuses_records_scored_differently() {
+        let old = record(&[("generics", &[], &[])]);
+        let mut new = old.clone();
+        new.key = key(MatchMode::Instance, Layer::default());
+        assert!(compare(&old, &new).is_err());
+        new.key = key(MatchMode::default(), Layer::Std);
+        assert!(compare(&old, &new).is_err());
+    }
+
+    #[test]
+    fn compare_refuses_records_of_different_benchmark_commits() {
+        // A call annotated by the newer commit only is missed by the newer record, which is no
+        // regression of the tool.
+        let old = record(&[("generics", &[], &[])]);
+        let mut new = record(&[("generics", &[("a", "b")], &[])]);
+        new.key.commit = "fedcba9876543210".to_string();
+        let error = compare(&old, &new).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "cannot compare records of different benchmark commits, 0123456789abcdef and \
+             fedcba9876543210"
+        );
+
+        new.key.commit = "0123456".to_string();
+        assert!(compare(&old, &new).is_ok());
+    }
+}
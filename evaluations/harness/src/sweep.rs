@@ -0,0 +1,302 @@
+// Sweeps of the build configuration of the 'main' package for IR-level call graphs.
+//
+// Tools that analyze LLVM IR see the calls that survived optimization. At opt-level=2 and above,
+// calls like structs::lib::Two::add_one are inlined into their callers and LTO may devirtualize
+// calls like the one in dynamic_dispatch::lib::dynamic, so a single build configuration tells
+// little about what such a tool can find. A sweep builds 'main' in each configuration with debug
+// info and codegen-units=1, takes the graph of the direct calls of the emitted IR and scores it,
+// as well as the graphs of a tool that its adapter (see run.rs) produces in the configuration.
+// The calls that a graph misses are told apart by the inline records of the debug info (see
+// llvm.rs): a call was inlined away if an instruction inlined at the call's location, or one in
+// the function that the caller was inlined into, stands for it, and missed otherwise.
+//
+// The graph is read from the IR by the harness rather than by 'opt -dot-callgraph', whose LLVM
+// version must match rustc's; both report the direct calls of each function.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::graph::Edge;
+use crate::index::Index;
+use crate::llvm;
+use crate::llvm::Call;
+use crate::llvm::Resolution;
+use crate::names::Normalizer;
+use crate::packages;
+use crate::score;
+use crate::score::Layer;
+use crate::score::MatchMode;
+use crate::score::PackageScore;
+use crate::tags::Filter;
+use crate::truth::Truth;
+
+pub const OPT_LEVELS: &[&str] = &["0", "1", "2", "3", "s", "z"];
+pub const LTO: &[&str] = &["off", "thin", "fat"];
+
+// The calls of a sweep are matched with collapsed instances.
+pub const MODE: MatchMode = MatchMode::Collapsed;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct Config {
+    pub opt_level: String,
+    pub lto: String,
+}
+
+impl Config {
+    // The configurations of the given comma separated opt-levels and LTO modes.
+    pub fn parse(opt_levels: &str, lto: &str) -> Result<Vec<Config>> {
+        let opt_levels = list(opt_levels, OPT_LEVELS, "opt-level")?;
+        let lto = list(lto, LTO, "LTO mode")?;
+        Ok(opt_levels
+            .iter()
+            .flat_map(|opt_level| {
+                lto.iter().map(move |lto| Config {
+                    opt_level: opt_level.to_string(),
+                    lto: lto.to_string(),
+                })
+            })
+            .collect())
+    }
+
+    pub fn name(&self) -> String {
+        format!("opt-level={} lto={}", self.opt_level, self.lto)
+    }
+
+    // Builds 'main' in the configuration, in a target directory of its own below 'target_dir',
+    // and returns the LLVM IR files of the benchmark crates. With LTO, those are the modules that
+    // went through LTO, all of which belong to 'main'.
+    pub fn build(&self, root: &Path, target_dir: &Path) -> Result<Vec<PathBuf>> {
+        let target_dir = target_dir.join(format!("opt-level-{}-lto-{}", self.opt_level, self.lto));
+        let main = packages::find("main").expect("main is a benchmark package");
+        let output = Command::new("cargo")
+            .args(["build", "--release", "--target-dir"])
+            .arg(&target_dir)
+            .current_dir(root.join(&main.dir))
+            .env("CARGO_INCREMENTAL", "0")
+            .envs(self.profile())
+            .env("RUSTFLAGS", "--emit=llvm-ir,link")
+            .output()
+            .map_err(|e| Error::new(format!("cannot run cargo: {}", e)))?;
+        if !output.status.success() {
+            return Err(Error::new(format!(
+                "building main with {} failed:\n{}",
+                self.name(),
+                String::from_utf8_lossy(&output.stderr)
+            )));
+        }
+
+        let mut files = Vec::new();
+        for entry in fs::read_dir(target_dir.join("release").join("deps"))? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|extension| extension != "ll") {
+                continue;
+            }
+            // <crate>-<hash>.ll, or <crate>-<hash>.<module>.ll for the modules of thin LTO.
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            let crate_name = name.split('-').next().unwrap_or_default();
+            let selected = match self.lto.as_str() {
+                "off" => packages::is_benchmark_crate(crate_name),
+                _ => crate_name == "main",
+            };
+            if selected {
+                files.push(path);
+            }
+        }
+        files.sort();
+        if files.is_empty() {
+            return Err(Error::new(format!(
+                "building main with {} emitted no LLVM IR",
+                self.name()
+            )));
+        }
+        Ok(files)
+    }
+
+    // The release profile of the configuration, as the environment of cargo.
+    fn profile(&self) -> Vec<(String, String)> {
+        vec![
+            env("CARGO_PROFILE_RELEASE_OPT_LEVEL", &self.opt_level),
+            env("CARGO_PROFILE_RELEASE_LTO", &self.lto),
+            env("CARGO_PROFILE_RELEASE_CODEGEN_UNITS", "1"),
+            env("CARGO_PROFILE_RELEASE_DEBUG", "2"),
+        ]
+    }
+
+    // The environment of the adapter runs of the configuration: its opt-level and LTO mode, the
+    // IR files of 'main' built in it, separated by spaces, and its release profile, so that an
+    // adapter that builds the package with 'cargo build --release' builds it in the
+    // configuration.
+    pub fn adapter_env(&self, files: &[PathBuf]) -> Vec<(String, String)> {
+        let files: Vec<String> = files
+            .iter()
+            .map(|file| file.to_string_lossy().into_owned())
+            .collect();
+        let mut env = vec![
+            env("HARNESS_OPT_LEVEL", &self.opt_level),
+            env("HARNESS_LTO", &self.lto),
+            env("HARNESS_LLVM_IR", &files.join(" ")),
+        ];
+        env.extend(self.profile());
+        env
+    }
+}
+
+fn env(name: &str, value: &str) -> (String, String) {
+    (name.to_string(), value.to_string())
+}
+
+fn list<'a>(text: &'a str, known: &[&str], what: &str) -> Result<Vec<&'a str>> {
+    text.split(',')
+        .map(|item| {
+            if known.contains(&item) {
+                Ok(item)
+            } else {
+                Err(Error::new(format!(
+                    "unknown {} '{}', expected one of {}",
+                    what,
+                    item,
+                    known.join(", ")
+                )))
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct SweepScore {
+    pub sites: usize,
+    // The calls whose edges are in the graph of the direct calls.
+    pub resolved: usize,
+    // The calls without an edge that were inlined away: the target was inlined at the call, or
+    // the caller into another function, which now holds the call.
+    pub inlined: usize,
+    // The calls without an edge that left no trace in the IR, e.g., they were folded away.
+    pub missed: usize,
+}
+
+impl SweepScore {
+    pub fn percentage(&self) -> f64 {
+        if self.sites == 0 {
+            100.0
+        } else {
+            100.0 * self.resolved as f64 / self.sites as f64
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConfigScores {
+    pub config: Config,
+    // The scores of each graph by package, under the name of its tool. The graph of the direct
+    // calls of the IR is scored as the tool 'ir'.
+    pub tools: BTreeMap<String, BTreeMap<String, SweepScore>>,
+}
+
+// The name under which the graph of the direct calls of the IR is scored.
+pub const IR_TOOL: &str = "ir";
+
+// The calls of the IR files of a configuration.
+pub fn read_ir(files: &[PathBuf]) -> Result<Vec<Call>> {
+    let mut calls = Vec::new();
+    for file in files {
+        let source = fs::read_to_string(file)
+            .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+        calls.extend(
+            llvm::parse(&source).map_err(|e| Error::new(format!("{}: {}", file.display(), e)))?,
+        );
+    }
+    Ok(calls)
+}
+
+// The graph of the direct calls of the IR.
+pub fn ir_graph(calls: &[Call], normalizer: &Normalizer) -> CallGraph {
+    MODE.graph(CallGraph::from_dot(&llvm::call_graph(calls), normalizer))
+}
+
+// Scores the graph of a tool for a package. The calls that the graph misses are told apart by the
+// IR of the configuration: a call was inlined away if the target was inlined at it, or if only the
+// functions that the caller was inlined into still call the target.
+pub fn evaluate(
+    package: &str,
+    graph: &CallGraph,
+    calls: &[Call],
+    truth: &Truth,
+    index: &Index,
+    normalizer: &Normalizer,
+    filter: &Filter,
+) -> (PackageScore, SweepScore) {
+    let score = score::score(package, truth, index, graph, filter, MODE, Layer::Benchmark);
+    let mut result = SweepScore {
+        sites: score.sites,
+        resolved: score.resolved,
+        ..SweepScore::default()
+    };
+    for resolution in llvm::resolve(package, truth, index, normalizer, calls, filter) {
+        let site = resolution.site;
+        let missed = site.layer_targets(Layer::Benchmark).iter().any(|target| {
+            score
+                .missed
+                .contains(&Edge::new(site.caller.as_str(), target.as_str()))
+        });
+        if !missed {
+            continue;
+        }
+        let inlined = match resolution.resolution {
+            Resolution::Inlined => true,
+            Resolution::Called => !resolution.functions.contains(&site.caller),
+            Resolution::Missed => false,
+        };
+        if inlined {
+            result.inlined += 1;
+        } else {
+            result.missed += 1;
+        }
+    }
+    (score, result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_configurations_of_a_sweep() {
+        let configs = Config::parse("0,3", "off,fat").unwrap();
+        let names: Vec<String> = configs.iter().map(Config::name).collect();
+        assert_eq!(
+            names,
+            [
+                "opt-level=0 lto=off",
+                "opt-level=0 lto=fat",
+                "opt-level=3 lto=off",
+                "opt-level=3 lto=fat",
+            ]
+        );
+        assert!(Config::parse("4", "off").is_err());
+        assert!(Config::parse("0", "full").is_err());
+    }
+
+    #[test]
+    fn gives_adapters_the_configuration_and_its_ir() {
+        let config = &Config::parse("s", "thin").unwrap()[0];
+        let env = config.adapter_env(&[PathBuf::from("a.ll"), PathBuf::from("b.ll")]);
+        let value = |name: &str| {
+            env.iter()
+                .find(|(variable, _)| variable == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(value("HARNESS_OPT_LEVEL"), Some("s"));
+        assert_eq!(value("HARNESS_LTO"), Some("thin"));
+        assert_eq!(value("HARNESS_LLVM_IR"), Some("a.ll b.ll"));
+        assert_eq!(value("CARGO_PROFILE_RELEASE_OPT_LEVEL"), Some("s"));
+        assert_eq!(value("CARGO_PROFILE_RELEASE_CODEGEN_UNITS"), Some("1"));
+    }
+}
@@ -0,0 +1,1157 @@
+// Taxonomy of the annotated calls.
+//
+// The kind of a call (see truth.rs) is the coarse category its annotation gives. In addition, each
+// call site is tagged along several axes that are read off the call expression, the function that
+// encloses it and the index, so that scores can be sliced by any combination of them, e.g., UFCS
+// calls on trait objects across crates:
+//
+//     harness score --filter syntax=ufcs,dispatch=dynamic,cross_crate <package>=<dot file>...
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use proc_macro2::Span;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use syn::spanned::Spanned;
+use syn::visit::Visit;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::index::strip_generics;
+use crate::index::FunctionKind;
+use crate::index::Index;
+use crate::names::crates_of;
+use crate::names::split_path;
+use crate::truth::CallKind;
+use crate::truth::CallSite;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Dispatch {
+    Static,
+    // The callee depends on the type of a trait object.
+    Dynamic,
+    FunctionPointer,
+    FnTrait,
+    // The callee is the poll method of an awaited future or of an executor's root future.
+    Await,
+    // The callees are the Drop implementations that the drop glue of a type runs.
+    Drop,
+}
+
+// The form of the receiver of a method call, or of the first argument of a UFCS call, as written
+// at the call site. Calls of functions without a 'self' parameter have no receiver.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Receiver {
+    None,
+    Value,
+    Ref,
+    RefMut,
+    Boxed,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Syntax {
+    // f(x), Type::f(x) and calls of function pointers and closures.
+    Function,
+    // x.method()
+    Method,
+    // Trait::method(x) or Type::method(x)
+    Ufcs,
+    // <Type as Trait>::method(x)
+    Qualified,
+    // x.await, which calls the poll method of future x.
+    Await,
+    // An operator expression, e.g., a + b, -a, a[i] or a += b, which calls the method of the
+    // operator's trait on its (left) operand.
+    Operator,
+    // The implicit deref of the receiver of a method call, e.g., p.m() on a smart pointer p,
+    // which calls Deref::deref or DerefMut::deref_mut.
+    AutoDeref,
+    // The drop of a value, e.g., at the end of its scope, which no expression of the line makes.
+    Drop,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Tags {
+    pub dispatch: Dispatch,
+    // The call targets a function of another crate.
+    pub cross_crate: bool,
+    // The call involves a generic instantiation: the target or the receiver is generic.
+    pub generic: bool,
+    pub receiver: Receiver,
+    pub syntax: Syntax,
+    // The call is part of a macro definition and is expanded at the macro's invocations.
+    pub macro_expanded: bool,
+    // The call or its target is conditionally compiled.
+    pub cfg_gated: bool,
+}
+
+// The axes of the taxonomy and their values.
+pub const AXES: &[(&str, &[&str])] = &[
+    (
+        "dispatch",
+        &[
+            "static",
+            "dynamic",
+            "function_pointer",
+            "fn_trait",
+            "await",
+            "drop",
+        ],
+    ),
+    ("cross_crate", &["true", "false"]),
+    ("generic", &["true", "false"]),
+    ("receiver", &["none", "value", "ref", "ref_mut", "boxed"]),
+    (
+        "syntax",
+        &[
+            "function",
+            "method",
+            "ufcs",
+            "qualified",
+            "await",
+            "operator",
+            "auto_deref",
+            "drop",
+        ],
+    ),
+    ("macro_expanded", &["true", "false"]),
+    ("cfg_gated", &["true", "false"]),
+];
+
+impl Tags {
+    pub fn value(&self, axis: &str) -> String {
+        let value = match axis {
+            "dispatch" => serde_json::to_value(self.dispatch),
+            "cross_crate" => serde_json::to_value(self.cross_crate),
+            "generic" => serde_json::to_value(self.generic),
+            "receiver" => serde_json::to_value(self.receiver),
+            "syntax" => serde_json::to_value(self.syntax),
+            "macro_expanded" => serde_json::to_value(self.macro_expanded),
+            "cfg_gated" => serde_json::to_value(self.cfg_gated),
+            _ => return String::new(),
+        };
+        match value {
+            Ok(serde_json::Value::String(value)) => value,
+            Ok(value) => value.to_string(),
+            Err(_) => String::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Tags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let values: Vec<String> = AXES
+            .iter()
+            .map(|(axis, _)| format!("{}={}", axis, self.value(axis)))
+            .collect();
+        write!(f, "{}", values.join(" "))
+    }
+}
+
+// A conjunction of tag values, e.g., 'syntax=ufcs,dispatch=dynamic,cross_crate,!generic'. Boolean
+// axes may be given by name alone, or negated with '!'.
+#[derive(Debug, Default)]
+pub struct Filter {
+    terms: Vec<(String, String)>,
+}
+
+impl Filter {
+    pub fn parse(text: &str) -> Result<Filter> {
+        let mut filter = Filter::default();
+        for term in text
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+        {
+            let (axis, value) = match term.split_once('=') {
+                Some((axis, value)) => (axis.trim(), value.trim()),
+                None => match term.strip_prefix('!') {
+                    Some(axis) => (axis, "false"),
+                    None => (term, "true"),
+                },
+            };
+            let values = match AXES.iter().find(|(name, _)| *name == axis) {
+                Some((_, values)) => values,
+                None => return Err(Error::new(format!("unknown tag '{}'", axis))),
+            };
+            if !values.contains(&value) {
+                return Err(Error::new(format!(
+                    "unknown value '{}' of tag '{}', expected one of: {}",
+                    value,
+                    axis,
+                    values.join(", ")
+                )));
+            }
+            filter.terms.push((axis.to_string(), value.to_string()));
+        }
+        Ok(filter)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    // Sites without tags, i.e., read from a manifest that predates them, match the empty filter
+    // only.
+    pub fn matches(&self, site: &CallSite) -> bool {
+        match &site.tags {
+            Some(tags) => self
+                .terms
+                .iter()
+                .all(|(axis, value)| tags.value(axis) == *value),
+            None => self.is_empty(),
+        }
+    }
+}
+
+// Tags the call sites of the ground truth. Source files are parsed once and shared by the sites
+// they contain.
+pub struct Tagger<'a> {
+    root: &'a Path,
+    index: &'a Index,
+    files: HashMap<PathBuf, (Vec<String>, syn::File)>,
+}
+
+impl<'a> Tagger<'a> {
+    pub fn new(root: &'a Path, index: &'a Index) -> Self {
+        Tagger {
+            root,
+            index,
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn tag(&mut self, site: &CallSite) -> Result<Tags> {
+        let index = self.index;
+        let caller = match site.invocation {
+            None => index.function_at(&site.file, site.line),
+            Some(_) => index.enclosing_function(&site.caller),
+        };
+        let scope = caller.map(|caller| index.function_scope(caller));
+        let caller = caller.map(|caller| &index.functions[caller]);
+        let targets: Vec<_> = site
+            .targets
+            .iter()
+            .filter_map(|target| index.function(target))
+            .collect();
+
+        // The function enclosing the call gives the types of the variables used at the call.
+        let mut context = Context::default();
+        if let Some(caller) = caller {
+            let (_, ast) = self.file(&caller.file)?;
+            if let Some((sig, block)) = find_function(ast, caller.start) {
+                let in_trait = caller.kind == FunctionKind::TraitMethod;
+                // The type that a receiver taken by value has in an implementation, e.g.,
+                // &dyn operators::lib::Weigh.
+                let self_ty = match caller.kind {
+                    FunctionKind::Inherent | FunctionKind::TraitImpl => scope
+                        .map(|scope| index.resolve(scope, &["Self".to_string()]))
+                        .and_then(|self_ty| syn::parse_str::<syn::Type>(&self_ty).ok()),
+                    _ => None,
+                };
+                context = Context::of(sig, block, scope.unwrap_or(0), in_trait, self_ty);
+            }
+        }
+
+        // Calls inside macro definitions are tagged after substituting the macro's parameters by
+        // the first argument of the invocation.
+        let statement = match site.invocation {
+            None => {
+                let (lines, ast) = self.file(&site.file)?;
+                let calls = match find_function(ast, caller.map_or(0, |caller| caller.start)) {
+                    Some((_, block)) => calls_at(block, site.line),
+                    None => Vec::new(),
+                };
+                if calls.is_empty() {
+                    statement_at(lines, site.line, None)
+                } else {
+                    Some(Statement {
+                        calls,
+                        cfg_gated: false,
+                    })
+                }
+            }
+            Some(line) => {
+                let argument = caller
+                    .and_then(|caller| {
+                        index.invocations.iter().find(|i| {
+                            i.line == line && index.functions[i.caller].name == caller.name
+                        })
+                    })
+                    .and_then(|invocation| {
+                        let text = invocation.tokens.to_string();
+                        let first = crate::names::split_top_level(&text, ",")
+                            .map(|(first, _)| first.to_string())
+                            .unwrap_or(text);
+                        Some(first.trim().to_string()).filter(|first| !first.is_empty())
+                    });
+                let (lines, _) = self.file(&site.file)?;
+                statement_at(lines, site.line, argument.as_deref())
+            }
+        };
+        let statement = statement.unwrap_or_default();
+        let call = match site.kind {
+            CallKind::Drop => None,
+            _ => select(&statement.calls, site),
+        };
+
+        // Targets that are missing from the index, e.g., of a misspelled annotation, are told by
+        // the callee's path: Trait::method(x) calls a trait's method on receiver x.
+        let path_calls_trait_method = match call {
+            Some(Call::Function(call)) if !call.args.is_empty() => match &*call.func {
+                syn::Expr::Path(path) if path.qself.is_none() && path.path.segments.len() > 1 => {
+                    let segments = &path.path.segments;
+                    let prefix: Vec<String> = segments
+                        .iter()
+                        .take(segments.len() - 1)
+                        .map(|s| s.ident.to_string())
+                        .collect();
+                    index.is_trait(&index.resolve(scope.unwrap_or(0), &prefix))
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+        let target_has_receiver = targets.iter().any(|target| target.receiver)
+            || (targets.is_empty() && path_calls_trait_method);
+        let syntax = match call {
+            Some(Call::Method(_)) => Syntax::Method,
+            Some(Call::Await(_)) => Syntax::Await,
+            Some(Call::Operator(_)) => Syntax::Operator,
+            Some(Call::AutoDeref(_)) => Syntax::AutoDeref,
+            Some(Call::Function(call)) => match &*call.func {
+                syn::Expr::Path(path) if path.qself.is_some() => Syntax::Qualified,
+                syn::Expr::Path(path) if path.path.segments.len() > 1 && target_has_receiver => {
+                    Syntax::Ufcs
+                }
+                _ => Syntax::Function,
+            },
+            None if site.kind == CallKind::Drop => Syntax::Drop,
+            None => Syntax::Function,
+        };
+
+        let operand = match (call, syntax) {
+            (Some(Call::Method(call)), _) => context.operand(index, &call.receiver, 0),
+            (Some(Call::Await(call)), _) => context.operand(index, &call.base, 0),
+            (Some(Call::Operator(call)), _) | (Some(Call::AutoDeref(call)), _) => {
+                context.operand(index, &call.operand, 0)
+            }
+            (Some(Call::Function(call)), Syntax::Ufcs | Syntax::Qualified)
+                if target_has_receiver =>
+            {
+                match call.args.first() {
+                    Some(arg) => context.operand(index, arg, 0),
+                    None => Operand::default(),
+                }
+            }
+            _ => Operand::default(),
+        };
+        let method_syntax = matches!(
+            syntax,
+            Syntax::Method | Syntax::Await | Syntax::Operator | Syntax::AutoDeref
+        );
+        let receiver = if matches!(syntax, Syntax::Function | Syntax::Drop)
+            || (!method_syntax && !target_has_receiver)
+        {
+            Receiver::None
+        } else {
+            operand.form
+        };
+
+        let calls_trait_method = targets
+            .iter()
+            .any(|target| target.kind == FunctionKind::TraitMethod)
+            || (targets.is_empty() && path_calls_trait_method);
+        let dispatch = match site.kind {
+            CallKind::FunctionPointer => Dispatch::FunctionPointer,
+            CallKind::FnTrait | CallKind::FnMutTrait | CallKind::FnOnceTrait => Dispatch::FnTrait,
+            CallKind::FuturePoll => Dispatch::Await,
+            CallKind::Drop => Dispatch::Drop,
+            // Operators of trait objects call the implementation for the trait object type.
+            _ if operand.pointee == Pointee::Dyn && calls_trait_method => Dispatch::Dynamic,
+            _ => Dispatch::Static,
+        };
+
+        // Instance calls of a trait's method, rather than of an implementation's, that are not
+        // dispatched dynamically have a generic receiver, e.g., 'self.0.method()' in Wrapper<T>.
+        let generic_receiver = site.kind == CallKind::InstanceTrait
+            && calls_trait_method
+            && dispatch == Dispatch::Static
+            && operand.pointee != Pointee::Concrete;
+        let generic = operand.pointee == Pointee::Param
+            || generic_receiver
+            || targets.iter().any(|target| target.generic)
+            || site.annotation.contains("::<")
+            || site.kind == CallKind::StaticMonomorphized
+            || site.kind == CallKind::StaticInherentMonomorphized;
+
+        let cross_crate = site
+            .targets
+            .iter()
+            .any(|target| !crates_of(target).contains(&site.package.as_str()));
+
+        let cfg_gated = statement.cfg_gated
+            || caller.is_some_and(|caller| caller.cfg_gated)
+            || index
+                .functions
+                .iter()
+                .any(|function| function.cfg_gated && site.targets.contains(&function.name));
+
+        Ok(Tags {
+            dispatch,
+            cross_crate,
+            generic,
+            receiver,
+            syntax,
+            macro_expanded: site.invocation.is_some(),
+            cfg_gated,
+        })
+    }
+
+    fn file(&mut self, file: &Path) -> Result<&(Vec<String>, syn::File)> {
+        if !self.files.contains_key(file) {
+            let source = fs::read_to_string(self.root.join(file))
+                .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+            let ast = syn::parse_file(&source)
+                .map_err(|e| Error::new(format!("cannot parse {}: {}", file.display(), e)))?;
+            let lines = source.lines().map(str::to_string).collect();
+            self.files.insert(file.to_path_buf(), (lines, ast));
+        }
+        Ok(&self.files[file])
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Call<'ast> {
+    Function(&'ast syn::ExprCall),
+    Method(&'ast syn::ExprMethodCall),
+    Await(&'ast syn::ExprAwait),
+    Operator(&'ast Operator),
+    AutoDeref(&'ast Operator),
+}
+
+impl Call<'_> {
+    fn name(&self) -> Option<String> {
+        match self {
+            Call::Function(call) => match &*call.func {
+                syn::Expr::Path(path) => path.path.segments.last().map(|s| s.ident.to_string()),
+                _ => None,
+            },
+            Call::Method(call) => Some(call.method.to_string()),
+            Call::Await(_) => Some("poll".to_string()),
+            Call::Operator(call) | Call::AutoDeref(call) => Some(call.method.to_string()),
+        }
+    }
+}
+
+// The calls of a statement, owned, as statements inside macro definitions are parsed on demand.
+#[derive(Default)]
+struct Statement {
+    calls: Vec<OwnedCall>,
+    cfg_gated: bool,
+}
+
+enum OwnedCall {
+    Function(syn::ExprCall),
+    Method(syn::ExprMethodCall),
+    Await(syn::ExprAwait),
+    Operator(Operator),
+    AutoDeref(Operator),
+}
+
+// An operator expression, or the implicit deref of a receiver: the method of the trait that it
+// calls and its (left) operand.
+struct Operator {
+    method: &'static str,
+    operand: syn::Expr,
+}
+
+impl OwnedCall {
+    fn borrow(&self) -> Call<'_> {
+        match self {
+            OwnedCall::Function(call) => Call::Function(call),
+            OwnedCall::Method(call) => Call::Method(call),
+            OwnedCall::Await(call) => Call::Await(call),
+            OwnedCall::Operator(call) => Call::Operator(call),
+            OwnedCall::AutoDeref(call) => Call::AutoDeref(call),
+        }
+    }
+}
+
+// The call that an annotation documents: the first call on the annotated line whose callee is named
+// by the annotation, e.g., 'add_one' in 'self.add_one().method_2()', or the first call otherwise.
+fn select<'s>(calls: &'s [OwnedCall], site: &CallSite) -> Option<Call<'s>> {
+    let named = |call: &Call| {
+        call.name().is_some_and(|name| {
+            site.targets
+                .iter()
+                .chain(std::iter::once(&site.annotation))
+                .any(|target| split_path(&strip_generics(target)).last() == Some(&name))
+        })
+    };
+    let calls: Vec<Call> = calls.iter().map(OwnedCall::borrow).collect();
+    calls
+        .iter()
+        .find(|call| named(call))
+        .or(calls.first())
+        .copied()
+}
+
+// Finds the signature and body of the function whose definition starts at the given line.
+fn find_function(ast: &syn::File, start: usize) -> Option<(&syn::Signature, &syn::Block)> {
+    struct Finder<'ast> {
+        start: usize,
+        found: Option<(&'ast syn::Signature, &'ast syn::Block)>,
+    }
+
+    impl<'ast> Visit<'ast> for Finder<'ast> {
+        fn visit_item_fn(&mut self, item: &'ast syn::ItemFn) {
+            if item.span().start().line == self.start {
+                self.found.get_or_insert((&item.sig, &item.block));
+            }
+            syn::visit::visit_item_fn(self, item);
+        }
+
+        fn visit_impl_item_fn(&mut self, item: &'ast syn::ImplItemFn) {
+            if item.span().start().line == self.start {
+                self.found.get_or_insert((&item.sig, &item.block));
+            }
+            syn::visit::visit_impl_item_fn(self, item);
+        }
+
+        fn visit_trait_item_fn(&mut self, item: &'ast syn::TraitItemFn) {
+            if let (true, Some(block)) = (item.span().start().line == self.start, &item.default) {
+                self.found.get_or_insert((&item.sig, block));
+            }
+            syn::visit::visit_trait_item_fn(self, item);
+        }
+    }
+
+    let mut finder = Finder { start, found: None };
+    finder.visit_file(ast);
+    finder.found
+}
+
+// The calls of a function body that start on the given line, outermost first.
+fn calls_at(block: &syn::Block, line: usize) -> Vec<OwnedCall> {
+    struct Calls {
+        line: usize,
+        calls: Vec<OwnedCall>,
+        // Whether the expression being visited is a place that is mutated, e.g., the left operand
+        // of an assignment, whose indexing calls IndexMut::index_mut rather than Index::index.
+        mutable: bool,
+    }
+
+    impl Calls {
+        fn operator(&mut self, span: Span, method: Option<&'static str>, operand: &syn::Expr) {
+            if let (true, Some(method)) = (span.start().line == self.line, method) {
+                self.calls.push(OwnedCall::Operator(Operator {
+                    method,
+                    operand: operand.clone(),
+                }));
+            }
+        }
+
+        fn visit_place(&mut self, expr: &syn::Expr) {
+            let mutable = std::mem::replace(&mut self.mutable, true);
+            self.visit_expr(expr);
+            self.mutable = mutable;
+        }
+    }
+
+    impl<'ast> Visit<'ast> for Calls {
+        fn visit_expr_call(&mut self, call: &'ast syn::ExprCall) {
+            if call.span().start().line == self.line {
+                self.calls.push(OwnedCall::Function(call.clone()));
+            }
+            syn::visit::visit_expr_call(self, call);
+        }
+
+        fn visit_expr_method_call(&mut self, call: &'ast syn::ExprMethodCall) {
+            let on_line = call.span().start().line == self.line;
+            if on_line {
+                self.calls.push(OwnedCall::Method(call.clone()));
+            }
+            syn::visit::visit_expr_method_call(self, call);
+            // The receiver may be dereferenced implicitly, by either trait, which only the
+            // annotation tells. The calls of the receiver itself, e.g., an explicit *p, come first.
+            if on_line {
+                for method in ["deref", "deref_mut"] {
+                    self.calls.push(OwnedCall::AutoDeref(Operator {
+                        method,
+                        operand: (*call.receiver).clone(),
+                    }));
+                }
+            }
+        }
+
+        fn visit_expr_await(&mut self, call: &'ast syn::ExprAwait) {
+            if call.span().start().line == self.line {
+                self.calls.push(OwnedCall::Await(call.clone()));
+            }
+            syn::visit::visit_expr_await(self, call);
+        }
+
+        fn visit_expr_binary(&mut self, expr: &'ast syn::ExprBinary) {
+            let method = binary_method(&expr.op);
+            self.operator(expr.span(), method, &expr.left);
+            if method.is_some_and(|method| method.ends_with("_assign")) {
+                self.visit_place(&expr.left);
+            } else {
+                self.visit_expr(&expr.left);
+            }
+            self.visit_expr(&expr.right);
+        }
+
+        fn visit_expr_unary(&mut self, expr: &'ast syn::ExprUnary) {
+            let method = match expr.op {
+                syn::UnOp::Deref(_) if self.mutable => Some("deref_mut"),
+                syn::UnOp::Deref(_) => Some("deref"),
+                syn::UnOp::Not(_) => Some("not"),
+                syn::UnOp::Neg(_) => Some("neg"),
+                _ => None,
+            };
+            self.operator(expr.span(), method, &expr.expr);
+            self.visit_expr(&expr.expr);
+        }
+
+        fn visit_expr_index(&mut self, expr: &'ast syn::ExprIndex) {
+            let method = if self.mutable { "index_mut" } else { "index" };
+            self.operator(expr.span(), Some(method), &expr.expr);
+            self.visit_expr(&expr.expr);
+            let mutable = std::mem::replace(&mut self.mutable, false);
+            self.visit_expr(&expr.index);
+            self.mutable = mutable;
+        }
+
+        fn visit_expr_assign(&mut self, expr: &'ast syn::ExprAssign) {
+            self.visit_place(&expr.left);
+            self.visit_expr(&expr.right);
+        }
+
+        fn visit_expr_reference(&mut self, expr: &'ast syn::ExprReference) {
+            if expr.mutability.is_some() {
+                self.visit_place(&expr.expr);
+            } else {
+                syn::visit::visit_expr_reference(self, expr);
+            }
+        }
+    }
+
+    let mut calls = Calls {
+        line,
+        calls: Vec::new(),
+        mutable: false,
+    };
+    calls.visit_block(block);
+    calls.calls
+}
+
+// The method of the trait of a binary operator, None for the operators that call no method, i.e.,
+// the lazy boolean operators.
+fn binary_method(op: &syn::BinOp) -> Option<&'static str> {
+    let method = match op {
+        syn::BinOp::Add(_) => "add",
+        syn::BinOp::Sub(_) => "sub",
+        syn::BinOp::Mul(_) => "mul",
+        syn::BinOp::Div(_) => "div",
+        syn::BinOp::Rem(_) => "rem",
+        syn::BinOp::BitXor(_) => "bitxor",
+        syn::BinOp::BitAnd(_) => "bitand",
+        syn::BinOp::BitOr(_) => "bitor",
+        syn::BinOp::Shl(_) => "shl",
+        syn::BinOp::Shr(_) => "shr",
+        syn::BinOp::Eq(_) => "eq",
+        syn::BinOp::Lt(_) => "lt",
+        syn::BinOp::Le(_) => "le",
+        syn::BinOp::Ne(_) => "ne",
+        syn::BinOp::Ge(_) => "ge",
+        syn::BinOp::Gt(_) => "gt",
+        syn::BinOp::AddAssign(_) => "add_assign",
+        syn::BinOp::SubAssign(_) => "sub_assign",
+        syn::BinOp::MulAssign(_) => "mul_assign",
+        syn::BinOp::DivAssign(_) => "div_assign",
+        syn::BinOp::RemAssign(_) => "rem_assign",
+        syn::BinOp::BitXorAssign(_) => "bitxor_assign",
+        syn::BinOp::BitAndAssign(_) => "bitand_assign",
+        syn::BinOp::BitOrAssign(_) => "bitor_assign",
+        syn::BinOp::ShlAssign(_) => "shl_assign",
+        syn::BinOp::ShrAssign(_) => "shr_assign",
+        _ => return None,
+    };
+    Some(method)
+}
+
+// Parses the statement that starts at the given line of a source file, substituting macro
+// parameters ($x) by 'argument'. The statement may span several lines.
+fn statement_at(lines: &[String], line: usize, argument: Option<&str>) -> Option<Statement> {
+    let mut text = String::new();
+    for line in lines.iter().skip(line - 1).take(8) {
+        text.push_str(line);
+        text.push('\n');
+        let source = match argument {
+            Some(argument) => substitute(&text, argument),
+            None => text.clone(),
+        };
+        let block = match syn::parse_str::<syn::Block>(&format!("{{ {} }}", source)) {
+            Ok(block) => block,
+            Err(_) => continue,
+        };
+        let cfg_gated = block.stmts.iter().any(|stmt| match stmt {
+            syn::Stmt::Local(local) => has_cfg(&local.attrs),
+            syn::Stmt::Macro(mac) => has_cfg(&mac.attrs),
+            _ => false,
+        });
+        let calls = calls_at(&block, 1);
+        if !calls.is_empty() {
+            return Some(Statement { calls, cfg_gated });
+        }
+    }
+    None
+}
+
+fn substitute(text: &str, argument: &str) -> String {
+    let mut substituted = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '$' && chars.peek().is_some_and(|c| c.is_alphabetic() || *c == '_') {
+            while chars
+                .peek()
+                .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+            {
+                chars.next();
+            }
+            substituted.push('(');
+            substituted.push_str(argument);
+            substituted.push(')');
+        } else {
+            substituted.push(c);
+        }
+    }
+    substituted
+}
+
+fn has_cfg(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().any(|attr| attr.path().is_ident("cfg"))
+}
+
+// What a receiver points to, as far as it can be told without type inference.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Pointee {
+    #[default]
+    Unknown,
+    Concrete,
+    // A type parameter, an 'impl Trait' type or Self inside a trait.
+    Param,
+    // A trait object.
+    Dyn,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Operand {
+    form: Receiver,
+    pointee: Pointee,
+}
+
+impl Default for Operand {
+    fn default() -> Self {
+        Operand {
+            form: Receiver::Value,
+            pointee: Pointee::Unknown,
+        }
+    }
+}
+
+enum Binding {
+    Type(syn::Type),
+    Expr(syn::Expr),
+    Operand(Operand),
+    // An element of a collection, e.g., the variable of a for loop.
+    Element(Receiver, syn::Expr),
+}
+
+// The variables of a function: its parameters and local bindings, with the line they are bound at.
+#[derive(Default)]
+struct Context {
+    // The scope of the function's body.
+    scope: usize,
+    type_params: Vec<String>,
+    bindings: HashMap<String, Vec<(usize, Binding)>>,
+}
+
+impl Context {
+    fn of(
+        sig: &syn::Signature,
+        block: &syn::Block,
+        scope: usize,
+        in_trait: bool,
+        self_ty: Option<syn::Type>,
+    ) -> Context {
+        let mut context = Context {
+            scope,
+            ..Context::default()
+        };
+        for param in &sig.generics.params {
+            if let syn::GenericParam::Type(param) = param {
+                context.type_params.push(param.ident.to_string());
+            }
+        }
+        if in_trait {
+            context.type_params.push("Self".to_string());
+        }
+
+        let line = sig.span().start().line;
+        for input in &sig.inputs {
+            match input {
+                syn::FnArg::Receiver(receiver) => {
+                    let binding = match (&receiver.reference, receiver.colon_token) {
+                        (_, Some(_)) => Binding::Type((*receiver.ty).clone()),
+                        (Some(_), None) => Binding::Operand(Operand {
+                            form: if receiver.mutability.is_some() {
+                                Receiver::RefMut
+                            } else {
+                                Receiver::Ref
+                            },
+                            pointee: Pointee::Unknown,
+                        }),
+                        (None, None) => match &self_ty {
+                            Some(self_ty) => Binding::Type(self_ty.clone()),
+                            None => Binding::Operand(Operand::default()),
+                        },
+                    };
+                    context.bind("self", line, binding);
+                }
+                syn::FnArg::Typed(arg) => {
+                    if let syn::Pat::Ident(pat) = &*arg.pat {
+                        context.bind(
+                            &pat.ident.to_string(),
+                            line,
+                            Binding::Type((*arg.ty).clone()),
+                        );
+                    }
+                }
+            }
+        }
+
+        struct Locals<'c> {
+            context: &'c mut Context,
+        }
+
+        impl<'ast> Visit<'ast> for Locals<'_> {
+            fn visit_local(&mut self, local: &'ast syn::Local) {
+                let line = local.span().start().line;
+                let init = local.init.as_ref().map(|init| (*init.expr).clone());
+                match (&local.pat, init) {
+                    (syn::Pat::Type(pat), _) => {
+                        if let syn::Pat::Ident(ident) = &*pat.pat {
+                            let binding = Binding::Type((*pat.ty).clone());
+                            self.context.bind(&ident.ident.to_string(), line, binding);
+                        }
+                    }
+                    (syn::Pat::Ident(ident), Some(init)) => {
+                        self.context
+                            .bind(&ident.ident.to_string(), line, Binding::Expr(init));
+                    }
+                    _ => {}
+                }
+                syn::visit::visit_local(self, local);
+            }
+
+            // Loop variables of 'for x in v.iter()' are references to the elements of 'v'.
+            fn visit_expr_for_loop(&mut self, expr: &'ast syn::ExprForLoop) {
+                if let syn::Pat::Ident(ident) = &*expr.pat {
+                    let (form, collection) = match &*expr.expr {
+                        syn::Expr::MethodCall(call) if call.method == "iter" => {
+                            (Receiver::Ref, &*call.receiver)
+                        }
+                        syn::Expr::MethodCall(call) if call.method == "iter_mut" => {
+                            (Receiver::RefMut, &*call.receiver)
+                        }
+                        syn::Expr::Reference(reference) if reference.mutability.is_some() => {
+                            (Receiver::RefMut, &*reference.expr)
+                        }
+                        syn::Expr::Reference(reference) => (Receiver::Ref, &*reference.expr),
+                        collection => (Receiver::Value, collection),
+                    };
+                    let binding = Binding::Element(form, collection.clone());
+                    let line = expr.span().start().line;
+                    self.context.bind(&ident.ident.to_string(), line, binding);
+                }
+                syn::visit::visit_expr_for_loop(self, expr);
+            }
+        }
+
+        Locals {
+            context: &mut context,
+        }
+        .visit_block(block);
+        context
+    }
+
+    fn bind(&mut self, name: &str, line: usize, binding: Binding) {
+        self.bindings
+            .entry(name.to_string())
+            .or_default()
+            .push((line, binding));
+    }
+
+    fn operand(&self, index: &Index, expr: &syn::Expr, depth: usize) -> Operand {
+        if depth > 4 {
+            return Operand::default();
+        }
+        match expr {
+            syn::Expr::Reference(reference) => Operand {
+                form: if reference.mutability.is_some() {
+                    Receiver::RefMut
+                } else {
+                    Receiver::Ref
+                },
+                pointee: self.operand(index, &reference.expr, depth + 1).pointee,
+            },
+            syn::Expr::Paren(expr) => self.operand(index, &expr.expr, depth + 1),
+            syn::Expr::Group(expr) => self.operand(index, &expr.expr, depth + 1),
+            syn::Expr::Cast(cast) => self.type_operand(index, &cast.ty),
+            syn::Expr::Call(call) => match &*call.func {
+                syn::Expr::Path(path) if is_box_new(&path.path) => Operand {
+                    form: Receiver::Boxed,
+                    pointee: call.args.first().map_or(Pointee::Unknown, |arg| {
+                        self.operand(index, arg, depth + 1).pointee
+                    }),
+                },
+                // Tuple struct constructors, e.g., Fat(1000).
+                syn::Expr::Path(path) if starts_uppercase(&path.path) => Operand {
+                    form: Receiver::Value,
+                    pointee: Pointee::Concrete,
+                },
+                _ => Operand::default(),
+            },
+            syn::Expr::Struct(_) => Operand {
+                form: Receiver::Value,
+                pointee: Pointee::Concrete,
+            },
+            syn::Expr::Path(path) => {
+                let ident = match path.path.get_ident() {
+                    Some(ident) => ident.to_string(),
+                    None => return Operand::default(),
+                };
+                let line = expr.span().start().line;
+                let binding = self.bindings.get(&ident).and_then(|bindings| {
+                    bindings
+                        .iter()
+                        .rfind(|(bound, _)| *bound <= line)
+                        .or(bindings.last())
+                });
+                match binding {
+                    Some((_, Binding::Type(ty))) => self.type_operand(index, ty),
+                    Some((_, Binding::Expr(init))) => self.operand(index, init, depth + 1),
+                    Some((_, Binding::Operand(operand))) => *operand,
+                    Some((_, Binding::Element(form, collection))) => Operand {
+                        form: *form,
+                        pointee: self
+                            .element_type(collection)
+                            .map_or(Pointee::Unknown, |ty| self.pointee(index, ty)),
+                    },
+                    // Unit structs, e.g., MacroStruct.
+                    None if starts_uppercase(&path.path) => Operand {
+                        form: Receiver::Value,
+                        pointee: Pointee::Concrete,
+                    },
+                    None => Operand::default(),
+                }
+            }
+            _ => Operand::default(),
+        }
+    }
+
+    // The element type of a collection variable declared with its type, e.g., Vec<&dyn FooTrait>.
+    fn element_type<'s>(&'s self, collection: &syn::Expr) -> Option<&'s syn::Type> {
+        let ident = match collection {
+            syn::Expr::Path(path) => path.path.get_ident()?.to_string(),
+            _ => return None,
+        };
+        let ty = match self.bindings.get(&ident)?.last()? {
+            (_, Binding::Type(ty)) => ty,
+            _ => return None,
+        };
+        let mut ty = ty;
+        while let syn::Type::Reference(reference) = ty {
+            ty = &reference.elem;
+        }
+        match ty {
+            syn::Type::Slice(slice) => Some(&slice.elem),
+            syn::Type::Array(array) => Some(&array.elem),
+            syn::Type::Path(path) => box_argument(&path.path),
+            _ => None,
+        }
+    }
+
+    fn type_operand(&self, index: &Index, ty: &syn::Type) -> Operand {
+        match ty {
+            syn::Type::Reference(reference) => Operand {
+                form: if reference.mutability.is_some() {
+                    Receiver::RefMut
+                } else {
+                    Receiver::Ref
+                },
+                pointee: self.pointee(index, &reference.elem),
+            },
+            syn::Type::Paren(ty) => self.type_operand(index, &ty.elem),
+            syn::Type::Path(path) if is_box(&path.path) => Operand {
+                form: Receiver::Boxed,
+                pointee: box_argument(&path.path)
+                    .map_or(Pointee::Unknown, |ty| self.pointee(index, ty)),
+            },
+            ty => Operand {
+                form: Receiver::Value,
+                pointee: self.pointee(index, ty),
+            },
+        }
+    }
+
+    fn pointee(&self, index: &Index, ty: &syn::Type) -> Pointee {
+        match ty {
+            syn::Type::TraitObject(_) => Pointee::Dyn,
+            syn::Type::ImplTrait(_) => Pointee::Param,
+            syn::Type::Paren(ty) => self.pointee(index, &ty.elem),
+            syn::Type::Reference(reference) => self.pointee(index, &reference.elem),
+            syn::Type::Path(path) if path.qself.is_none() => {
+                let segments: Vec<String> = path
+                    .path
+                    .segments
+                    .iter()
+                    .map(|s| s.ident.to_string())
+                    .collect();
+                if segments.len() == 1 && self.type_params.contains(&segments[0]) {
+                    return Pointee::Param;
+                }
+                // Bare trait objects of the 2015 edition, e.g., &FooTrait.
+                let resolved = index.path_in_scope(self.scope, &path.path);
+                if index.is_trait(&strip_generics(&resolved)) {
+                    Pointee::Dyn
+                } else {
+                    Pointee::Concrete
+                }
+            }
+            _ => Pointee::Concrete,
+        }
+    }
+}
+
+fn is_box(path: &syn::Path) -> bool {
+    path.segments.last().is_some_and(|s| s.ident == "Box")
+}
+
+fn is_box_new(path: &syn::Path) -> bool {
+    let segments: Vec<_> = path.segments.iter().collect();
+    matches!(segments.as_slice(), [.., ty, new] if ty.ident == "Box" && new.ident == "new")
+}
+
+fn box_argument(path: &syn::Path) -> Option<&syn::Type> {
+    match &path.segments.last()?.arguments {
+        syn::PathArguments::AngleBracketed(args) => args.args.iter().find_map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => Some(ty),
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+fn starts_uppercase(path: &syn::Path) -> bool {
+    path.segments
+        .last()
+        .is_some_and(|s| s.ident.to_string().starts_with(char::is_uppercase))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A UFCS call on a trait object of another crate.
+    fn site(tags: Option<Tags>) -> CallSite {
+        CallSite {
+            package: "dynamic_dispatch".to_string(),
+            file: PathBuf::from("src/dynamic_dispatch/src/lib.rs"),
+            line: 20,
+            invocation: None,
+            caller: "dynamic_dispatch::lib::dynamic_ufcs".to_string(),
+            kind: CallKind::InstanceTrait,
+            annotation: "traits::lib::FooTrait::method".to_string(),
+            targets: vec!["traits::lib::FooTrait::method".to_string()],
+            description: String::new(),
+            tags,
+            instances: Vec::new(),
+            callbacks: Vec::new(),
+            candidates: Vec::new(),
+        }
+    }
+
+    fn tags() -> Tags {
+        Tags {
+            dispatch: Dispatch::Dynamic,
+            cross_crate: true,
+            generic: false,
+            receiver: Receiver::Ref,
+            syntax: Syntax::Ufcs,
+            macro_expanded: false,
+            cfg_gated: false,
+        }
+    }
+
+    #[test]
+    fn parses_values_boolean_axes_and_their_negation() {
+        let filter =
+            Filter::parse("syntax=ufcs, dispatch = dynamic,cross_crate,!generic,").unwrap();
+        assert_eq!(
+            filter.terms,
+            [
+                ("syntax".to_string(), "ufcs".to_string()),
+                ("dispatch".to_string(), "dynamic".to_string()),
+                ("cross_crate".to_string(), "true".to_string()),
+                ("generic".to_string(), "false".to_string()),
+            ]
+        );
+        assert!(Filter::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_unknown_tags_and_values() {
+        let error = Filter::parse("syntax=ufcs,color=red").unwrap_err();
+        assert_eq!(error.to_string(), "unknown tag 'color'");
+        let error = Filter::parse("dispatch=virtual").unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("unknown value 'virtual' of tag 'dispatch'"));
+        // Only boolean axes may be given by name alone.
+        assert!(Filter::parse("syntax").is_err());
+    }
+
+    #[test]
+    fn matches_the_sites_whose_tags_have_all_the_values() {
+        let site = site(Some(tags()));
+        assert!(Filter::default().matches(&site));
+        assert!(
+            Filter::parse("syntax=ufcs,dispatch=dynamic,cross_crate,!generic")
+                .unwrap()
+                .matches(&site)
+        );
+        assert!(!Filter::parse("syntax=ufcs,generic").unwrap().matches(&site));
+        assert!(!Filter::parse("!cross_crate").unwrap().matches(&site));
+    }
+
+    #[test]
+    fn matches_untagged_sites_with_the_empty_filter_only() {
+        let site = site(None);
+        assert!(Filter::parse("").unwrap().matches(&site));
+        assert!(!Filter::parse("cross_crate").unwrap().matches(&site));
+        assert!(!Filter::parse("!cross_crate").unwrap().matches(&site));
+    }
+}
@@ -0,0 +1,814 @@
+// Ground truth extraction.
+//
+// The ground truth is not maintained separately from the benchmark. It is extracted from the
+// comments that document each call in the benchmark sources (see src/README.md):
+//
+//     // instance method call (trait)
+//     // structs::lib::fat::{impl FooTrait for Fat}::method
+//     // Fully qualified syntax call circumvents method lookup.
+//     let num2 = FooTrait::method(&fat);
+//
+// The first line gives the kind of the call, the second line its target and the rest of the lines
+// describe the call. The caller is the function or closure that encloses the call. Calls inside
+// macro definitions are attributed to the functions (or closures) that invoke the macro.
+//
+// Targets name the definitions of generic functions. Their instances are given by the generic
+// arguments of the target, e.g., generics::lib::monomorphized::<structs::lib::One>, or, for calls
+// inside generic functions, by a line per instance of the caller that gives the caller's generic
+// arguments and the target in that instance:
+//
+//     // instance <structs::lib::One>: <structs::lib::One as generics::base::BoundTrait>::method
+//
+// Calls into the standard library can name the functions that the standard library calls back,
+// by a line per callback that gives the function of the standard library that makes the call and
+// the target of the call. They are only scored with the std layer (see score.rs):
+//
+//     // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
+//
+// Indirect calls give the signature of the called function instead of a target. They can name
+// their targets, the functions that the pointer or closure may point to, by a line per target:
+//
+//     // points to static_dispatch::bench::run
+//
+// The functions whose type matches the signature are the candidates of an indirect call, which
+// tools that resolve calls by types alone report.
+//
+// Drops are calls of the drop glue of a type, core::ptr::drop_in_place, which runs the Drop
+// implementations of the type and of the types of its fields. They give the dropped type instead
+// of a target and point to the Drop implementations that run. Drops at the end of a scope are
+// annotated on the line that closes it:
+//
+//     // instance method call (trait - std::ops::Drop::drop)
+//     // drop_glue::lib::Holder
+//     // points to drop_glue::lib::{impl Drop for Holder}::drop
+//     }
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
+use syn::parse::Parser;
+
+use crate::error::Error;
+use crate::error::Result;
+use crate::graph::CallGraph;
+use crate::graph::Edge;
+use crate::index::strip_generics;
+use crate::index::FunctionKind;
+use crate::index::Index;
+use crate::index::Invocation;
+use crate::names::canonical_path;
+use crate::names::is_benchmark_path;
+use crate::names::is_std_path;
+use crate::names::split_path;
+use crate::names::split_top_level;
+use crate::names::std_layer_path;
+use crate::names::std_path;
+use crate::packages;
+use crate::packages::is_benchmark_crate;
+use crate::schema::TruthManifest;
+use crate::score;
+use crate::score::Layer;
+use crate::tags::Tagger;
+use crate::tags::Tags;
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CallKind {
+    StaticFunction,
+    StaticMonomorphized,
+    StaticInherent,
+    StaticInherentMonomorphized,
+    StaticTrait,
+    StaticTraitDefault,
+    InstanceInherent,
+    InstanceTrait,
+    FunctionPointer,
+    FnTrait,
+    FnMutTrait,
+    FnOnceTrait,
+    FuturePoll,
+    Drop,
+}
+
+// The first annotation line of each kind of call.
+const KINDS: &[(CallKind, &str)] = &[
+    (CallKind::StaticFunction, "static function call"),
+    (
+        CallKind::StaticMonomorphized,
+        "static function call (monomorphized)",
+    ),
+    (CallKind::StaticInherent, "static method call (inherent)"),
+    (
+        CallKind::StaticInherentMonomorphized,
+        "static method call (inherent monomorphized)",
+    ),
+    (CallKind::StaticTrait, "static method call (trait)"),
+    (
+        CallKind::StaticTraitDefault,
+        "static method call (trait default)",
+    ),
+    (
+        CallKind::InstanceInherent,
+        "instance method call (inherent)",
+    ),
+    (CallKind::InstanceTrait, "instance method call (trait)"),
+    (CallKind::FunctionPointer, "function pointer call"),
+    (
+        CallKind::FnTrait,
+        "instance method call (trait - std::ops::Fn::call)",
+    ),
+    (
+        CallKind::FnMutTrait,
+        "instance method call (trait - std::ops::FnMut::call_mut)",
+    ),
+    (
+        CallKind::FnOnceTrait,
+        "instance method call (trait - std::ops::FnOnce::call_once)",
+    ),
+    (
+        CallKind::FuturePoll,
+        "instance method call (trait - std::future::Future::poll)",
+    ),
+    (
+        CallKind::Drop,
+        "instance method call (trait - std::ops::Drop::drop)",
+    ),
+];
+
+impl CallKind {
+    pub fn parse(annotation: &str) -> Option<CallKind> {
+        KINDS
+            .iter()
+            .find(|(_, text)| *text == annotation)
+            .map(|(kind, _)| *kind)
+    }
+
+    pub fn annotation(self) -> &'static str {
+        KINDS
+            .iter()
+            .find(|(kind, _)| *kind == self)
+            .map(|(_, text)| *text)
+            .unwrap()
+    }
+
+    // The targets of indirect calls cannot be read off the call expression. Their annotations
+    // give the signature of the called function instead.
+    pub fn is_indirect(self) -> bool {
+        matches!(
+            self,
+            CallKind::FunctionPointer
+                | CallKind::FnTrait
+                | CallKind::FnMutTrait
+                | CallKind::FnOnceTrait
+                | CallKind::FuturePoll
+                | CallKind::Drop
+        )
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CallSite {
+    pub package: String,
+    pub file: PathBuf,
+    pub line: usize,
+    // For calls inside macro definitions, the line of the macro invocation in the caller's file.
+    pub invocation: Option<usize>,
+    pub caller: String,
+    pub kind: CallKind,
+    // The target line of the annotation as written in the sources.
+    pub annotation: String,
+    // The canonical names of the expected callees. Empty if the annotation does not name them.
+    pub targets: Vec<String>,
+    pub description: String,
+    // The taxonomy tags of the call (see tags.rs). Manifests of schema version 1 have none.
+    pub tags: Option<Tags>,
+    // The expected edges between the instances of the caller and the callee, if the call's
+    // instances are annotated. Empty otherwise, and in manifests older than schema version 3.
+    pub instances: Vec<Edge>,
+    // The expected edges from functions of the standard library to the functions it calls back
+    // for the call. Empty in manifests older than schema version 5.
+    pub callbacks: Vec<Edge>,
+    // For function pointer calls, the functions of the caller's package and its dependencies
+    // whose type matches the annotated signature, which the call may reach as far as types tell.
+    // Empty for other calls, and in manifests older than schema version 6.
+    pub candidates: Vec<String>,
+}
+
+impl CallSite {
+    // Calls into functions outside the layer, e.g., std::vec::Vec::push on the benchmark layer,
+    // are not scored, as call graphs are reduced to the functions of the layer.
+    pub fn is_scored(&self, layer: Layer) -> bool {
+        let targets = self.layer_targets(layer);
+        !targets.is_empty() && targets.iter().all(|target| layer.contains(target))
+    }
+
+    // The targets of the call on a layer. Calls of the methods of std traits on generic receivers,
+    // e.g., 'a + b' with T: Add, leave the benchmark layer, but the implementations that the
+    // instances of the caller call are in it: they are the targets of such calls on the layer.
+    pub fn layer_targets(&self, layer: Layer) -> Vec<String> {
+        if self.instances.is_empty() || self.targets.iter().any(|target| layer.contains(target)) {
+            return self.targets.clone();
+        }
+        let mut targets: Vec<String> = self
+            .instances
+            .iter()
+            .map(|instance| canonical_path(&instance.callee))
+            .collect();
+        targets.sort();
+        targets.dedup();
+        targets
+    }
+}
+
+#[derive(Default)]
+pub struct Truth {
+    pub sites: Vec<CallSite>,
+    // Annotations that could not be attributed to a caller.
+    pub warnings: Vec<String>,
+}
+
+struct Annotation {
+    kind: CallKind,
+    target: String,
+    // The generic arguments of the caller and the target of each annotated instance.
+    instances: Vec<(String, String)>,
+    // The function of the standard library and the target of each annotated callback.
+    callbacks: Vec<(String, String)>,
+    // The functions that an indirect call points to.
+    points_to: Vec<String>,
+    description: String,
+    line: usize,
+}
+
+impl Truth {
+    pub fn extract(root: &Path, index: &Index) -> Result<Truth> {
+        let mut truth = Truth::default();
+        for (package, file) in &index.files {
+            let source = fs::read_to_string(root.join(file))
+                .map_err(|e| Error::new(format!("cannot read {}: {}", file.display(), e)))?;
+            for annotation in annotations(&source) {
+                truth.add(index, package, file, annotation);
+            }
+        }
+
+        let mut visible: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for package in packages::all() {
+            let dependencies = package.dependencies();
+            let names = dependencies
+                .iter()
+                .map(|dependency| dependency.name.to_string());
+            let names = std::iter::once(package.name.to_string()).chain(names);
+            visible.insert(package.name.to_string(), names.collect());
+        }
+        for site in &mut truth.sites {
+            if let Some(packages) = visible.get(&site.package) {
+                site.candidates = candidates(index, packages, site);
+            }
+        }
+
+        let mut tagger = Tagger::new(root, index);
+        for site in &mut truth.sites {
+            site.tags = Some(tagger.tag(site)?);
+        }
+        Ok(truth)
+    }
+
+    pub fn from_manifest(manifest: TruthManifest) -> Truth {
+        Truth {
+            sites: manifest.sites,
+            warnings: Vec::new(),
+        }
+    }
+
+    fn add(&mut self, index: &Index, package: &str, file: &Path, annotation: Annotation) {
+        if let Some(caller) = index.function_at(file, annotation.line) {
+            let scope = index.function_scope(caller);
+            let targets = call_targets(index, scope, &annotation, None);
+            let instances = instances(index, scope, caller, &annotation, &targets);
+            self.sites.push(site(
+                index,
+                package,
+                file,
+                &annotation,
+                caller,
+                None,
+                targets,
+                instances,
+            ));
+            return;
+        }
+
+        let macro_def = match index.macro_at(file, annotation.line) {
+            Some(macro_def) => macro_def,
+            None => {
+                self.warnings.push(format!(
+                    "{}:{}: annotated call outside of a function or macro",
+                    file.display(),
+                    annotation.line
+                ));
+                return;
+            }
+        };
+
+        for invocation in &index.invocations {
+            let scope = index.function_scope(invocation.caller);
+            let invoked = index.resolve(scope, &invocation.path);
+            if invoked != macro_def.path && invocation.path != [macro_def.name.clone()] {
+                continue;
+            }
+            let targets = call_targets(index, scope, &annotation, Some(invocation));
+            let line = Some(invocation.line);
+            let caller = invocation.caller;
+            let instances = instances(index, scope, caller, &annotation, &targets);
+            self.sites.push(site(
+                index,
+                package,
+                file,
+                &annotation,
+                caller,
+                line,
+                targets,
+                instances,
+            ));
+        }
+    }
+
+    pub fn package_sites<'a>(&'a self, package: &'a str) -> impl Iterator<Item = &'a CallSite> {
+        self.sites
+            .iter()
+            .filter(move |site| site.package == package)
+    }
+
+    // The expected call graph: an edge from the caller of each annotated call to each of its
+    // targets on the layer. Calls of a trait's method also get an edge to the method of each
+    // implementation of the trait, as they might dispatch to any of them. Calls that do not name
+    // their targets add no edges. On the std layer, functions of the standard library are named
+    // after their definitions and call back the functions they are given.
+    pub fn graph(&self, index: &Index, layer: Layer) -> CallGraph {
+        let mut graph = CallGraph::default();
+        for site in &self.sites {
+            graph.nodes.insert(site.caller.clone());
+            if layer == Layer::Std {
+                for target in site
+                    .targets
+                    .iter()
+                    .filter(|target| !is_benchmark_path(target))
+                {
+                    if is_std_path(target) {
+                        graph.add_edge(&site.caller, &std_path(target));
+                    }
+                }
+                for callback in &site.callbacks {
+                    graph.add_edge(
+                        &std_layer_path(&callback.caller),
+                        &std_layer_path(&callback.callee),
+                    );
+                }
+            }
+            for target in site
+                .layer_targets(Layer::Benchmark)
+                .iter()
+                .filter(|target| is_benchmark_path(target))
+            {
+                graph.add_edge(&site.caller, target);
+                for function in &index.functions {
+                    if function.kind == FunctionKind::TraitImpl
+                        && score::matches(index, target, &function.name)
+                    {
+                        graph.add_edge(&site.caller, &function.name);
+                    }
+                }
+            }
+        }
+        graph
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn site(
+    index: &Index,
+    package: &str,
+    file: &Path,
+    annotation: &Annotation,
+    caller: usize,
+    invocation: Option<usize>,
+    targets: Vec<String>,
+    instances: Vec<Edge>,
+) -> CallSite {
+    CallSite {
+        package: package.to_string(),
+        file: file.to_path_buf(),
+        line: annotation.line,
+        invocation,
+        caller: index
+            .caller_at(caller, file, annotation.line, invocation)
+            .to_string(),
+        kind: annotation.kind,
+        annotation: annotation.target.clone(),
+        targets,
+        description: annotation.description.clone(),
+        tags: None,
+        instances,
+        callbacks: callbacks(index, index.function_scope(caller), annotation),
+        candidates: Vec::new(),
+    }
+}
+
+fn comment(line: &str) -> Option<&str> {
+    line.trim().strip_prefix("//").map(str::trim)
+}
+
+// Whether a line of code may contain a call: a call expression, an '.await', which calls the poll
+// of the awaited future, an operator expression, which calls the method of the operator's trait,
+// e.g., 'a + b' or 'a[i]', or the end of a scope, which drops the values of its variables.
+fn is_call(line: &str) -> bool {
+    line.contains(['(', '[', '+', '-', '*', '/', '%', '!', '<', '>'])
+        || line.contains("==")
+        || line.contains(".await")
+        || line.trim_start().starts_with('}')
+}
+
+// Finds the annotated calls of a source file.
+fn annotations(source: &str) -> Vec<Annotation> {
+    let lines: Vec<&str> = source.lines().collect();
+
+    let mut annotations = Vec::new();
+    let mut pos = 0;
+    while pos < lines.len() {
+        let kind = match comment(lines[pos]).and_then(CallKind::parse) {
+            Some(kind) => kind,
+            None => {
+                pos += 1;
+                continue;
+            }
+        };
+        pos += 1;
+
+        let mut block = Vec::new();
+        while pos < lines.len() {
+            match comment(lines[pos]) {
+                Some(text) if CallKind::parse(text).is_none() => block.push(text),
+                _ => break,
+            }
+            pos += 1;
+        }
+        // The call is on the first line of code following the annotation that contains a call,
+        // e.g., 'let mut one = One;' is skipped in favor of 'let num2 = one.method_2();'.
+        let mut line = pos;
+        while line < lines.len() && (comment(lines[line]).is_some() || !is_call(lines[line])) {
+            line += 1;
+        }
+        if line == lines.len() {
+            line = pos;
+        }
+
+        let mut instances = Vec::new();
+        let mut callbacks = Vec::new();
+        let mut points_to = Vec::new();
+        let mut description = Vec::new();
+        for text in block.iter().skip(1) {
+            if let Some(target) = text.strip_prefix("points to ") {
+                points_to.push(target.to_string());
+                continue;
+            }
+            let instance = text
+                .strip_prefix("instance ")
+                .and_then(|text| split_top_level(text, ": "));
+            let callback = text
+                .strip_prefix("callback ")
+                .and_then(|text| split_top_level(text, ": "));
+            match (instance, callback) {
+                (Some((args, target)), _) => instances.push((args.to_string(), target.to_string())),
+                (None, Some((function, target))) => {
+                    callbacks.push((function.to_string(), target.to_string()))
+                }
+                (None, None) => description.push(*text),
+            }
+        }
+
+        annotations.push(Annotation {
+            kind,
+            target: block.first().map(|s| s.to_string()).unwrap_or_default(),
+            instances,
+            callbacks,
+            points_to,
+            description: description.join(" "),
+            line: line + 1,
+        });
+    }
+    annotations
+}
+
+// The canonical names of the targets of an annotated call, which indirect calls give by the
+// functions they point to.
+fn call_targets(
+    index: &Index,
+    scope: usize,
+    annotation: &Annotation,
+    invocation: Option<&Invocation>,
+) -> Vec<String> {
+    if annotation.points_to.is_empty() {
+        return targets(
+            index,
+            scope,
+            annotation.kind,
+            &annotation.target,
+            invocation,
+        );
+    }
+    annotation
+        .points_to
+        .iter()
+        .flat_map(|target| targets(index, scope, CallKind::StaticFunction, target, invocation))
+        .collect()
+}
+
+// The functions of the given packages whose type matches the signature of a function pointer
+// call, in the order of the index.
+fn candidates(index: &Index, packages: &[String], site: &CallSite) -> Vec<String> {
+    if site.kind != CallKind::FunctionPointer {
+        return Vec::new();
+    }
+    let scope = match index.function_at(&site.file, site.line) {
+        Some(function) => index.function_scope(function),
+        None => return Vec::new(),
+    };
+    let signature = match syn::parse_str::<syn::Type>(&site.annotation) {
+        Ok(ty @ syn::Type::BareFn(_)) => index.type_in_scope(scope, &ty),
+        _ => return Vec::new(),
+    };
+    let mut candidates: Vec<String> = Vec::new();
+    for function in &index.functions {
+        if packages.contains(&function.package)
+            && index.pointer_type(function).as_ref() == Some(&signature)
+            && !candidates.contains(&function.name)
+        {
+            candidates.push(function.name.clone());
+        }
+    }
+    candidates
+}
+
+// Translates the target of an annotation to canonical names. Targets are mostly given as absolute
+// paths, but implementations are referred to as {impl Trait for Type}, with the names of the trait
+// and the type as they are visible in the module that precedes them.
+fn targets(
+    index: &Index,
+    scope: usize,
+    kind: CallKind,
+    text: &str,
+    invocation: Option<&Invocation>,
+) -> Vec<String> {
+    if kind.is_indirect() || text.is_empty() {
+        return Vec::new();
+    }
+
+    let segments = split_path(text);
+    if let Some(pos) = segments.iter().position(|s| s.starts_with("{impl ")) {
+        let module = segments[..pos].join("::");
+        let scope = index.module_scope(&module).unwrap_or(scope);
+        let method = segments[pos + 1..].join("::");
+        let inner = &segments[pos]["{impl ".len()..segments[pos].len() - 1];
+        let (trait_, self_ty) = match split_top_level(inner, " for ") {
+            Some(parts) => parts,
+            None => return vec![text.to_string()],
+        };
+        let trait_ = type_text(index, scope, trait_);
+        // 'type_of($x)' stands for the type of a macro argument, one target per argument.
+        let self_types = if self_ty.starts_with("type_of(") {
+            invocation
+                .map(|i| argument_types(index, i))
+                .unwrap_or_default()
+        } else {
+            vec![type_text(index, scope, self_ty)]
+        };
+        return self_types
+            .iter()
+            .map(|self_ty| implementation(index, self_ty, &trait_, &method))
+            .collect();
+    }
+
+    let path: syn::TypePath = match syn::parse_str(text) {
+        Ok(path) => path,
+        Err(_) => return vec![text.to_string()],
+    };
+    let idents: Vec<String> = path
+        .path
+        .segments
+        .iter()
+        .map(|s| s.ident.to_string())
+        .collect();
+
+    match &path.qself {
+        // <T as Trait>::method calls the trait's method on the generic type T, otherwise the
+        // method of a specific implementation.
+        Some(qself) => {
+            let trait_path = syn::Path {
+                leading_colon: None,
+                segments: path
+                    .path
+                    .segments
+                    .iter()
+                    .take(qself.position)
+                    .cloned()
+                    .collect(),
+            };
+            let trait_ = index.path_in_scope(scope, &trait_path);
+            let method = idents[qself.position..].join("::");
+            let self_ty = index.type_in_scope(scope, &qself.ty);
+            if is_type_parameter(&self_ty) {
+                vec![format!("{}::{}", strip_generics(&trait_), method)]
+            } else {
+                vec![implementation(index, &self_ty, &trait_, &method)]
+            }
+        }
+        None => vec![absolute(index, scope, &idents)],
+    }
+}
+
+// The expected edges of the callbacks of the standard library. The functions of the standard
+// library are named as written.
+fn callbacks(index: &Index, scope: usize, annotation: &Annotation) -> Vec<Edge> {
+    annotation
+        .callbacks
+        .iter()
+        .filter_map(|(function, target)| {
+            let callee = targets(index, scope, CallKind::StaticFunction, target, None).pop()?;
+            Some(Edge::new(function.as_str(), callee))
+        })
+        .collect()
+}
+
+// The expected edges between instances. Annotated instances of the caller are named by appending
+// their generic arguments to the caller, e.g., generics::base::Wrapper::method_wrapper::<
+// structs::lib::One>. The generic arguments of a target, e.g., monomorphized::<One>, name its
+// instance if the caller is not generic.
+fn instances(
+    index: &Index,
+    scope: usize,
+    caller: usize,
+    annotation: &Annotation,
+    definitions: &[String],
+) -> Vec<Edge> {
+    let caller = &index.functions[caller];
+    if !annotation.instances.is_empty() {
+        return annotation
+            .instances
+            .iter()
+            .filter_map(|(args, target)| {
+                let args: syn::AngleBracketedGenericArguments = syn::parse_str(args).ok()?;
+                let instance = format!(
+                    "{}::{}",
+                    caller.name,
+                    generic_arguments(index, scope, &args)
+                );
+                let callee = targets(index, scope, annotation.kind, target, None).pop()?;
+                Some(Edge::new(
+                    instance,
+                    instance_of(index, scope, &callee, target),
+                ))
+            })
+            .collect();
+    }
+    if caller.generic {
+        return Vec::new();
+    }
+    let instances: Vec<Edge> = definitions
+        .iter()
+        .map(|target| {
+            Edge::new(
+                caller.name.as_str(),
+                instance_of(index, scope, target, &annotation.target),
+            )
+        })
+        .collect();
+    if instances
+        .iter()
+        .zip(definitions)
+        .all(|(instance, definition)| instance.callee == *definition)
+    {
+        Vec::new()
+    } else {
+        instances
+    }
+}
+
+// The instance of 'target' given by the generic arguments of the annotated path, e.g., the target
+// generics::base::Wrapper::new of Wrapper::<One>::new is instance
+// generics::base::Wrapper::new::<generics::base::One>.
+fn instance_of(index: &Index, scope: usize, target: &str, text: &str) -> String {
+    let path: syn::TypePath = match syn::parse_str(text) {
+        Ok(path) => path,
+        Err(_) => return target.to_string(),
+    };
+    let mut args = Vec::new();
+    for segment in &path.path.segments {
+        // Only turbofish arguments, e.g., not those of GenericFooTrait<i32>::method.
+        if let syn::PathArguments::AngleBracketed(segment_args) = &segment.arguments {
+            if segment_args.colon2_token.is_some() {
+                args.extend(segment_args.args.iter().cloned());
+            }
+        }
+    }
+    if args.is_empty() {
+        return target.to_string();
+    }
+    let args = syn::AngleBracketedGenericArguments {
+        colon2_token: None,
+        lt_token: Default::default(),
+        args: args.into_iter().collect(),
+        gt_token: Default::default(),
+    };
+    format!("{}::{}", target, generic_arguments(index, scope, &args))
+}
+
+fn generic_arguments(
+    index: &Index,
+    scope: usize,
+    args: &syn::AngleBracketedGenericArguments,
+) -> String {
+    let args: Vec<String> = args
+        .args
+        .iter()
+        .map(|arg| match arg {
+            syn::GenericArgument::Type(ty) => index.type_in_scope(scope, ty),
+            arg => quote::quote!(#arg).to_string(),
+        })
+        .collect();
+    format!("<{}>", args.join(", "))
+}
+
+// Annotations name their targets by absolute paths, which must not be resolved against the items
+// in scope, e.g., 'main::helpers::run_benchmark' does not start with function 'main::main'.
+fn absolute(index: &Index, scope: usize, idents: &[String]) -> String {
+    let first = idents.first().map(String::as_str).unwrap_or("");
+    if is_benchmark_crate(first) || ["std", "core", "alloc"].contains(&first) {
+        idents.join("::")
+    } else {
+        index.resolve(scope, idents)
+    }
+}
+
+// The method of a trait implementation, or the trait's default method if the implementation does
+// not override it.
+fn implementation(index: &Index, self_ty: &str, trait_: &str, method: &str) -> String {
+    let name = format!("<{} as {}>::{}", self_ty, trait_, method);
+    let default = format!("{}::{}", strip_generics(trait_), method);
+    if index.function(&name).is_none() && index.function(&default).is_some() {
+        default
+    } else {
+        name
+    }
+}
+
+fn type_text(index: &Index, scope: usize, text: &str) -> String {
+    match syn::parse_str::<syn::Type>(text) {
+        Ok(ty) => index.type_in_scope(scope, &ty),
+        Err(_) => text.to_string(),
+    }
+}
+
+fn is_type_parameter(ty: &str) -> bool {
+    !ty.contains("::") && ty.chars().next().is_some_and(char::is_uppercase)
+}
+
+// The types of the arguments of a macro invocation, as far as they can be told from the argument
+// expressions themselves, e.g., &Fat(1000) has type Fat. References are looked through, as the
+// arguments are used as method receivers.
+fn argument_types(index: &Index, invocation: &Invocation) -> Vec<String> {
+    let parser = syn::punctuated::Punctuated::<syn::Expr, syn::Token![,]>::parse_terminated;
+    let args = match parser.parse2(invocation.tokens.clone()) {
+        Ok(args) => args,
+        Err(_) => return Vec::new(),
+    };
+    let scope = index.function_scope(invocation.caller);
+    args.iter()
+        .filter_map(|arg| expression_type(arg))
+        .map(|path| {
+            let idents: Vec<String> = path.segments.iter().map(|s| s.ident.to_string()).collect();
+            index.resolve(scope, &idents)
+        })
+        .collect()
+}
+
+fn expression_type(expr: &syn::Expr) -> Option<&syn::Path> {
+    match expr {
+        syn::Expr::Reference(expr) => expression_type(&expr.expr),
+        syn::Expr::Paren(expr) => expression_type(&expr.expr),
+        syn::Expr::Path(expr) => Some(&expr.path),
+        syn::Expr::Struct(expr) => Some(&expr.path),
+        syn::Expr::Call(expr) => match &*expr.func {
+            syn::Expr::Path(func) => Some(&func.path),
+            _ => None,
+        },
+        _ => None,
+    }
+}
@@ -0,0 +1,224 @@
+// Async and await
+//
+// An async function returns a future, whose state machine runs the body of the function each time
+// the future is polled, up to the first await point whose future is pending. rustc generates the
+// state machine as a coroutine, which is named like a closure of the function that defines it:
+// the coroutine of an async function is its closure #0, e.g.,
+// async_await::lib::foo_of::{closure#0}, and an async block is numbered among the closures of its
+// function. The calls in the body of an async function are thus made by the poll of its coroutine,
+// and '.await' calls Future::poll on the awaited future, which is resolved like any other trait
+// method call: statically for concrete futures and through the vtable for boxed trait objects. An
+// executor starts it all by polling the root future. Wakers call back the functions of a
+// RawWakerVTable that the executor provides.
+
+pub mod executor {
+    use std::future::Future;
+    use std::ptr;
+    use std::task::Context;
+    use std::task::Poll;
+    use std::task::RawWaker;
+    use std::task::RawWakerVTable;
+    use std::task::Waker;
+
+    // The functions that the wakers of the executor call through their vtable. The executor
+    // polls its future again as soon as it is pending, so there is nothing to wake and the
+    // wakers carry no data.
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, wake, wake_by_ref, drop_waker);
+
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(ptr::null(), &VTABLE)
+    }
+
+    fn wake(_: *const ()) {}
+
+    fn wake_by_ref(_: *const ()) {}
+
+    fn drop_waker(_: *const ()) {}
+
+    fn waker() -> Waker {
+        // The functions of the vtable ignore the data pointer, so any pointer is valid.
+        unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) }
+    }
+
+    // Runs a future to completion on the current thread.
+    pub fn block_on<F: Future>(future: F) -> F::Output {
+        // static function call
+        // async_await::executor::waker
+        let waker = waker();
+        let mut context = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        let output = loop {
+            // instance method call (trait - std::future::Future::poll)
+            // F: std::future::Future
+            // points to async_await::lib::sum::{closure#0}
+            // points to async_await::bench::run::{closure#0}
+            // The executor polls the root future, i.e., the coroutine of the async function or
+            // block that 'block_on' is given. Each instance of 'block_on' polls a single one.
+            if let Poll::Ready(output) = future.as_mut().poll(&mut context) {
+                break output;
+            }
+        };
+
+        // static function call
+        // std::mem::drop
+        // callback <std::task::Waker as std::ops::Drop>::drop: async_await::executor::drop_waker
+        // Dropping the waker calls the drop function of its vtable.
+        drop(waker);
+        output
+    }
+}
+
+pub mod lib {
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::Context;
+    use std::task::Poll;
+    use structs::lib::fat::Fat;
+    use traits::lib::BarTrait;
+    use traits::lib::FooTrait;
+
+    // A future that is pending the first time it is polled, and asks to be polled again.
+    pub struct YieldNow {
+        yielded: bool,
+    }
+
+    pub fn yield_now() -> YieldNow {
+        YieldNow { yielded: false }
+    }
+
+    impl Future for YieldNow {
+        type Output = ();
+
+        fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+            if self.yielded {
+                return Poll::Ready(());
+            }
+            self.yielded = true;
+            // instance method call (inherent)
+            // std::task::Waker::wake_by_ref
+            // callback std::task::Waker::wake_by_ref: async_await::executor::wake_by_ref
+            // The waker calls the wake_by_ref function of the executor's vtable.
+            context.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    pub async fn foo_of(fat: &Fat) -> u32 {
+        // instance method call (trait)
+        // structs::lib::fat::{impl FooTrait for Fat}::method
+        // The call is made by the coroutine of 'foo_of' when it is polled, not by 'foo_of'.
+        FooTrait::method(fat)
+    }
+
+    // Returns an async block that owns 'fat', boxed as a trait object.
+    pub fn bar_of(fat: Fat) -> Pin<Box<dyn Future<Output = u32>>> {
+        Box::pin(async move {
+            // instance method call (trait)
+            // structs::lib::fat::{impl BarTrait for Fat}::method
+            // Call of a trait method on a struct that the async block owns.
+            BarTrait::method(&fat)
+        })
+    }
+
+    pub async fn sum(fat: Fat) -> u32 {
+        // static function call
+        // async_await::lib::foo_of
+        // Calling an async function only creates its future.
+        let future = foo_of(&fat);
+
+        // instance method call (trait - std::future::Future::poll)
+        // impl std::future::Future<Output = u32>
+        // points to async_await::lib::foo_of::{closure#0}
+        // Awaiting the future of an async function polls its coroutine.
+        let foo_value = future.await;
+
+        // static function call
+        // async_await::lib::yield_now
+        let pending = yield_now();
+
+        // instance method call (trait - std::future::Future::poll)
+        // async_await::lib::YieldNow
+        // points to async_await::lib::{impl Future for YieldNow}::poll
+        // The future implements Future by hand. It is polled twice, as it is pending at first.
+        pending.await;
+
+        // static function call
+        // async_await::lib::bar_of
+        let boxed = bar_of(fat);
+
+        // instance method call (trait - std::future::Future::poll)
+        // std::pin::Pin<Box<dyn std::future::Future<Output = u32>>>
+        // points to async_await::lib::bar_of::{closure#0}
+        // Awaiting a boxed trait object polls the async block through the vtable of the object.
+        let bar_value = boxed.await;
+
+        foo_value + bar_value
+    }
+}
+
+pub mod bench {
+    use crate::executor::block_on;
+    use crate::lib::foo_of;
+    use crate::lib::sum;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+    use traits::lib::FooTrait;
+
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which futures the executor and the awaits polled.
+    pub fn run() -> Vec<(&'static str, i64)> {
+        // static function call
+        // async_await::lib::sum
+        let root = sum(Fat(10));
+
+        // static function call (monomorphized)
+        // async_await::executor::block_on
+        // The executor polls the future of 'sum', which awaits the other futures in turn.
+        let num1 = block_on(root);
+
+        let fat = Fat(20);
+        let thin = Thin;
+        let block = async move {
+            // static function call
+            // async_await::lib::foo_of
+            let future = foo_of(&fat);
+
+            // instance method call (trait - std::future::Future::poll)
+            // impl std::future::Future<Output = u32>
+            // points to async_await::lib::foo_of::{closure#0}
+            // The async block awaits the future of an async function on a struct it owns.
+            let foo_value = future.await;
+
+            // instance method call (trait)
+            // structs::lib::thin::{impl FooTrait for Thin}::method
+            foo_value + thin.method()
+        };
+
+        // static function call (monomorphized)
+        // async_await::executor::block_on
+        // Another instance of 'block_on', for the async block.
+        let num2 = block_on(block);
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
+        // This is here to ensure that the above calls are not optimized away as dead code.
+        println!(
+            "Just making sure no code is deemed dead by the compiler: {}",
+            num1 + num2
+        );
+
+        vec![
+            ("async_await::executor::block_on", num1 as i64),
+            ("async_await::executor::block_on", num2 as i64),
+        ]
+    }
+}
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "async_await" => bench::run {
+        category: "async",
+        description: "Async functions and blocks polled by .await and by an executor.",
+    },
+}
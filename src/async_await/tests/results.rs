@@ -0,0 +1,15 @@
+// The values returned by the calls of the benchmark tell which futures were polled.
+
+#[test]
+fn async_await() {
+    assert_eq!(
+        async_await::bench::run(),
+        vec![
+            // FooTrait::method of Fat(10), plus BarTrait::method of the Fat(10) that the boxed
+            // async block owns.
+            ("async_await::executor::block_on", 130),
+            // FooTrait::method of the Fat(20) that the async block owns, plus that of Thin.
+            ("async_await::executor::block_on", 30),
+        ]
+    );
+}
@@ -0,0 +1,268 @@
+// Closures
+//
+// A closure is an anonymous function that may capture variables of its environment by reference,
+// by mutable reference or by value. Each closure has a type of its own, which implements Fn, FnMut
+// or FnOnce depending on how the closure uses its captures. Calls of closures that are given as
+// generic arguments are thus resolved statically once the callee is monomorphized, while calls of
+// boxed closures are dispatched dynamically, like the calls of any other trait object. Closures are
+// named after the function or closure that defines them and their ordinal among its closures, e.g.,
+// closures::bench::run::{closure#0}.
+
+pub mod lib {
+    use structs::lib::fat::Fat;
+    use traits::lib::BarTrait;
+    use traits::lib::FooTrait;
+
+    // 'apply', 'apply_mut' and 'apply_once' call the closure they are given through the Fn trait
+    // that bounds it.
+    pub fn apply<F: Fn() -> u32>(f: F) -> u32 {
+        // instance method call (trait - std::ops::Fn::call)
+        // F: std::ops::Fn() -> u32
+        // points to closures::bench::run::{closure#0}
+        // points to closures::bench::run::{closure#1}
+        // Call of a closure given as a generic argument. Each instance of 'apply' calls a single
+        // closure.
+        f()
+    }
+
+    pub fn apply_mut<F: FnMut() -> u32>(mut f: F) -> u32 {
+        // instance method call (trait - std::ops::FnMut::call_mut)
+        // F: std::ops::FnMut() -> u32
+        // points to closures::bench::run::{closure#2}
+        // Call of a closure that mutates its captures.
+        f();
+
+        // instance method call (trait - std::ops::FnMut::call_mut)
+        // F: std::ops::FnMut() -> u32
+        // points to closures::bench::run::{closure#2}
+        // The second call sees the captures as the first call left them.
+        f()
+    }
+
+    pub fn apply_once<F: FnOnce() -> Fat>(f: F) -> Fat {
+        // instance method call (trait - std::ops::FnOnce::call_once)
+        // F: std::ops::FnOnce() -> structs::lib::fat::Fat
+        // points to closures::bench::run::{closure#3}
+        // Call of a closure that moves a captured value out of itself, so it can only be called
+        // once.
+        f()
+    }
+
+    // A struct that stores a closure in a field.
+    pub struct Callback<F: Fn(&Fat) -> u32> {
+        pub f: F,
+    }
+
+    impl<F: Fn(&Fat) -> u32> Callback<F> {
+        pub fn call(&self, fat: &Fat) -> u32 {
+            // instance method call (trait - std::ops::Fn::call)
+            // F: for<'r> std::ops::Fn(&'r structs::lib::fat::Fat) -> u32
+            // points to closures::bench::run::{closure#4}
+            // Call of the closure stored in field 'f'. The parentheses tell it apart from a call
+            // of a method named 'f'.
+            (self.f)(fat)
+        }
+    }
+
+    // Returns a closure that captures 'fat' by value, as an opaque type.
+    pub fn foo_of(fat: Fat) -> impl Fn() -> u32 {
+        move || {
+            // instance method call (trait)
+            // structs::lib::fat::{impl FooTrait for Fat}::method
+            // Call of a trait method on a struct that the closure owns.
+            FooTrait::method(&fat)
+        }
+    }
+
+    // Returns one of two closures, boxed as a trait object.
+    pub fn boxed(bar: bool) -> Box<dyn Fn(&Fat) -> u32> {
+        if bar {
+            Box::new(|fat: &Fat| {
+                // instance method call (trait)
+                // structs::lib::fat::{impl BarTrait for Fat}::method
+                // Fully qualified syntax call circumvents method lookup.
+                BarTrait::method(fat)
+            })
+        } else {
+            Box::new(|fat: &Fat| {
+                // instance method call (inherent)
+                // structs::lib::fat::Fat::method
+                // Inherent methods take precedence over the methods of traits.
+                fat.method()
+            })
+        }
+    }
+
+    // Returns a closure that calls 'g' with the result of 'f'.
+    pub fn compose<F, G>(f: F, g: G) -> impl Fn(u32) -> u32
+    where
+        F: Fn(u32) -> u32,
+        G: Fn(u32) -> u32,
+    {
+        move |x| {
+            // instance method call (trait - std::ops::Fn::call)
+            // F: std::ops::Fn(u32) -> u32
+            // points to closures::bench::run::{closure#5}
+            // Call of a closure captured by another closure.
+            let y = f(x);
+
+            // instance method call (trait - std::ops::Fn::call)
+            // G: std::ops::Fn(u32) -> u32
+            // points to closures::bench::run::{closure#6}
+            g(y)
+        }
+    }
+}
+
+pub mod bench {
+    use crate::lib::apply;
+    use crate::lib::apply_mut;
+    use crate::lib::apply_once;
+    use crate::lib::boxed;
+    use crate::lib::compose;
+    use crate::lib::foo_of;
+    use crate::lib::Callback;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+    use traits::lib::BarTrait;
+    use traits::lib::FooTrait;
+
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which closure each call called.
+    pub fn run() -> Vec<(&'static str, i64)> {
+        let fat = Fat(10);
+        let thin = Thin;
+
+        let by_ref = || {
+            // instance method call (trait)
+            // structs::lib::fat::{impl FooTrait for Fat}::method
+            // Call of a trait method on a struct that the closure captures by reference.
+            FooTrait::method(&fat)
+        };
+
+        // static function call (monomorphized)
+        // closures::lib::apply
+        // 'apply' is monomorphized for the type of the closure, which captures 'fat' by reference.
+        let num1 = apply(by_ref);
+
+        let by_move = move || {
+            // instance method call (trait)
+            // structs::lib::thin::{impl FooTrait for Thin}::method
+            // Call of a trait method on a struct that the closure captures by value.
+            thin.method()
+        };
+
+        // static function call (monomorphized)
+        // closures::lib::apply
+        // Another instance of 'apply', for a closure that captures 'thin' by value.
+        let num2 = apply(by_move);
+
+        let mut count = 0;
+        let counter = || {
+            count += 1;
+            count
+        };
+
+        // static function call (monomorphized)
+        // closures::lib::apply_mut
+        // The closure captures 'count' by mutable reference.
+        let num3 = apply_mut(counter);
+
+        let owned = Fat(7);
+        let consume = move || owned;
+
+        // static function call (monomorphized)
+        // closures::lib::apply_once
+        // The closure gives away the struct it captured by value.
+        let num4 = apply_once(consume).0;
+
+        let callback = Callback {
+            f: |fat: &Fat| {
+                // instance method call (trait)
+                // structs::lib::fat::{impl BarTrait for Fat}::another_method
+                // Call of a trait method on an argument of the closure.
+                BarTrait::another_method(fat)
+            },
+        };
+
+        // instance method call (inherent)
+        // closures::lib::Callback::call
+        // 'call' calls the closure stored in the struct.
+        let num5 = callback.call(&fat);
+
+        // static function call
+        // closures::lib::foo_of
+        let foo_method = foo_of(Fat(20));
+
+        // instance method call (trait - std::ops::Fn::call)
+        // impl std::ops::Fn() -> u32
+        // points to closures::lib::foo_of::{closure#0}
+        // Call of a closure returned as an opaque type, whose type is known to the compiler.
+        let num6 = foo_method();
+
+        // static function call
+        // closures::lib::boxed
+        let bar = boxed(true);
+
+        // static function call
+        // closures::lib::boxed
+        let inherent = boxed(false);
+
+        // instance method call (trait - std::ops::Fn::call)
+        // Box<dyn for<'r> std::ops::Fn(&'r structs::lib::fat::Fat) -> u32>
+        // points to closures::lib::boxed::{closure#0}
+        // Dynamic dispatch on a boxed closure. Both closures of 'boxed' have the same signature.
+        let num7 = bar(&fat);
+
+        // instance method call (trait - std::ops::Fn::call)
+        // Box<dyn for<'r> std::ops::Fn(&'r structs::lib::fat::Fat) -> u32>
+        // points to closures::lib::boxed::{closure#1}
+        let num8 = inherent(&fat);
+
+        let double = |x: u32| x * 2;
+        let plus_foo = |x: u32| {
+            // instance method call (trait)
+            // structs::lib::fat::{impl FooTrait for Fat}::method
+            x + FooTrait::method(&fat)
+        };
+
+        // static function call (monomorphized)
+        // closures::lib::compose
+        let composed = compose(double, plus_foo);
+
+        // instance method call (trait - std::ops::Fn::call)
+        // impl std::ops::Fn(u32) -> u32
+        // points to closures::lib::compose::{closure#0}
+        // Call of a closure composed from two other closures, which it calls in turn.
+        let num9 = composed(1);
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
+        // This is here to ensure that the above calls are not optimized away as dead code.
+        println!(
+            "Just making sure no code is deemed dead by the compiler: {}",
+            num1 + num2 + num3 + num4 + num5 + num6 + num7 + num8 + num9
+        );
+
+        vec![
+            ("closures::lib::apply", num1 as i64),
+            ("closures::lib::apply", num2 as i64),
+            ("closures::lib::apply_mut", num3 as i64),
+            ("closures::lib::apply_once", num4 as i64),
+            ("closures::lib::Callback::call", num5 as i64),
+            ("closures::lib::foo_of::{closure#0}", num6 as i64),
+            ("closures::lib::boxed::{closure#0}", num7 as i64),
+            ("closures::lib::boxed::{closure#1}", num8 as i64),
+            ("closures::lib::compose::{closure#0}", num9 as i64),
+        ]
+    }
+}
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "closures" => bench::run {
+        category: "closure",
+        description: "Calls of closures through the Fn, FnMut and FnOnce traits.",
+    },
+}
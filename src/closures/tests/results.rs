@@ -0,0 +1,26 @@
+// The values returned by the calls of the benchmark tell which closures the calls called.
+
+#[test]
+fn closures() {
+    assert_eq!(
+        closures::bench::run(),
+        vec![
+            // FooTrait::method of the captured Fat(10), and of the captured Thin.
+            ("closures::lib::apply", 20),
+            ("closures::lib::apply", 0),
+            // The counter was called twice.
+            ("closures::lib::apply_mut", 2),
+            // The consumed Fat(7).
+            ("closures::lib::apply_once", 7),
+            // BarTrait::another_method of Fat(10).
+            ("closures::lib::Callback::call", 111),
+            // FooTrait::method of the owned Fat(20).
+            ("closures::lib::foo_of::{closure#0}", 30),
+            // BarTrait::method, then the inherent method, of Fat(10).
+            ("closures::lib::boxed::{closure#0}", 110),
+            ("closures::lib::boxed::{closure#1}", 10),
+            // 1 doubled, plus FooTrait::method of Fat(10).
+            ("closures::lib::compose::{closure#0}", 22),
+        ]
+    );
+}
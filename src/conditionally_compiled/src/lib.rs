@@ -34,7 +34,9 @@ pub mod lib {
 }
 
 pub mod bench {
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which version of 'foo' was compiled.
+    pub fn run() -> Vec<(&'static str, i64)> {
         use crate::lib::bar;
         use crate::lib::foo;
 
@@ -48,10 +50,26 @@ pub mod bench {
         // Function 'foo' is conditionally compiled on feature 'foo'.
         let num2 = foo();
 
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
         // This is here to ensure that the above calls are not optimized away as dead code.
         println!(
             "Just making sure no code is deemed dead by the compiler: {}",
             num1 + num2
         );
+
+        vec![
+            ("conditionally_compiled::lib::bar", num1 as i64),
+            ("conditionally_compiled::lib::foo", num2 as i64),
+        ]
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "conditionally_compiled" => bench::run {
+        category: "cfg",
+        description: "Conditionally compiled functions.",
+    },
+}
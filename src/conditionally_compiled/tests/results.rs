@@ -0,0 +1,14 @@
+// The values returned by the calls of the benchmark tell which version of 'foo' was compiled.
+
+#[test]
+fn conditionally_compiled() {
+    // 'foo' returns base_one() with feature 'foo' and base_two() without.
+    let foo = if cfg!(feature = "foo") { 1 } else { 2 };
+    assert_eq!(
+        conditionally_compiled::bench::run(),
+        vec![
+            ("conditionally_compiled::lib::bar", foo),
+            ("conditionally_compiled::lib::foo", foo),
+        ]
+    );
+}
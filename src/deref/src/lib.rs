@@ -0,0 +1,169 @@
+// Deref
+//
+// Method lookup dereferences the receiver until it finds a type with a method of the given name:
+// 'pointer.method()' on a 'Pointer' that implements Deref<Target = Fat> calls
+// Deref::deref(&pointer) and then Fat::method on the result, as described in
+// https://doc.rust-lang.org/reference/expressions/method-call-expr.html. The derefs are implicit
+// calls of the Deref implementation of each type along the way, or of the DerefMut
+// implementation if the method takes '&mut self'. References and boxes are dereferenced by the
+// compiler itself, without a call. A type with a method of the name stops the lookup, even if the
+// type it dereferences to has one as well.
+
+pub mod lib {
+    use std::ops::Deref;
+    use std::ops::DerefMut;
+    use structs::lib::fat::Fat;
+
+    // A smart pointer that owns a Fat.
+    pub struct Pointer(pub Fat);
+
+    impl Deref for Pointer {
+        type Target = Fat;
+
+        fn deref(&self) -> &Fat {
+            &self.0
+        }
+    }
+
+    impl DerefMut for Pointer {
+        fn deref_mut(&mut self) -> &mut Fat {
+            &mut self.0
+        }
+    }
+
+    // A smart pointer to a smart pointer, which reaches Fat by two derefs.
+    pub struct Outer(pub Pointer);
+
+    impl Deref for Outer {
+        type Target = Pointer;
+
+        fn deref(&self) -> &Pointer {
+            &self.0
+        }
+    }
+
+    // A smart pointer with an inherent 'method' of its own, which shadows that of Fat.
+    pub struct Shadow(pub Fat);
+
+    impl Shadow {
+        pub fn method(&self) -> u32 {
+            self.0 .0 + 1000
+        }
+    }
+
+    impl Deref for Shadow {
+        type Target = Fat;
+
+        fn deref(&self) -> &Fat {
+            &self.0
+        }
+    }
+}
+
+pub mod bench {
+    use crate::lib::Outer;
+    use crate::lib::Pointer;
+    use crate::lib::Shadow;
+    use std::rc::Rc;
+    use structs::lib::fat::Fat;
+
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which method each lookup resolved to, through whichever derefs.
+    pub fn run() -> Vec<(&'static str, i64)> {
+        let boxed = Box::new(Fat(1));
+
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        // Method call through a Box, which the compiler dereferences without calling Deref::deref.
+        let num1 = boxed.method();
+
+        let rc = Rc::new(Fat(2));
+
+        // instance method call (trait)
+        // <std::rc::Rc<T> as std::ops::Deref>::deref
+        // Implicit deref of an Rc, which the standard library implements.
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        // Method call on the Fat that the Rc points to.
+        let num2 = rc.method();
+
+        let fat = Fat(3);
+        let reference = &&fat;
+
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        // Method call through a reference to a reference, which the compiler dereferences twice.
+        let num3 = reference.method();
+
+        let mut pointer = Pointer(Fat(4));
+
+        // instance method call (trait)
+        // deref::lib::{impl Deref for Pointer}::deref
+        // Implicit deref of a smart pointer of the package.
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        // Method call on the Fat that the smart pointer dereferences to.
+        let num4 = pointer.method();
+
+        // instance method call (trait)
+        // deref::lib::{impl DerefMut for Pointer}::deref_mut
+        // 'another_method' takes '&mut self', so the smart pointer is dereferenced mutably.
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::another_method
+        let num5 = pointer.another_method();
+
+        let outer = Outer(Pointer(Fat(6)));
+
+        // instance method call (trait)
+        // deref::lib::{impl Deref for Outer}::deref
+        // The first deref of a chain, from Outer to Pointer.
+        // instance method call (trait)
+        // deref::lib::{impl Deref for Pointer}::deref
+        // The second deref of the chain, from Pointer to Fat.
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        let num6 = outer.method();
+
+        let shadow = Shadow(Fat(7));
+
+        // instance method call (inherent)
+        // deref::lib::Shadow::method
+        // Shadow has a method of the name, so it is not dereferenced.
+        let num7 = shadow.method();
+
+        // instance method call (trait)
+        // deref::lib::{impl Deref for Shadow}::deref
+        // Explicit deref, which skips the method of Shadow.
+        // instance method call (inherent)
+        // structs::lib::fat::Fat::method
+        let num8 = (*shadow).method();
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
+        // This is here to ensure that the above calls are not optimized away as dead code.
+        println!(
+            "Just making sure no code is deemed dead by the compiler: {}",
+            num1 + num2 + num3 + num4 + num5 + num6 + num7 + num8
+        );
+
+        vec![
+            ("structs::lib::fat::Fat::method", num1 as i64),
+            ("structs::lib::fat::Fat::method", num2 as i64),
+            ("structs::lib::fat::Fat::method", num3 as i64),
+            ("structs::lib::fat::Fat::method", num4 as i64),
+            ("structs::lib::fat::Fat::another_method", num5 as i64),
+            ("structs::lib::fat::Fat::method", num6 as i64),
+            ("deref::lib::Shadow::method", num7 as i64),
+            ("structs::lib::fat::Fat::method", num8 as i64),
+        ]
+    }
+}
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "deref" => bench::run {
+        category: "deref",
+        description: "Method calls through Deref and DerefMut, by auto-deref and explicitly.",
+    },
+}
@@ -0,0 +1,21 @@
+// The values returned by the method calls of the benchmark tell which method the lookup through
+// the derefs resolved to.
+
+#[test]
+fn deref() {
+    assert_eq!(
+        deref::bench::run(),
+        vec![
+            ("structs::lib::fat::Fat::method", 1),
+            ("structs::lib::fat::Fat::method", 2),
+            ("structs::lib::fat::Fat::method", 3),
+            ("structs::lib::fat::Fat::method", 4),
+            // Fat(4) reached through DerefMut.
+            ("structs::lib::fat::Fat::another_method", 5),
+            ("structs::lib::fat::Fat::method", 6),
+            // The method of Shadow, rather than that of the Fat(7) it dereferences to.
+            ("deref::lib::Shadow::method", 1007),
+            ("structs::lib::fat::Fat::method", 7),
+        ]
+    );
+}
@@ -0,0 +1,270 @@
+// Drop glue
+//
+// Dropping a value calls its drop glue, core::ptr::drop_in_place::<T>, which rustc generates for
+// each type T: it calls the Drop implementation of T, if any, and then drops each field of T in
+// turn. The drops are not written anywhere: a value is dropped at the end of the scope of its
+// variable, when its variable is assigned another value, at the end of the statement of a
+// temporary, or while unwinding, and the standard library drops values on behalf of its callers,
+// e.g., in mem::drop or Vec::truncate. A Box<dyn Trait> drops its object by the drop_in_place of
+// the object's vtable. Values that are moved out of a variable are dropped by their new owner, and
+// values in a ManuallyDrop or given to mem::forget are not dropped at all.
+
+pub mod lib {
+    use std::cell::RefCell;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+    use traits::lib::FooTrait;
+
+    thread_local! {
+        // The Drop implementations that have run on the thread, in order, each with its value.
+        // The log is per thread so that threads that drop values at the same time, e.g., tests
+        // run in parallel, do not see each other's drops.
+        pub static DROPPED: RefCell<Vec<(&'static str, i64)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn log(target: &'static str, value: i64) {
+        DROPPED.with_borrow_mut(|dropped| dropped.push((target, value)));
+    }
+
+    // Logs the value of its Fat when dropped.
+    pub struct Holder(pub Fat);
+
+    impl Drop for Holder {
+        fn drop(&mut self) {
+            // static function call
+            // drop_glue::lib::log
+            log(
+                "drop_glue::lib::{impl Drop for Holder}::drop",
+                i64::from(self.0 .0),
+            );
+        }
+    }
+
+    impl FooTrait for Holder {
+        fn method(&self) -> u32 {
+            self.0 .0
+        }
+    }
+
+    // Logs 100 when dropped.
+    pub struct Guard(pub Thin);
+
+    impl Drop for Guard {
+        fn drop(&mut self) {
+            // static function call
+            // drop_glue::lib::log
+            log("drop_glue::lib::{impl Drop for Guard}::drop", 100);
+        }
+    }
+
+    impl FooTrait for Guard {
+        fn method(&self) -> u32 {
+            100
+        }
+    }
+
+    // Logs 10000 when dropped, before its fields are dropped in turn.
+    pub struct Outer {
+        pub holder: Holder,
+        pub guard: Guard,
+    }
+
+    impl Drop for Outer {
+        fn drop(&mut self) {
+            // static function call
+            // drop_glue::lib::log
+            log("drop_glue::lib::{impl Drop for Outer}::drop", 10000);
+        }
+    }
+
+    // A type without a Drop implementation, whose drop glue only drops its fields. Its fields can
+    // be moved out of it, which types with a Drop implementation do not allow.
+    pub struct Pair(pub Holder, pub Guard);
+
+    // Logs 1000000 when dropped, which it never is.
+    pub struct Leaked(pub Thin);
+
+    impl Drop for Leaked {
+        fn drop(&mut self) {
+            // static function call
+            // drop_glue::lib::log
+            log("drop_glue::lib::{impl Drop for Leaked}::drop", 1000000);
+        }
+    }
+}
+
+pub mod bench {
+    use crate::lib::Guard;
+    use crate::lib::Holder;
+    use crate::lib::Leaked;
+    use crate::lib::Outer;
+    use crate::lib::Pair;
+    use crate::lib::DROPPED;
+    use std::mem;
+    use std::mem::ManuallyDrop;
+    use std::panic;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+    use traits::lib::FooTrait;
+
+    // Returns the drops of the benchmark in order, each labelled by the Drop implementation that
+    // ran and valued by the value it logged, which tells which value was dropped.
+    pub fn run() -> Vec<(&'static str, i64)> {
+        // Empties the log of the thread, in case a previous run was interrupted.
+        DROPPED.take();
+
+        {
+            let _holder = Holder(Fat(1));
+
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Holder
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // The end of the scope of a variable drops its value.
+        }
+
+        let mut holder = Holder(Fat(2));
+        let first = holder.0 .0;
+
+        // instance method call (trait - std::ops::Drop::drop)
+        // drop_glue::lib::Holder
+        // points to drop_glue::lib::{impl Drop for Holder}::drop
+        // Assigning a variable drops its old value.
+        holder = Holder(Fat(3));
+
+        // static function call
+        // std::mem::drop
+        // instance method call (trait - std::ops::Drop::drop)
+        // drop_glue::lib::Holder
+        // points to drop_glue::lib::{impl Drop for Holder}::drop
+        // mem::drop takes ownership of the value, which the end of its scope drops.
+        drop(holder);
+
+        {
+            let mut holders = vec![Holder(Fat(4)), Holder(Fat(5)), Holder(Fat(6))];
+
+            // instance method call (inherent)
+            // std::vec::Vec::truncate
+            // instance method call (trait - std::ops::Drop::drop)
+            // [drop_glue::lib::Holder]
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // Truncating a vector drops the elements past its new length, in place.
+            holders.truncate(1);
+
+            // instance method call (trait - std::ops::Drop::drop)
+            // std::vec::Vec<drop_glue::lib::Holder>
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // The drop of a vector drops its remaining elements.
+        }
+
+        let mut slot = Some(Holder(Fat(7)));
+
+        // instance method call (inherent)
+        // std::option::Option::take
+        // instance method call (trait - std::ops::Drop::drop)
+        // std::option::Option<drop_glue::lib::Holder>
+        // points to drop_glue::lib::{impl Drop for Holder}::drop
+        // The taken value is a temporary, which is dropped at the end of the statement. The None
+        // left in 'slot' has nothing to drop.
+        slot.take();
+
+        // The scrutinee is a temporary, which lives to the end of the statement of the match.
+        let in_arm = match Holder(Fat(8)) {
+            Holder(ref fat) => fat.0 + DROPPED.with_borrow(Vec::len) as u32,
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Holder
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // The temporary is dropped after the arm has read its fat.
+        };
+
+        {
+            let pair = Pair(Holder(Fat(9)), Guard(Thin));
+            let _holder = pair.0;
+
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Holder
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // The holder moved out of 'pair' is dropped as the value of '_holder'.
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Guard
+            // points to drop_glue::lib::{impl Drop for Guard}::drop
+            // 'pair' is partially moved, so only its remaining field is dropped, not the pair.
+        }
+
+        let unwinding = || {
+            let _holder = Holder(Fat(10));
+
+            // static function call
+            // std::panic::resume_unwind
+            // Unwinds without running the panic hook, which would print a message.
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Holder
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // Unwinding drops the values of the variables of the closure on its way out.
+            panic::resume_unwind(Box::new(()));
+        };
+
+        // static function call
+        // std::panic::catch_unwind
+        // callback std::panic::catch_unwind: drop_glue::bench::run::{closure#0}
+        let unwound = panic::catch_unwind(unwinding);
+
+        {
+            let boxed: Box<dyn FooTrait> = Box::new(Holder(Fat(11)));
+
+            // instance method call (trait)
+            // traits::lib::FooTrait::method
+            // Dynamic dispatch on the object that the box is about to drop.
+            let _foo_value = boxed.method();
+
+            // instance method call (trait - std::ops::Drop::drop)
+            // Box<dyn traits::lib::FooTrait>
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // The box drops its object by the drop_in_place of the object's vtable. Guard
+            // implements FooTrait too, but no Guard is boxed.
+        }
+
+        {
+            let _outer = Outer {
+                holder: Holder(Fat(12)),
+                guard: Guard(Thin),
+            };
+
+            // instance method call (trait - std::ops::Drop::drop)
+            // drop_glue::lib::Outer
+            // points to drop_glue::lib::{impl Drop for Outer}::drop
+            // points to drop_glue::lib::{impl Drop for Holder}::drop
+            // points to drop_glue::lib::{impl Drop for Guard}::drop
+            // Nested drop glue: the drop glue of Outer calls the Drop implementation of Outer and
+            // then drops its fields, whose drop glue calls their Drop implementations.
+        }
+
+        // Neither of the following values is ever dropped: ManuallyDrop does not drop the value
+        // it wraps, and mem::forget takes ownership of its argument without dropping it. The
+        // Drop implementation of Leaked is never called.
+        {
+            let _manual = ManuallyDrop::new(Leaked(Thin));
+            mem::forget(Leaked(Thin));
+        }
+
+        let drops = DROPPED.take();
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
+        // This is here to ensure that the above calls are not optimized away as dead code.
+        println!(
+            "Just making sure no code is deemed dead by the compiler: {}",
+            first + in_arm + u32::from(unwound.is_err())
+        );
+
+        drops
+    }
+}
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "drop_glue" => bench::run {
+        category: "drop",
+        description: "Implicit calls of Drop implementations by the drop glue of values.",
+    },
+}
@@ -0,0 +1,43 @@
+// The drops of the benchmark, in order, tell which Drop implementations the drop glue called and
+// on which values. The drops are logged per thread, so that tests run in parallel do not see each
+// other's drops.
+
+#[test]
+fn drop_glue() {
+    assert_eq!(
+        drop_glue::bench::run(),
+        vec![
+            // End of scope, reassignment and mem::drop.
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 1),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 2),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 3),
+            // Truncation drops Fat(5) and Fat(6), the vector then drops Fat(4).
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 5),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 6),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 4),
+            // The taken value and the scrutinee of the match.
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 7),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 8),
+            // The holder moved out of the pair, then the guard left in it.
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 9),
+            ("drop_glue::lib::{impl Drop for Guard}::drop", 100),
+            // The holder of the unwinding closure.
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 10),
+            // The holder of the box, through the vtable.
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 11),
+            // Outer, then its holder and its guard.
+            ("drop_glue::lib::{impl Drop for Outer}::drop", 10000),
+            ("drop_glue::lib::{impl Drop for Holder}::drop", 12),
+            ("drop_glue::lib::{impl Drop for Guard}::drop", 100),
+            // The leaked values are never dropped, so Leaked logs nothing.
+        ]
+    );
+}
+
+// The benchmark empties the log of its thread first, so that it returns its own drops only, and
+// leaves it empty.
+#[test]
+fn drop_glue_twice() {
+    assert_eq!(drop_glue::bench::run(), drop_glue::bench::run());
+    assert!(drop_glue::lib::DROPPED.with_borrow(Vec::is_empty));
+}
@@ -43,14 +43,16 @@ pub mod lib {
     // resolve this method call.
     pub fn dynamic_generic<T>(x: &dyn GenericFooTrait<T>) -> T {
         // instance method call (trait)
-        // traits::lib::GenericTrait<T>::method
+        // traits::lib::GenericFooTrait<T>::method
         // Dynamic dispatch on generic trait object.
         GenericFooTrait::<T>::method(x)
     }
 }
 
 pub mod bench {
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which implementation each call dispatched to.
+    pub fn run() -> Vec<(&'static str, i64)> {
         use crate::lib::dynamic;
         use crate::lib::dynamic_ufcs;
         use crate::lib::dynamic_default;
@@ -108,10 +110,30 @@ pub mod bench {
             num5 += item.method();
         }
 
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
         // This is here to ensure that the above calls are not optimized away as dead code.
         println!(
             "Just making sure no code is deemed dead by the compiler: {}",
             num1 + num2 + num3 + num4 + num5
         );
+
+        vec![
+            ("dynamic_dispatch::lib::dynamic", num1 as i64),
+            ("dynamic_dispatch::lib::dynamic_ufcs", num2 as i64),
+            ("dynamic_dispatch::lib::dynamic_default", num3 as i64),
+            ("dynamic_dispatch::lib::dynamic_generic", num4 as i64),
+            // The sum of the calls of the loop.
+            ("traits::lib::FooTrait::method", num5 as i64),
+        ]
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "dynamic_dispatch" => bench::run {
+        category: "dynamic",
+        description: "Trait objects and dynamic dispatch.",
+    },
+}
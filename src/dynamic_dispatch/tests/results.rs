@@ -0,0 +1,19 @@
+// The values returned by the calls of the benchmark tell which implementations the calls
+// dispatched to, so that a change of method resolution is caught.
+
+#[test]
+fn dynamic_dispatch() {
+    assert_eq!(
+        dynamic_dispatch::bench::run(),
+        vec![
+            // FooTrait::method of Fat(10) returns 20, Fat::method returns 10.
+            ("dynamic_dispatch::lib::dynamic", 20),
+            ("dynamic_dispatch::lib::dynamic_ufcs", 20),
+            // Fat overrides the default method, which returns 0.
+            ("dynamic_dispatch::lib::dynamic_default", 1),
+            ("dynamic_dispatch::lib::dynamic_generic", 42),
+            // FooTrait::method of Fat(10) and of Thin, which returns 0.
+            ("traits::lib::FooTrait::method", 20),
+        ]
+    );
+}
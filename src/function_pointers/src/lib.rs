@@ -65,52 +65,77 @@ pub mod bench {
         // could also point to 'm1'.
         #[allow(dead_code)]
         pub fn m1(obj: &dyn FooTrait) -> u32 {
+            // instance method call (trait)
+            // traits::lib::FooTrait::method
+            // Dynamic dispatch inside a function that is never called.
             obj.method()
         }
 
         pub fn m2(obj: &dyn FooTrait) -> u32 {
+            // instance method call (trait)
+            // traits::lib::FooTrait::method
+            // Dynamic dispatch inside a function that is only called through a function pointer.
             obj.method()
         }
     }
 
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which function each pointer pointed to.
+    pub fn run() -> Vec<(&'static str, i64)> {
         let f = Fat(10);
 
         // static function call
         // function_pointers::lib::indirection
         // Pointed function is part of Fat's implementation (struct impl).
-        indirection(&f, Fat::method);
+        let num1 = indirection(&f, Fat::method);
 
         // static function call
         // function_pointers::lib::indirection
         // Pointed function is part of FooTrait's implementation by Fat (trait impl).
-        indirection(&f, FooTrait::method);
+        let num2 = indirection(&f, FooTrait::method);
 
         // static function call
         // function_pointers::lib::indirection
         // Pointed function is part of BarTrait's implementation by Fat (trait impl). The syntax
         // used to specify the method is slightly different than in the last testcase but normally
         // there should not be any significant difference. We include this case for completeness.
-        indirection(&f, <Fat as BarTrait>::method);
+        let num3 = indirection(&f, <Fat as BarTrait>::method);
 
         // static function call
         // function_pointers::lib::indirection_generic
         // Pointed function is generic.
-        indirection_generic(&f, Fat::method);
+        let num4 = indirection_generic(&f, Fat::method);
         // The following two calls should be covered by the 'indirection' testcases.
         // indirection_generic(&f, FooTrait::method);
         // indirection_generic(&f, <Fat as BarTrait>::method);
 
         // static function call
-        // function_pointers::lib::indirection_generic
+        // function_pointers::lib::indirection_trait_object
         // Pointed function accepts a trait object as an argument.
-        indirection_trait_object(&f, helpers::m2);
+        let num5 = indirection_trait_object(&f, helpers::m2);
 
         // static function call
         // function_pointers::lib::indirection_fn_trait
-        indirection_fn_trait(&f, &Fat::method);
+        let num6 = indirection_fn_trait(&f, &Fat::method);
         // The following two calls should be covered by the 'indirection' testcases.
         // indirection_fn_trait(&f, &BarTrait::method);
         // indirection_fn_trait(&f, &<Fat as FooTrait>::method);
+
+        vec![
+            ("function_pointers::lib::indirection", num1 as i64),
+            ("function_pointers::lib::indirection", num2 as i64),
+            ("function_pointers::lib::indirection", num3 as i64),
+            ("function_pointers::lib::indirection_generic", num4 as i64),
+            ("function_pointers::lib::indirection_trait_object", num5 as i64),
+            ("function_pointers::lib::indirection_fn_trait", num6 as i64),
+        ]
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "function_pointers" => bench::run {
+        category: "function_pointer",
+        description: "Function pointer and Fn trait instance calls.",
+    },
+}
@@ -0,0 +1,17 @@
+// The values returned by the calls of the benchmark tell which functions the pointers pointed to.
+
+#[test]
+fn function_pointers() {
+    // The methods of Fat(10) return 10 if inherent, 20 for FooTrait and 110 for BarTrait.
+    assert_eq!(
+        function_pointers::bench::run(),
+        vec![
+            ("function_pointers::lib::indirection", 10),
+            ("function_pointers::lib::indirection", 20),
+            ("function_pointers::lib::indirection", 110),
+            ("function_pointers::lib::indirection_generic", 10),
+            ("function_pointers::lib::indirection_trait_object", 20),
+            ("function_pointers::lib::indirection_fn_trait", 10),
+        ]
+    );
+}
@@ -36,6 +36,7 @@ impl <T: BoundTrait> Wrapper<T> {
     pub fn method_wrapper(&self) -> i32 {
         // instance method call (trait)
         // <T as generics::base::BoundTrait>::method
+        // instance <structs::lib::One>: <structs::lib::One as generics::base::BoundTrait>::method
         // Generic receiver method call inside generic struct.
         self.0.method()
     }
@@ -22,6 +22,7 @@ pub mod lib {
     pub fn monomorphized<T: BoundTrait>(arg: T) -> i32 {
         // instance method call (trait)
         // generics::base::BoundTrait::method
+        // instance <structs::lib::One>: <structs::lib::One as generics::base::BoundTrait>::method
         arg.method()
     }
 
@@ -30,6 +31,8 @@ pub mod lib {
     pub fn monomorphized_foreign_bound<T: ForeignBoundTrait>(arg: T) -> i32 {
         // instance method call (trait)
         // traits::lib::bounds::BoundTrait::method
+        // instance <generics::base::One>: <generics::base::One as traits::lib::bounds::BoundTrait>::method
+        // instance <generics::base::Two>: <generics::base::Two as traits::lib::bounds::BoundTrait>::method
         // This call is similar to that of 'monomorphized' but the trait that bounds type parameter
         // T is defined in a different package than the function and the structs implementing it.
         arg.method()
@@ -38,6 +41,7 @@ pub mod lib {
     pub fn impl_trait(arg: impl ForeignBoundTrait) -> i32 {
         // instance method call (trait)
         // traits::lib::bounds::BoundTrait::method
+        // instance <generics::base::One>: <generics::base::One as traits::lib::bounds::BoundTrait>::method
         // This call is equivalent to that of 'monomorphized_foreign_bound' as the compiler
         // produces the same ASM for both. However, produced MIR for the call differs slightly.
         arg.method()
@@ -48,6 +52,7 @@ pub mod lib {
     pub fn monomorphized_i32<T: GenericFooTrait<i32>>(arg: T) -> i32 {
         // instance method call (trait)
         // traits::lib::GenericFooTrait<i32>::method
+        // instance <generics::base::Two>: <generics::base::Two as traits::lib::GenericFooTrait<i32>>::method
         arg.method()
     }
 
@@ -58,6 +63,7 @@ pub mod lib {
     {
         // instance method call (trait)
         // traits::lib::GenericFooTrait<P>::method
+        // instance <generics::base::Two, i32>: <generics::base::Two as traits::lib::GenericFooTrait<i32>>::method
         arg.method()
     }
 }
@@ -80,7 +86,9 @@ pub mod bench {
         }
     }
     
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which implementation of the bound each instance called.
+    pub fn run() -> Vec<(&'static str, i64)> {
 
         // No dynamic dispatch should be needed to resolve calls inside 'monomorphized' and
         // 'monomorphized_foreign_bound' as the compiler monomorphizes it during code generation.
@@ -113,7 +121,7 @@ pub mod bench {
         let num6 = monomorphized_i32(Two);
 
         // static function call (monomorphized)
-        // generics::lib::monomorphized_where::<generics::base::Two>
+        // generics::lib::monomorphized_where::<generics::base::Two, i32>
         // Call of function with generic parameter type T bounded by GenericFooTrait<P> and
         // concrete parameter type generics::base::Two which implements GenericFooTrait<i32>.
         let num7 = monomorphized_where(Two);
@@ -128,10 +136,32 @@ pub mod bench {
         // Instance method call implemented on generic struct Wrapper<T>
         let num8 = wrapper.method_wrapper();
 
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <i32 as core::fmt::Display>::fmt
         // This is here to ensure that the above calls are not optimized away as dead code.
         println!(
             "Just making sure no code is deemed dead by the compiler: {}",
             num1 + num2 + num3 + num4 + num5 + num6 + num7 + num8
         );
+
+        vec![
+            ("generics::lib::monomorphized", num1 as i64),
+            ("generics::lib::monomorphized_foreign_bound", num2 as i64),
+            ("generics::lib::monomorphized_foreign_bound", num3 as i64),
+            ("generics::lib::monomorphized_foreign_bound", num4 as i64),
+            ("generics::lib::impl_trait", num5 as i64),
+            ("generics::lib::monomorphized_i32", num6 as i64),
+            ("generics::lib::monomorphized_where", num7 as i64),
+            ("generics::base::Wrapper::method_wrapper", num8 as i64),
+        ]
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "generics" => bench::run {
+        category: "generic",
+        description: "Generic trait bounded functions and structs that are monomorphized.",
+    },
+}
@@ -0,0 +1,21 @@
+// The values returned by the calls of the benchmark tell which implementations of their bounds the
+// instances called, so that a change of method resolution is caught.
+
+#[test]
+fn generics() {
+    assert_eq!(
+        generics::bench::run(),
+        vec![
+            ("generics::lib::monomorphized", 1),
+            // BoundTrait::method of One returns 1, that of Two returns 2.
+            ("generics::lib::monomorphized_foreign_bound", 1),
+            ("generics::lib::monomorphized_foreign_bound", 2),
+            ("generics::lib::monomorphized_foreign_bound", 2),
+            ("generics::lib::impl_trait", 1),
+            // GenericFooTrait<i32>::method of Two, implemented in generics::bench.
+            ("generics::lib::monomorphized_i32", 42),
+            ("generics::lib::monomorphized_where", 42),
+            ("generics::base::Wrapper::method_wrapper", 1),
+        ]
+    );
+}
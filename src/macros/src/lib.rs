@@ -24,6 +24,20 @@ pub mod lib {
         };
     }
 
+    // 'method_of' macro expands to a closure that calls Foo::method on $x, so the closure is
+    // defined by the expansion in the body of the invoking function, among its other closures.
+    #[macro_export]
+    macro_rules! method_of {
+        ( $x:expr ) => {
+            move || {
+                // instance method call (trait)
+                // structs::lib::fat::{impl FooTrait for type_of($x)}::method
+                // The caller is the closure of the expansion rather than the invoking function.
+                traits::lib::FooTrait::method($x)
+            }
+        };
+    }
+
     use macros_derive::Macros;
     use traits::lib::MacroTrait;
 
@@ -32,9 +46,12 @@ pub mod lib {
 }
 
 pub mod bench {
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The calls
+    // that the macro expands to are labelled by their target for each argument.
+    pub fn run() -> Vec<(&'static str, i64)> {
         use crate::foo;
         use crate::lib::MacroStruct;
+        use crate::method_of;
         use structs::lib::fat::Fat;
         use structs::lib::thin::Thin;
         use traits::lib::MacroTrait;
@@ -51,10 +68,36 @@ pub mod bench {
             num2 += result;
         }
 
+        let method = method_of!(&Fat(2000));
+
+        // instance method call (trait - std::ops::Fn::call)
+        // impl std::ops::Fn() -> u32
+        // points to macros::bench::run::{closure#0}
+        // Call of the closure that the expansion of 'method_of' defined.
+        let num3 = method();
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
         // This is here to ensure that the above calls are not optimized away as dead code.
         println!(
             "Just making sure no code is deemed dead by the compiler: {}",
-            num1 + num2
+            num1 + num2 + num3
         );
+
+        vec![
+            ("<macros::lib::MacroStruct as traits::lib::MacroTrait>::method", num1 as i64),
+            ("<structs::lib::fat::Fat as traits::lib::FooTrait>::method", results[0] as i64),
+            ("<structs::lib::thin::Thin as traits::lib::FooTrait>::method", results[1] as i64),
+            ("macros::bench::run::{closure#0}", num3 as i64),
+        ]
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "macros" => bench::run {
+        category: "macro",
+        description: "Function and method calls inside macros.",
+    },
+}
@@ -0,0 +1,28 @@
+// The values returned by the calls of the benchmark tell which implementations the calls generated
+// by macros resolved to.
+
+#[test]
+fn macros() {
+    assert_eq!(
+        macros::bench::run(),
+        vec![
+            // The derived method returns the derived 'another_method', 100.
+            (
+                "<macros::lib::MacroStruct as traits::lib::MacroTrait>::method",
+                100
+            ),
+            // FooTrait::method of Fat(1000) and of Thin.
+            (
+                "<structs::lib::fat::Fat as traits::lib::FooTrait>::method",
+                1010
+            ),
+            (
+                "<structs::lib::thin::Thin as traits::lib::FooTrait>::method",
+                0
+            ),
+            // The closure of the expansion of 'method_of', which calls FooTrait::method of
+            // Fat(2000).
+            ("macros::bench::run::{closure#0}", 2010),
+        ]
+    );
+}
@@ -0,0 +1,74 @@
+// Generates the 'PACKAGES' table of main, so that a new benchmark package is not added to main.rs
+// by hand. The table lists the 'BENCHMARKS' of the packages of the '[workspace.metadata.benchmark]'
+// table of the workspace manifest that main depends on, in the order of the metadata, except for
+// the support packages. Cargo builds only the packages that main depends on, so a new package is
+// still added to the dependencies of main, but to nothing else of main.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+fn main() {
+    let dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let manifest = dir.join("Cargo.toml");
+    let workspace_manifest = dir.join("../../Cargo.toml");
+    println!("cargo:rerun-if-changed={}", manifest.display());
+    println!("cargo:rerun-if-changed={}", workspace_manifest.display());
+
+    let dependencies = dependencies(&read(&manifest));
+    let mut table = String::from("static PACKAGES: &[&[Benchmark]] = &[\n");
+    for (name, category) in benchmark_packages(&read(&workspace_manifest)) {
+        if category == "support" || !dependencies.contains(&name) {
+            continue;
+        }
+        table.push_str(&format!("    {}::BENCHMARKS,\n", name));
+    }
+    table.push_str("];\n");
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("packages.rs");
+    fs::write(&out, table).unwrap_or_else(|e| panic!("cannot write {}: {}", out.display(), e));
+}
+
+fn read(path: &Path) -> String {
+    fs::read_to_string(path).unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e))
+}
+
+// The names of the '[dependencies]' of a manifest, one per line as in main's manifest.
+fn dependencies(manifest: &str) -> Vec<String> {
+    section(manifest, "[dependencies]")
+        .filter_map(|line| line.split('=').next())
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+        .collect()
+}
+
+// The name and category of each entry of the '[workspace.metadata.benchmark]' packages, one
+// '{ name = "...", category = "..." }' entry per line as in the workspace manifest.
+fn benchmark_packages(manifest: &str) -> Vec<(String, String)> {
+    let packages: Vec<(String, String)> = section(manifest, "[workspace.metadata.benchmark]")
+        .filter_map(|line| Some((value(line, "name")?, value(line, "category")?)))
+        .collect();
+    if packages.is_empty() {
+        panic!("the workspace lists no [workspace.metadata.benchmark] packages");
+    }
+    packages
+}
+
+// The lines of a section of a manifest, without comments and blank lines.
+fn section<'a>(manifest: &'a str, header: &'a str) -> impl Iterator<Item = &'a str> {
+    manifest
+        .lines()
+        .map(str::trim)
+        .skip_while(move |line| *line != header)
+        .skip(1)
+        .take_while(|line| !line.starts_with('['))
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+}
+
+// The string value of a key of an inline table, e.g., 'name' of '{ name = "main", ... }'.
+fn value(line: &str, key: &str) -> Option<String> {
+    let start = line.find(&format!("{} = \"", key))? + key.len() + 4;
+    let end = line[start..].find('"')?;
+    Some(line[start..start + end].to_string())
+}
@@ -1,29 +1,189 @@
+use registry::lib::Benchmark;
+
 mod helpers {
-    // Accepts a function pointer as its argument and calls the function it points to.
-    pub fn run_benchmark(bench: &fn () -> ()) {
+    use registry::lib::Benchmark;
+
+    // Calls the function of a benchmark, a function pointer of the benchmark's entry in the
+    // static table of its package.
+    pub fn run_benchmark(benchmark: &Benchmark) {
         // There is a dedicated function pointers benchmark. However, it does not hurt to test the
         // call-graph generator again at this point. This is part of the benchmark after all.
 
-        // TODO: Define which functions should be included in a call-graph for the call to 'bench'.
-        // All the functions with matching signatures that are available at this scope and are
-        // declared in the current crate or one of its dependencies should be included.
-        bench();
+        // function pointer call
+        // fn() -> Vec<(&'static str, i64)>
+        // points to static_dispatch::bench::run
+        // points to static_dispatch::bench_method_lookup::run
+        // points to generics::bench::run
+        // points to dynamic_dispatch::bench::run
+        // points to function_pointers::bench::run
+        // points to conditionally_compiled::bench::run
+        // points to macros::bench::run
+        // points to closures::bench::run
+        // points to async_await::bench::run
+        // points to operators::bench::run
+        // points to deref::bench::run
+        // points to drop_glue::bench::run
+        // The pointer is read from a static table: the 'BENCHMARKS' of each package, which
+        // registry::benchmarks! builds from the package's registrations, and which 'PACKAGES'
+        // collects. It points to the 'run' functions registered in the tables. Every function of
+        // the same type that is declared in this crate or one of its dependencies has a matching
+        // signature and might be reported by an analysis based on types.
+        (benchmark.run)();
+    }
+}
+
+// The benchmarks registered by the benchmark packages, in the order in which they run: the static
+// 'PACKAGES' table that build.rs generates from the workspace metadata and the dependencies of main.
+include!(concat!(env!("OUT_DIR"), "/packages.rs"));
+
+// Selection of the benchmarks to run from the command line:
+//
+//     main                          runs all benchmarks
+//     main --list                   lists the benchmarks with their package, category and
+//                                   description
+//     main <name>...                runs the named benchmarks
+//     main --category <category>    runs the benchmarks of a category, e.g., 'dynamic'
+//
+// Options can be combined, e.g., 'main --list --category static' lists the static benchmarks.
+mod cli {
+    use std::process;
+
+    use registry::lib::Benchmark;
+
+    use crate::helpers;
+    use crate::PACKAGES;
+
+    const USAGE: &str = "usage: main [--list] [--category <category>] [<benchmark>...]";
 
+    // Runs, or lists, the benchmarks selected by the command line arguments.
+    pub fn run(args: &[String]) {
+        let mut list = false;
+        let mut category = None;
+        let mut names = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--list" => list = true,
+                "--category" => match args.next() {
+                    Some(value) => category = Some(value.as_str()),
+                    None => {
+                        eprintln!("missing value of option --category\n{}", USAGE);
+                        process::exit(2);
+                    }
+                },
+                "--help" | "-h" => {
+                    println!("{}", USAGE);
+                    return;
+                }
+                name if name.starts_with("--") => {
+                    eprintln!("unknown option '{}'\n{}", name, USAGE);
+                    process::exit(2);
+                }
+                name => names.push(name),
+            }
+        }
+        for name in &names {
+            // static function call
+            // main::cli::find
+            if find(name).is_none() {
+                eprintln!("unknown benchmark '{}', see 'main --list'", name);
+                process::exit(2);
+            }
+        }
+        if let Some(category) = category {
+            // static function call
+            // main::cli::is_category
+            if !is_category(category) {
+                eprintln!(
+                    "unknown category '{}', see 'main --list'\n{}",
+                    category, USAGE
+                );
+                process::exit(2);
+            }
+        }
+
+        if list {
+            println!(
+                "{:<24} {:<24} {:<18} description",
+                "benchmark", "package", "category"
+            );
+        }
+        for benchmarks in PACKAGES {
+            for benchmark in benchmarks.iter() {
+                // static function call
+                // main::cli::is_selected
+                if !is_selected(benchmark, &names, category) {
+                    continue;
+                }
+                if list {
+                    println!(
+                        "{:<24} {:<24} {:<18} {}",
+                        benchmark.name,
+                        benchmark.package,
+                        benchmark.category,
+                        benchmark.description
+                    );
+                } else {
+                    // static function call
+                    // main::helpers::run_benchmark
+                    helpers::run_benchmark(benchmark);
+                }
+            }
+        }
+    }
+
+    // The benchmark of a name. Written without closures, whose calls from the standard library
+    // are not part of the ground truth.
+    fn find(name: &str) -> Option<&'static Benchmark> {
+        for benchmarks in PACKAGES {
+            for benchmark in benchmarks.iter() {
+                if benchmark.name == name {
+                    return Some(benchmark);
+                }
+            }
+        }
+        None
+    }
+
+    // Whether a category is the category of a registered benchmark. Written without closures, like
+    // 'find'.
+    fn is_category(category: &str) -> bool {
+        for benchmarks in PACKAGES {
+            for benchmark in benchmarks.iter() {
+                if benchmark.category == category {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // Whether a benchmark is one of the named ones, if any, and of the category, if any.
+    fn is_selected(benchmark: &Benchmark, names: &[&str], category: Option<&str>) -> bool {
+        let named = names.is_empty() || names.contains(&benchmark.name);
+        match category {
+            Some(category) => named && category == benchmark.category,
+            None => named,
+        }
     }
 }
 
 fn main() {
-    let benchmarks = [
-        static_dispatch::bench::run,
-        static_dispatch::bench_method_lookup::run,
-        generics::bench::run,
-        dynamic_dispatch::bench::run,
-        function_pointers::bench::run,
-        conditionally_compiled::bench::run,
-        macros::bench::run,
-    ];
-
-    for bench in benchmarks.into_iter() {
-        helpers::run_benchmark(bench);
+    // Without arguments, all registered benchmarks are run, so that the default entry point stays
+    // the same for the analyses.
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        // static function call
+        // main::cli::run
+        cli::run(&args);
+        return;
+    }
+
+    for benchmarks in PACKAGES {
+        for benchmark in benchmarks.iter() {
+            // static function call
+            // main::helpers::run_benchmark
+            helpers::run_benchmark(benchmark);
+        }
     }
 }
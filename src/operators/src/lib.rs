@@ -0,0 +1,196 @@
+// Operators
+//
+// Operator expressions are calls in disguise: 'a + b' calls Add::add(a, b), '-a' calls
+// Neg::neg(a), 'a[i]' calls Index::index(&a, i), or IndexMut::index_mut(&mut a, i) if the result
+// is mutated, 'a += b' calls AddAssign::add_assign(&mut a, b), and 'a == b' and 'a < b' call
+// PartialEq::eq(&a, &b) and PartialOrd::lt(&a, &b). The method is that of the implementation of
+// the operator's trait for the type of the (left) operand, given the type of the right one, e.g.,
+// structs::lib::fat::{impl Mul<Thin> for Fat}::mul for 'fat * thin'. It is resolved statically,
+// for generic operands once the caller is monomorphized, and for trait objects by the
+// implementation for the trait object type, which dispatches dynamically in turn. The operators of
+// the primitive types are built into the language and call no function.
+
+pub mod lib {
+    use std::ops::Add;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+    use traits::lib::FooTrait;
+
+    // Adds two values of any type that can be added.
+    pub fn sum<T: Add<Output = T>>(a: T, b: T) -> T {
+        // instance method call (trait)
+        // std::ops::Add::add
+        // instance <structs::lib::fat::Fat>: <structs::lib::fat::Fat as std::ops::Add>::add
+        // instance <structs::lib::thin::Thin>: <structs::lib::thin::Thin as std::ops::Add>::add
+        // Addition of a generic type, which calls the Add implementation of each instance.
+        a + b
+    }
+
+    // A trait of the package, so that the package can implement the operators for its trait
+    // objects.
+    pub trait Weigh {
+        fn weight(&self) -> u32;
+    }
+
+    impl Weigh for Fat {
+        fn weight(&self) -> u32 {
+            // instance method call (trait)
+            // structs::lib::fat::{impl FooTrait for Fat}::method
+            FooTrait::method(self)
+        }
+    }
+
+    impl Weigh for Thin {
+        fn weight(&self) -> u32 {
+            1
+        }
+    }
+
+    // Adds the weights of two trait objects, whatever their types.
+    impl<'a> Add for &'a dyn Weigh {
+        type Output = u32;
+
+        fn add(self, other: &'a dyn Weigh) -> u32 {
+            // instance method call (trait)
+            // operators::lib::Weigh::weight
+            // Dynamic dispatch on the left operand of the addition.
+            let left = self.weight();
+
+            // instance method call (trait)
+            // operators::lib::Weigh::weight
+            // Dynamic dispatch on the right operand of the addition.
+            let right = other.weight();
+
+            left + right
+        }
+    }
+}
+
+pub mod bench {
+    use crate::lib::sum;
+    use crate::lib::Weigh;
+    use structs::lib::fat::Fat;
+    use structs::lib::thin::Thin;
+
+    // Returns the value of each annotated call, labelled by the target of the call. The values
+    // tell which operators the expressions called.
+    pub fn run() -> Vec<(&'static str, i64)> {
+        // instance method call (trait)
+        // structs::lib::fat::{impl Add for Fat}::add
+        // Addition of two values, which are moved into 'add'.
+        let num1 = (Fat(1) + Fat(2)).0;
+
+        let a = Fat(10);
+        let b = Fat(20);
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl Add<&Fat> for &Fat}::add
+        // Addition of two references, which leaves the operands to be used again. The
+        // implementation for references is not the one for values.
+        let num2 = (&a + &b).0;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl Sub for Fat}::sub
+        let num3 = (Fat(20) - Fat(5)).0;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl Neg for Fat}::neg
+        // Unary operator.
+        let num4 = -Fat(7);
+
+        // instance method call (trait)
+        // structs::lib::thin::{impl Not for Thin}::not
+        // Unary operator on a unit struct.
+        let num5 = !Thin;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl Mul<Thin> for Fat}::mul
+        // The operands are of different types: the implementation is chosen by both.
+        let num6 = (Fat(4) * Thin).0;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl Index<usize> for Fat}::index
+        // Indexing that is read calls Index::index.
+        let num7 = a[0];
+
+        let mut c = Fat(1);
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl IndexMut<usize> for Fat}::index_mut
+        // Indexing that is assigned to calls IndexMut::index_mut.
+        c[0] = 40;
+        let num8 = c.0;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl AddAssign<u32> for Fat}::add_assign
+        // Compound assignment, which takes the left operand by mutable reference.
+        c += 2;
+        let num9 = c.0;
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl PartialEq for Fat}::eq
+        // Comparison, which takes both operands by reference.
+        let num10 = a == Fat(10);
+
+        // instance method call (trait)
+        // structs::lib::fat::{impl PartialOrd for Fat}::lt
+        // Comparison with a method that Fat implements rather than the one the trait provides.
+        let num11 = a < b;
+
+        // static function call (monomorphized)
+        // operators::lib::sum::<structs::lib::fat::Fat>
+        let num12 = sum(Fat(5), Fat(6)).0;
+
+        // static function call (monomorphized)
+        // operators::lib::sum::<structs::lib::thin::Thin>
+        let thin = sum(Thin, Thin);
+
+        // instance method call (trait)
+        // structs::lib::thin::{impl Not for Thin}::not
+        let num13 = !thin;
+
+        let fat: &dyn Weigh = &Fat(30);
+        let thin: &dyn Weigh = &Thin;
+
+        // instance method call (trait)
+        // operators::lib::{impl Add for &dyn Weigh}::add
+        // Addition of two trait objects, whose implementation dispatches dynamically on each.
+        let num14 = fat + thin;
+
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <i64 as core::fmt::Display>::fmt
+        // This is here to ensure that the above calls are not optimized away as dead code.
+        println!(
+            "Just making sure no code is deemed dead by the compiler: {}",
+            i64::from(num1 + num2 + num3 + num6 + num7 + num8 + num9 + num12 + num14)
+                + num4
+                + i64::from(num5 && num10 && num11 && num13)
+        );
+
+        vec![
+            ("structs::lib::fat::{impl Add for Fat}::add", num1 as i64),
+            ("structs::lib::fat::{impl Add<&Fat> for &Fat}::add", num2 as i64),
+            ("structs::lib::fat::{impl Sub for Fat}::sub", num3 as i64),
+            ("structs::lib::fat::{impl Neg for Fat}::neg", num4),
+            ("structs::lib::thin::{impl Not for Thin}::not", num5 as i64),
+            ("structs::lib::fat::{impl Mul<Thin> for Fat}::mul", num6 as i64),
+            ("structs::lib::fat::{impl Index<usize> for Fat}::index", num7 as i64),
+            ("structs::lib::fat::{impl IndexMut<usize> for Fat}::index_mut", num8 as i64),
+            ("structs::lib::fat::{impl AddAssign<u32> for Fat}::add_assign", num9 as i64),
+            ("structs::lib::fat::{impl PartialEq for Fat}::eq", num10 as i64),
+            ("structs::lib::fat::{impl PartialOrd for Fat}::lt", num11 as i64),
+            ("operators::lib::sum::<structs::lib::fat::Fat>", num12 as i64),
+            ("structs::lib::thin::{impl Not for Thin}::not", num13 as i64),
+            ("operators::lib::{impl Add for &dyn Weigh}::add", num14 as i64),
+        ]
+    }
+}
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "operators" => bench::run {
+        category: "operator",
+        description: "Implicit calls of the methods of operator traits.",
+    },
+}
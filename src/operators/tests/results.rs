@@ -0,0 +1,30 @@
+// The values returned by the operator expressions of the benchmark tell which implementations of
+// the operator traits they called.
+
+#[test]
+fn operators() {
+    assert_eq!(
+        operators::bench::run(),
+        vec![
+            // 1 + 2, and 10 + 20 by reference.
+            ("structs::lib::fat::{impl Add for Fat}::add", 3),
+            ("structs::lib::fat::{impl Add<&Fat> for &Fat}::add", 30),
+            ("structs::lib::fat::{impl Sub for Fat}::sub", 15),
+            ("structs::lib::fat::{impl Neg for Fat}::neg", -7),
+            ("structs::lib::thin::{impl Not for Thin}::not", 1),
+            // Multiplying by Thin doubles Fat(4).
+            ("structs::lib::fat::{impl Mul<Thin> for Fat}::mul", 8),
+            ("structs::lib::fat::{impl Index<usize> for Fat}::index", 10),
+            // The field assigned through index_mut, then incremented by 2.
+            ("structs::lib::fat::{impl IndexMut<usize> for Fat}::index_mut", 40),
+            ("structs::lib::fat::{impl AddAssign<u32> for Fat}::add_assign", 42),
+            // Fat(10) == Fat(10) and Fat(10) < Fat(20).
+            ("structs::lib::fat::{impl PartialEq for Fat}::eq", 1),
+            ("structs::lib::fat::{impl PartialOrd for Fat}::lt", 1),
+            ("operators::lib::sum::<structs::lib::fat::Fat>", 11),
+            ("structs::lib::thin::{impl Not for Thin}::not", 1),
+            // FooTrait::method of Fat(30), plus the weight of Thin.
+            ("operators::lib::{impl Add for &dyn Weigh}::add", 41),
+        ]
+    );
+}
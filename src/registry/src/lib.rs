@@ -0,0 +1,45 @@
+// Registration of the benchmarks. Each benchmark package declares its benchmarks with the
+// 'benchmarks!' macro, which builds the static 'BENCHMARKS' table of the package, and 'main' runs
+// the benchmarks of the tables of its dependencies.
+
+pub mod lib {
+    // A benchmark: its name, the package that defines it, the category of the calls it exercises,
+    // a description and the function that runs it.
+    pub struct Benchmark {
+        pub name: &'static str,
+        pub package: &'static str,
+        pub category: &'static str,
+        pub description: &'static str,
+        pub run: fn() -> Vec<(&'static str, i64)>,
+    }
+}
+
+// Declares the benchmarks of a package as its static 'BENCHMARKS' table, e.g.,
+//
+//     registry::benchmarks! {
+//         "dynamic_dispatch" => bench::run {
+//             category: "dynamic",
+//             description: "Trait objects and dynamic dispatch.",
+//         },
+//     }
+//
+// The package of the benchmarks is the one that invokes the macro.
+#[macro_export]
+macro_rules! benchmarks {
+    ($($name:literal => $run:path {
+        category: $category:literal,
+        description: $description:literal $(,)?
+    }),* $(,)?) => {
+        pub static BENCHMARKS: &[$crate::lib::Benchmark] = &[
+            $(
+                $crate::lib::Benchmark {
+                    name: $name,
+                    package: env!("CARGO_PKG_NAME"),
+                    category: $category,
+                    description: $description,
+                    run: $run,
+                },
+            )*
+        ];
+    };
+}
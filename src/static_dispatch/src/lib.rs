@@ -1,5 +1,6 @@
 pub mod bench {
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call.
+    pub fn run() -> Vec<(&'static str, i64)> {
         use structs::lib::One;
         use structs::lib::Two;
 
@@ -32,16 +33,29 @@ pub mod bench {
         // Same name and signature as structs::lib::One::method_2 but different definition path.
         let num5 = two.method_2();
 
+        // static function call
+        // std::io::_print
+        // callback core::fmt::write: <i32 as core::fmt::Display>::fmt
         // This is here to ensure that the above calls are not optimized away as dead code.
         println!(
             "Just making sure no code is deemed dead by the compiler: {}",
             num1 + num2 + num3 + num4 + num5
         );
+
+        vec![
+            ("structs::lib::One::method_1", num1 as i64),
+            ("structs::lib::One::method_2", num2 as i64),
+            ("structs::lib::One::method_2", num3 as i64),
+            ("structs::lib::Two::method_1", num4 as i64),
+            ("structs::lib::Two::method_2", num5 as i64),
+        ]
     }
 }
 
 pub mod bench_method_lookup {
-    pub fn run() {
+    // Returns the value of each annotated call, labelled by the target of the call. The methods
+    // of Fat that share a name return different values, which tells the outcome of method lookup.
+    pub fn run() -> Vec<(&'static str, i64)> {
         // Traits FooTrait, BarTrait and BazTrait are implemented by Fat.
         // BazTrait is not imported in the current scope and thus is not visible.
         use structs::lib::fat::Fat;
@@ -116,11 +130,45 @@ pub mod bench_method_lookup {
             // structs::lib::fat::{impl BazTrait for Fat}::another_method
             let num10= <Fat as BazTrait>::another_method(&fat);
 
+            // static function call
+            // std::io::_print
+            // callback core::fmt::write: <u32 as core::fmt::Display>::fmt
             // This is here to ensure that the above calls are not optimized away as dead code.
             println!(
                 "Just making sure no code is deemed dead by the compiler: {}",
                 num1 + num2 + num3 + num4 + num5 + num6 + num7 + num8 + num9 + num10
             );
+
+            vec![
+                ("structs::lib::fat::Fat::method", num1 as i64),
+                ("<structs::lib::fat::Fat as traits::lib::FooTrait>::method", num2 as i64),
+                ("structs::lib::fat::Fat::default_method_no_self", num3 as i64),
+                (
+                    "<structs::lib::fat::Fat as traits::lib::DefaultTrait>::default_method_no_self",
+                    num4 as i64,
+                ),
+                ("traits::lib::DefaultTrait::default_method_no_self", num5 as i64),
+                ("<structs::lib::fat::Fat as traits::lib::BarTrait>::method", num6 as i64),
+                ("<structs::lib::fat::Fat as traits::lib::BarTrait>::another_method", num7 as i64),
+                (
+                    "<structs::lib::fat::Fat as traits::lib::BarTrait>::yet_another_method",
+                    num8 as i64,
+                ),
+                ("<structs::lib::fat::Fat as traits::lib::BarTrait>::another_method", num9 as i64),
+                ("<structs::lib::fat::Fat as traits::lib::BazTrait>::another_method", num10 as i64),
+            ]
         }
     }
 }
+
+// The benchmarks of the package, which 'main' runs.
+registry::benchmarks! {
+    "static_dispatch" => bench::run {
+        category: "static",
+        description: "Static and instance calls of inherent and trait methods.",
+    },
+    "bench_method_lookup" => bench_method_lookup::run {
+        category: "static",
+        description: "Method lookup among inherent methods and the methods of several traits.",
+    },
+}
@@ -0,0 +1,61 @@
+// The values returned by the calls of the benchmarks tell which functions the calls resolved to,
+// so that a change of method resolution is caught.
+
+#[test]
+fn static_dispatch() {
+    assert_eq!(
+        static_dispatch::bench::run(),
+        vec![
+            ("structs::lib::One::method_1", 1),
+            ("structs::lib::One::method_2", 2),
+            ("structs::lib::One::method_2", 2),
+            ("structs::lib::Two::method_1", 1),
+            // Two::method_2 increments the number before returning it.
+            ("structs::lib::Two::method_2", 2),
+        ]
+    );
+}
+
+#[test]
+fn method_lookup() {
+    // The methods of Fat(100) return 100 plus 0 to 2 if inherent, 10 for FooTrait, 100 to 102
+    // for BarTrait and 1001 for BazTrait.
+    assert_eq!(
+        static_dispatch::bench_method_lookup::run(),
+        vec![
+            ("structs::lib::fat::Fat::method", 100),
+            (
+                "<structs::lib::fat::Fat as traits::lib::FooTrait>::method",
+                110
+            ),
+            ("structs::lib::fat::Fat::default_method_no_self", 1),
+            (
+                "<structs::lib::fat::Fat as traits::lib::DefaultTrait>::default_method_no_self",
+                2,
+            ),
+            ("traits::lib::DefaultTrait::default_method_no_self", 0),
+            (
+                "<structs::lib::fat::Fat as traits::lib::BarTrait>::method",
+                200
+            ),
+            // Not the inherent 'another_method(&mut self)', which returns 101.
+            (
+                "<structs::lib::fat::Fat as traits::lib::BarTrait>::another_method",
+                201
+            ),
+            // Not the private inherent 'yet_another_method', which returns 102.
+            (
+                "<structs::lib::fat::Fat as traits::lib::BarTrait>::yet_another_method",
+                202
+            ),
+            (
+                "<structs::lib::fat::Fat as traits::lib::BarTrait>::another_method",
+                201
+            ),
+            (
+                "<structs::lib::fat::Fat as traits::lib::BazTrait>::another_method",
+                1101
+            ),
+        ]
+    );
+}
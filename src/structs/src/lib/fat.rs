@@ -1,8 +1,19 @@
+use std::cmp::Ordering;
+use std::ops::Add;
+use std::ops::AddAssign;
+use std::ops::Index;
+use std::ops::IndexMut;
+use std::ops::Mul;
+use std::ops::Neg;
+use std::ops::Sub;
+
 use traits::lib::FooTrait;
 use traits::lib::BarTrait;
 use traits::lib::BazTrait;
 use traits::lib::DefaultTrait;
 
+use crate::lib::thin::Thin;
+
 pub struct Fat(pub u32);
 
 impl Fat {
@@ -65,3 +76,90 @@ impl DefaultTrait for Fat {
         2
     }
 }
+
+// The operators of Fat, which operator expressions call implicitly, e.g., 'a + b' calls Add::add.
+impl Add for Fat {
+    type Output = Fat;
+
+    fn add(self, other: Fat) -> Fat {
+        Fat(self.0 + other.0)
+    }
+}
+
+// Adds references without consuming the operands.
+impl<'a> Add<&'a Fat> for &'a Fat {
+    type Output = Fat;
+
+    fn add(self, other: &'a Fat) -> Fat {
+        Fat(self.0 + other.0)
+    }
+}
+
+impl Sub for Fat {
+    type Output = Fat;
+
+    fn sub(self, other: Fat) -> Fat {
+        Fat(self.0 - other.0)
+    }
+}
+
+impl Neg for Fat {
+    type Output = i64;
+
+    fn neg(self) -> i64 {
+        -i64::from(self.0)
+    }
+}
+
+// Multiplies by a value of another type.
+impl Mul<Thin> for Fat {
+    type Output = Fat;
+
+    fn mul(self, _: Thin) -> Fat {
+        Fat(self.0 * 2)
+    }
+}
+
+impl AddAssign<u32> for Fat {
+    fn add_assign(&mut self, num: u32) {
+        self.0 += num;
+    }
+}
+
+// Fat has a single field, at index 0.
+impl Index<usize> for Fat {
+    type Output = u32;
+
+    fn index(&self, index: usize) -> &u32 {
+        match index {
+            0 => &self.0,
+            _ => panic!("index out of bounds: {}", index),
+        }
+    }
+}
+
+impl IndexMut<usize> for Fat {
+    fn index_mut(&mut self, index: usize) -> &mut u32 {
+        match index {
+            0 => &mut self.0,
+            _ => panic!("index out of bounds: {}", index),
+        }
+    }
+}
+
+impl PartialEq for Fat {
+    fn eq(&self, other: &Fat) -> bool {
+        self.0 == other.0
+    }
+}
+
+// 'lt' is implemented rather than provided by the trait, so that 'a < b' calls a method of Fat.
+impl PartialOrd for Fat {
+    fn partial_cmp(&self, other: &Fat) -> Option<Ordering> {
+        Some(self.0.cmp(&other.0))
+    }
+
+    fn lt(&self, other: &Fat) -> bool {
+        self.0 < other.0
+    }
+}
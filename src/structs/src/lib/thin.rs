@@ -1,3 +1,6 @@
+use std::ops::Add;
+use std::ops::Not;
+
 use traits::lib::FooTrait;
 use traits::lib::DefaultTrait;
 use traits::lib::GenericFooTrait;
@@ -23,3 +26,20 @@ impl GenericFooTrait<u32> for Thin {
 }
 
 impl DefaultTrait for Thin {}
+
+// The operators of Thin, which operator expressions call implicitly, e.g., '!a' calls Not::not.
+impl Add for Thin {
+    type Output = Thin;
+
+    fn add(self, _: Thin) -> Thin {
+        Thin
+    }
+}
+
+impl Not for Thin {
+    type Output = bool;
+
+    fn not(self) -> bool {
+        true
+    }
+}